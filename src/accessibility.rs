@@ -0,0 +1,82 @@
+// This software is licensed under Apache License 2.0 and distributed on an
+// "as-is" basis without warranties of any kind. See the LICENSE file for
+// details.
+
+//! Information about widgets for assistive technologies.
+
+use crate::widget::WidgetRef;
+use crate::Widget;
+
+/// What a widget is for, as told to assistive technologies like screen readers.
+///
+/// The landmark roles follow the [ARIA landmarks], and let users jump between the
+/// main parts of a window.
+///
+/// [ARIA landmarks]: https://www.w3.org/WAI/ARIA/apg/practices/landmark-regions/
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum Role {
+    /// No particular meaning. This is the default.
+    #[default]
+    Generic,
+    /// A set of related widgets, eg the fields of a form section.
+    Group,
+    /// A named part of the window, which isn't covered by a more specific role.
+    Region,
+    /// Links or buttons to move around the app.
+    Navigation,
+    /// The main content of the window.
+    Main,
+    /// Content at the top of the window, eg a title bar or logo.
+    Banner,
+    /// Supporting content, eg a sidebar.
+    Complementary,
+    /// Information about the window's content, eg a footer.
+    ContentInfo,
+    /// A search field and its controls.
+    Search,
+}
+
+/// What a widget tells assistive technologies about itself.
+///
+/// See [`Widget::accessibility`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Accessibility {
+    /// What the widget is for.
+    pub role: Role,
+    /// The name announced for the widget, if any.
+    pub label: Option<String>,
+}
+
+/// A widget in the tree exposed to assistive technologies.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AccessibilityNode {
+    /// What the widget is for.
+    pub role: Role,
+    /// The name announced for the widget, if any.
+    pub label: Option<String>,
+    /// The exposed widgets inside this one.
+    pub children: Vec<AccessibilityNode>,
+}
+
+impl AccessibilityNode {
+    /// The exposed widgets among `widget` and its descendants.
+    ///
+    /// Widgets which don't expose anything are left out, and their exposed descendants
+    /// take their place.
+    pub fn tree(widget: WidgetRef<'_, dyn Widget>) -> Vec<AccessibilityNode> {
+        let children: Vec<_> = widget
+            .children()
+            .into_iter()
+            .flat_map(AccessibilityNode::tree)
+            .collect();
+        match widget.accessibility() {
+            Some(Accessibility { role, label }) => vec![AccessibilityNode {
+                role,
+                label,
+                children,
+            }],
+            None => children,
+        }
+    }
+}
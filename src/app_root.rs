@@ -31,7 +31,7 @@ use crate::contexts::GlobalPassCtx;
 use crate::debug_logger::DebugLogger;
 use crate::ext_event::{ExtEventQueue, ExtEventSink, ExtMessage};
 use crate::kurbo::{Point, Size};
-use crate::piet::{Color, Piet, RenderContext};
+use crate::piet::{Color, Device, Piet, RenderContext};
 use crate::platform::{
     DialogInfo, WindowConfig, WindowSizePolicy, EXT_EVENT_IDLE_TOKEN, RUN_COMMANDS_TOKEN,
 };
@@ -39,10 +39,10 @@ use crate::testing::MockTimerQueue;
 use crate::text::TextFieldRegistration;
 use crate::widget::{FocusChange, StoreInWidgetMut, WidgetMut, WidgetRef, WidgetState};
 use crate::{
-    command as sys_cmd, ArcStr, BoxConstraints, Command, Env, Event, EventCtx, Handled,
-    InternalEvent, InternalLifeCycle, LayoutCtx, LifeCycle, LifeCycleCtx, MasonryWinHandler,
-    PaintCtx, PlatformError, Target, Widget, WidgetCtx, WidgetId, WidgetPod, WindowDescription,
-    WindowId,
+    command as sys_cmd, ArcStr, BoxConstraints, ChangedKeys, Command, Env, Event, EventCtx,
+    Handled, InputModality, InternalEvent, InternalLifeCycle, LayoutCtx, LifeCycle, LifeCycleCtx,
+    MasonryWinHandler, PaintCtx, PlatformError, Target, Widget, WidgetCtx, WidgetId, WidgetPod,
+    WindowDescription, WindowId,
 };
 
 /// The type of a function that will be called once an IME field is updated.
@@ -107,11 +107,18 @@ pub struct WindowRoot {
     pub(crate) last_anim: Option<Instant>,
     pub(crate) last_mouse_pos: Option<Point>,
     pub(crate) focus: Option<WidgetId>,
+    pub(crate) input_modality: InputModality,
+    // Whether `focus` was given with the keyboard.
+    pub(crate) focus_visible: bool,
+    // The cursor last set on `handle`.
+    pub(crate) cursor: Cursor,
     pub(crate) ext_event_sink: ExtEventSink,
     pub(crate) handle: WindowHandle,
     pub(crate) timers: HashMap<TimerToken, WidgetId>,
     // Used in unit tests - see `src/testing/mock_timer_queue.rs`
     pub(crate) mock_timer_queue: Option<MockTimerQueue>,
+    // Devices for offscreen painting, kept between paints - see `PaintCtx::paint_offscreen`.
+    pub(crate) offscreen_devices: Vec<Device>,
     pub(crate) transparent: bool,
     pub(crate) ime_handlers: Vec<(TextFieldToken, TextFieldRegistration)>,
     pub(crate) ime_focus_change: Option<Option<TextFieldToken>>,
@@ -336,6 +343,35 @@ impl AppRoot {
         }
     }
 
+    /// Apply the values of `theme` on top of the app's [`Env`], eg to reload a theme.
+    ///
+    /// Every widget receives [`LifeCycle::EnvChanged`] with the keys whose values
+    /// changed, and invalidates whatever depends on them. Returns those keys; nothing
+    /// is sent if no value changed.
+    pub fn merge_env(&mut self, theme: &Env) -> ChangedKeys {
+        let changed = {
+            let mut inner = self.inner.borrow_mut();
+            let inner = inner.deref_mut();
+            let changed = inner.env.merge(theme);
+            if !changed.is_empty() {
+                let event = LifeCycle::EnvChanged(changed.clone());
+                for win in inner.active_windows.values_mut() {
+                    win.lifecycle(
+                        &event,
+                        &mut inner.debug_logger,
+                        &mut inner.command_queue,
+                        &mut inner.action_queue,
+                        &inner.env,
+                        false,
+                    );
+                }
+            }
+            changed
+        };
+        self.run_commands();
+        changed
+    }
+
     /// Run any leftover commands from previous events.
     pub fn run_commands(&mut self) {
         self.process_commands_and_actions();
@@ -568,6 +604,8 @@ impl AppRoot {
                 &window.handle,
                 inner.main_window_id,
                 window.focus,
+                window.input_modality,
+                window.focus_visible,
             );
             fake_widget_state = window.root.state.clone();
 
@@ -878,10 +916,14 @@ impl WindowRoot {
             last_anim: None,
             last_mouse_pos: None,
             focus: None,
+            input_modality: InputModality::Pointer,
+            focus_visible: false,
+            cursor: Cursor::Arrow,
             ext_event_sink,
             handle,
             timers: HashMap::new(),
             mock_timer_queue,
+            offscreen_devices: Vec::new(),
             ime_handlers: Vec::new(),
             ime_focus_change: None,
         }
@@ -1030,6 +1072,11 @@ impl WindowRoot {
             Event::Internal(InternalEvent::MouseLeave) => self.last_mouse_pos = None,
             _ => (),
         }
+        match &event {
+            Event::MouseDown(_) | Event::Wheel(_) => self.input_modality = InputModality::Pointer,
+            Event::KeyDown(_) => self.input_modality = InputModality::Keyboard,
+            _ => (),
+        }
 
         let event = match event {
             Event::Timer(token) => {
@@ -1066,6 +1113,8 @@ impl WindowRoot {
                 &self.handle,
                 self.id,
                 self.focus,
+                self.input_modality,
+                self.focus_visible,
             );
             let mut notifications = VecDeque::new();
 
@@ -1105,11 +1154,13 @@ impl WindowRoot {
 
         if let Some(cursor) = &widget_state.cursor {
             self.handle.set_cursor(cursor);
+            self.cursor = cursor.clone();
         } else if matches!(
             event,
             Event::MouseMove(..) | Event::Internal(InternalEvent::MouseLeave)
         ) {
             self.handle.set_cursor(&Cursor::Arrow);
+            self.cursor = Cursor::Arrow;
         }
 
         if matches!(
@@ -1155,6 +1206,8 @@ impl WindowRoot {
             &self.handle,
             self.id,
             self.focus,
+            self.input_modality,
+            self.focus_visible,
         );
         let mut ctx = LifeCycleCtx {
             global_state: &mut global_state,
@@ -1281,6 +1334,8 @@ impl WindowRoot {
             &self.handle,
             self.id,
             self.focus,
+            self.input_modality,
+            self.focus_visible,
         );
         let mut layout_ctx = LayoutCtx {
             global_state: &mut global_state,
@@ -1349,7 +1404,10 @@ impl WindowRoot {
             &self.handle,
             self.id,
             self.focus,
+            self.input_modality,
+            self.focus_visible,
         );
+        global_state.offscreen_devices = Some(&mut self.offscreen_devices);
         let mut ctx = PaintCtx {
             render_ctx: piet,
             global_state: &mut global_state,
@@ -1431,6 +1489,7 @@ impl WindowRoot {
                     false,
                 );
                 self.focus = new;
+                self.focus_visible = self.input_modality == InputModality::Keyboard;
                 // check if the newly focused widget has an IME session, and
                 // notify the system if so.
                 //
@@ -264,6 +264,17 @@ impl BoxConstraints {
     }
 }
 
+/// Formats the constraints as `[min 0x200, max 400x200]`, eg for logs.
+impl std::fmt::Display for BoxConstraints {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "[min {}x{}, max {}x{}]",
+            self.min.width, self.min.height, self.max.width, self.max.height
+        )
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -366,4 +377,16 @@ mod tests {
 
         assert_eq!(BoxConstraints::UNBOUNDED.min(), Size::ZERO);
     }
+
+    #[test]
+    fn display() {
+        assert_eq!(
+            bc(0.0, 200.0, 400.0, 200.0).to_string(),
+            "[min 0x200, max 400x200]"
+        );
+        assert_eq!(
+            BoxConstraints::UNBOUNDED.to_string(),
+            "[min 0x0, max infxinf]"
+        );
+    }
 }
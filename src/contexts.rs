@@ -12,20 +12,23 @@ use std::time::Duration;
 
 use druid_shell::text::Event as ImeInvalidation;
 use druid_shell::{Cursor, Region, TimerToken, WindowHandle};
+use instant::Instant;
 use tracing::{error, trace, warn};
 
 use crate::action::{Action, ActionQueue};
 use crate::command::{Command, CommandQueue, Notification, SingleUse};
 use crate::debug_logger::DebugLogger;
 use crate::ext_event::ExtEventSink;
-use crate::piet::{Piet, PietText, RenderContext};
+use crate::kurbo::RoundedRectRadii;
+use crate::piet::{Device, ImageBuf, ImageFormat, Piet, PietText, RenderContext};
 use crate::platform::WindowDescription;
 use crate::promise::PromiseToken;
 use crate::testing::MockTimerQueue;
 use crate::text::{ImeHandlerRef, TextFieldRegistration};
 use crate::widget::{CursorChange, FocusChange, StoreInWidgetMut, WidgetMut, WidgetState};
 use crate::{
-    Affine, Env, Insets, Point, Rect, Size, Target, Vec2, Widget, WidgetId, WidgetPod, WindowId,
+    Affine, Env, Event, InputModality, Insets, Point, Rect, Size, Target, Vec2, Widget, WidgetId,
+    WidgetPod, WindowId,
 };
 
 /// A macro for implementing methods on multiple contexts.
@@ -55,11 +58,17 @@ pub(crate) struct GlobalPassCtx<'a> {
     pub(crate) timers: &'a mut HashMap<TimerToken, WidgetId>,
     // Used in Harness for unit tests - see `src/testing/mock_timer_queue.rs`
     pub(crate) mock_timer_queue: Option<&'a mut MockTimerQueue>,
+    /// Devices for offscreen painting which can be reused. Only set in the paint pass.
+    pub(crate) offscreen_devices: Option<&'a mut Vec<Device>>,
     pub(crate) window_id: WindowId,
     pub(crate) window: &'a WindowHandle,
     pub(crate) text: PietText,
     /// The id of the widget that currently has focus.
     pub(crate) focus_widget: Option<WidgetId>,
+    /// The kind of input the user last interacted with.
+    pub(crate) input_modality: InputModality,
+    /// Whether the focused widget got focus through the keyboard.
+    pub(crate) focus_visible: bool,
 }
 
 /// A context provided to implementors of [`StoreInWidgetMut`].
@@ -149,6 +158,16 @@ impl_context_method!(
             self.global_state.window
         }
 
+        /// The current time.
+        ///
+        /// Widgets that measure time between events, eg to estimate a pointer's
+        /// velocity, should use this rather than `Instant::now()`: in unit tests, it
+        /// reads the harness clock, which only moves through
+        /// [`TestHarness::move_timers_forward`](crate::testing::TestHarness::move_timers_forward).
+        pub fn now(&self) -> Instant {
+            self.global_state.now()
+        }
+
         /// Get the `WindowId` of the current window.
         pub fn window_id(&self) -> WindowId {
             self.global_state.window_id
@@ -170,6 +189,17 @@ impl_context_method!(
         pub fn skip_child(&self, child: &mut WidgetPod<impl Widget>) {
             child.mark_as_visited();
         }
+
+        /// The corner radii of the rounded area this widget is drawn in.
+        ///
+        /// Containers with rounded corners, like [`SizedBox`](crate::widget::SizedBox),
+        /// report them to their child with [`LayoutCtx::set_child_corner_radii`], so that
+        /// the child can clip its own content to match. The radii are zero otherwise.
+        ///
+        /// This is set by the parent during layout; before the first layout it is zero.
+        pub fn parent_corner_radii(&self) -> RoundedRectRadii {
+            self.widget_state.parent_corner_radii
+        }
     }
 );
 
@@ -277,6 +307,22 @@ impl_context_method!(
             self.widget_state.has_focus
         }
 
+        /// Whether this widget is focused, and should show it.
+        ///
+        /// This is `true` when the widget [`is_focused`](Self::is_focused) and got focus
+        /// from the keyboard, eg by pressing Tab, rather than from a click. Widgets
+        /// that draw a focus ring should use this, so the ring doesn't show up on every
+        /// click. Like the CSS `:focus-visible` selector, this is decided when focus
+        /// changes; later input doesn't hide or show the ring.
+        pub fn is_focus_visible(&self) -> bool {
+            self.is_focused() && self.global_state.focus_visible
+        }
+
+        /// The kind of input the user last interacted with in this window.
+        pub fn input_modality(&self) -> InputModality {
+            self.global_state.input_modality
+        }
+
         /// The disabled state of a widget.
         ///
         /// Returns `true` if this widget or any of its ancestors is explicitly disabled.
@@ -304,7 +350,7 @@ impl_context_method!(
     }
 );
 
-impl_context_method!(EventCtx<'_, '_>, {
+impl_context_method!(WidgetCtx<'_, '_>, EventCtx<'_, '_>, {
     /// Set the cursor icon.
     ///
     /// This setting will be retained until [`clear_cursor`] is called, but it will only take
@@ -411,6 +457,7 @@ impl_context_method!(WidgetCtx<'_, '_>, EventCtx<'_, '_>, LifeCycleCtx<'_, '_>,
     /// [`paint_rect`]: struct.WidgetPod.html#method.paint_rect
     pub fn request_paint(&mut self) {
         trace!("request_paint");
+        self.widget_state.needs_paint = true;
         self.widget_state.invalid.set_rect(
             self.widget_state.paint_rect() - self.widget_state.layout_rect().origin().to_vec2(),
         );
@@ -422,6 +469,7 @@ impl_context_method!(WidgetCtx<'_, '_>, EventCtx<'_, '_>, LifeCycleCtx<'_, '_>,
     /// [`paint`]: trait.Widget.html#tymethod.paint
     pub fn request_paint_rect(&mut self, rect: Rect) {
         trace!("request_paint_rect {}", rect);
+        self.widget_state.needs_paint = true;
         self.widget_state.invalid.add_rect(rect);
     }
 
@@ -456,6 +504,15 @@ impl_context_method!(WidgetCtx<'_, '_>, EventCtx<'_, '_>, LifeCycleCtx<'_, '_>,
         self.request_layout();
     }
 
+    /// Request that the focus chain be rebuilt.
+    ///
+    /// Widgets must call this method when they start or stop registering for focus
+    /// during [`LifeCycle::BuildFocusChain`].
+    pub fn request_focus_chain_update(&mut self) {
+        trace!("request_focus_chain_update");
+        self.widget_state.update_focus_chain = true;
+    }
+
     /// Set the disabled state for this widget.
     ///
     /// Setting this to `false` does not mean a widget is not still disabled; for instance it may
@@ -638,6 +695,27 @@ impl EventCtx<'_, '_> {
         // TODO: plumb mouse grab through to platform (through druid-shell)
     }
 
+    /// Send `event` to each of `children`, highest [z-index] first.
+    ///
+    /// Children with the same z-index receive the event in the order they are given.
+    /// If one of them handles the event, the remaining children don't receive it, so
+    /// a container whose children can overlap should use this instead of iterating
+    /// over its children directly.
+    ///
+    /// [z-index]: struct.WidgetPod.html#method.z_index
+    pub fn dispatch_by_z_index<'c, W: Widget + 'c>(
+        &mut self,
+        children: impl IntoIterator<Item = &'c mut WidgetPod<W>>,
+        event: &Event,
+        env: &Env,
+    ) {
+        let mut children: Vec<_> = children.into_iter().collect();
+        children.sort_by_key(|child| std::cmp::Reverse(child.state.z_index));
+        for child in children {
+            child.on_event(self, event, env);
+        }
+    }
+
     /// Set the event as "handled", which stops its propagation to other
     /// widgets.
     pub fn set_handled(&mut self) {
@@ -789,6 +867,51 @@ impl LayoutCtx<'_, '_> {
         self.widget_state.paint_insets = insets.nonnegative();
     }
 
+    /// Report how far this widget's content extends beyond its layout rect.
+    ///
+    /// This is for widgets that clip their content, so that a scrolling parent
+    /// can know the full size of the content, eg to size its scrollbars. Unlike
+    /// paint insets, this doesn't let the widget paint outside its bounds.
+    ///
+    /// The overflow is reset to zero before each layout pass. For more information,
+    /// see [`WidgetPod::content_overflow`].
+    ///
+    /// [`WidgetPod::content_overflow`]: struct.WidgetPod.html#method.content_overflow
+    pub fn set_content_overflow(&mut self, overflow: impl Into<Insets>) {
+        let overflow = overflow.into();
+        trace!("set_content_overflow {:?}", overflow);
+        self.widget_state.content_overflow = overflow.nonnegative();
+    }
+
+    /// Change the area of this widget which receives mouse events.
+    ///
+    /// Positive values shrink the area inside the layout rect, and negative ones grow
+    /// it past the layout rect, eg to make a small control easier to click. This
+    /// doesn't change the layout or painting. Mouse events only reach the widget if
+    /// its parent receives them, so the area is limited by the parent's own area.
+    ///
+    /// The inset is reset to zero before each layout pass.
+    pub fn set_hit_test_inset(&mut self, inset: impl Into<Insets>) {
+        let inset = inset.into();
+        trace!("set_hit_test_inset {:?}", inset);
+        self.widget_state.hit_test_inset = inset;
+    }
+
+    /// Set where this widget stands in the hit-testing order among its siblings.
+    ///
+    /// When siblings overlap, containers that dispatch events with
+    /// [`EventCtx::dispatch_by_z_index`] send them to the widget with the highest
+    /// z-index first, so it can handle a click before the ones below it.
+    ///
+    /// The z-index is reset to zero before each layout pass. It doesn't change the
+    /// paint order. For more information, see [`WidgetPod::z_index`].
+    ///
+    /// [`WidgetPod::z_index`]: struct.WidgetPod.html#method.z_index
+    pub fn set_z_index(&mut self, z_index: i32) {
+        trace!("set_z_index {}", z_index);
+        self.widget_state.z_index = z_index;
+    }
+
     /// Set an explicit baseline position for this widget.
     ///
     /// The baseline position is used to align widgets that contain text,
@@ -798,9 +921,27 @@ impl LayoutCtx<'_, '_> {
     ///
     /// The provided value should be the distance from the *bottom* of the
     /// widget to the baseline.
+    ///
+    /// This also sets the last baseline to the same value. Widgets with several
+    /// lines of text should call [`set_last_baseline_offset`] afterwards.
+    ///
+    /// [`set_last_baseline_offset`]: LayoutCtx::set_last_baseline_offset
     pub fn set_baseline_offset(&mut self, baseline: f64) {
         trace!("set_baseline_offset {}", baseline);
-        self.widget_state.baseline_offset = baseline
+        self.widget_state.baseline_offset = baseline;
+        self.widget_state.last_baseline_offset = baseline;
+    }
+
+    /// Set the position of the baseline of this widget's last line of text.
+    ///
+    /// Like [`set_baseline_offset`], the value is the distance from the *bottom*
+    /// of the widget. Containers can use it to align the end of a block of text,
+    /// rather than its start, with its siblings.
+    ///
+    /// [`set_baseline_offset`]: LayoutCtx::set_baseline_offset
+    pub fn set_last_baseline_offset(&mut self, baseline: f64) {
+        trace!("set_last_baseline_offset {}", baseline);
+        self.widget_state.last_baseline_offset = baseline;
     }
 
     /// Set the position of a child widget, in the paren't coordinate space. This
@@ -811,7 +952,7 @@ impl LayoutCtx<'_, '_> {
     pub fn place_child(&mut self, child: &mut WidgetPod<impl Widget>, origin: Point, env: &Env) {
         child.state.origin = origin;
         child.state.is_expecting_place_child_call = false;
-        let layout_rect = child.layout_rect();
+        let hit_rect = child.state.hit_rect();
 
         self.widget_state.local_paint_rect =
             self.widget_state.local_paint_rect.union(child.paint_rect());
@@ -822,13 +963,62 @@ impl LayoutCtx<'_, '_> {
             &mut child.inner,
             &mut child.state,
             self.global_state,
-            layout_rect,
+            hit_rect,
             self.mouse_pos,
             env,
         ) {
             self.widget_state.merge_up(&mut child.state);
         }
     }
+
+    /// Tell `child` the corner radii of the rounded area it is drawn in.
+    ///
+    /// The radii should be given in the child's coordinate space, eg reduced by the
+    /// width of any border between the two. The child can read them with
+    /// [`parent_corner_radii`](Self::parent_corner_radii).
+    ///
+    /// This should be called before `child.layout(...)`, so the value is up to date
+    /// during the child's layout.
+    pub fn set_child_corner_radii(
+        &mut self,
+        child: &mut WidgetPod<impl Widget>,
+        radii: impl Into<RoundedRectRadii>,
+    ) {
+        child.state.parent_corner_radii = radii.into();
+    }
+
+    /// Mark a child as stashed or not, depending on the constraints it's laid out with.
+    ///
+    /// This is for widgets which decide which children to show during layout, eg
+    /// depending on the space available. Stashed children keep their state, but must
+    /// not be laid out or painted, and aren't sent events.
+    ///
+    /// This should be called before laying out the children. Unlike
+    /// [`EventCtx::set_stashed`], it doesn't request another layout pass.
+    pub fn set_stashed(&mut self, child: &mut WidgetPod<impl Widget>, stashed: bool) {
+        if child.state.is_stashed != stashed {
+            child.state.is_stashed = stashed;
+            self.widget_state.update_focus_chain = true;
+        }
+    }
+
+    /// Check that `child` was placed with [`place_child`](Self::place_child) since it
+    /// was last laid out.
+    ///
+    /// The framework already performs this check once `layout` returns, but calling this
+    /// at the end of a widget's own layout method reports the error closer to its source,
+    /// eg in a branch that returns early. Panics in debug builds, and logs an error
+    /// in release builds.
+    pub fn debug_check_child_placed(&self, child: &WidgetPod<impl Widget>) {
+        if child.state.is_expecting_place_child_call {
+            debug_panic!(
+                "Error in widget #{}: child widget '{}' #{} was laid out but not placed. After calling WidgetPod::layout() on a child, a widget must call LayoutCtx::place_child() on it.",
+                self.widget_state.id.to_raw(),
+                child.inner.short_type_name(),
+                child.state.id.to_raw(),
+            );
+        }
+    }
 }
 
 impl PaintCtx<'_, '_, '_> {
@@ -850,6 +1040,16 @@ impl PaintCtx<'_, '_, '_> {
         &self.region
     }
 
+    /// The number of device pixels per logical pixel.
+    ///
+    /// This is read from the transform of the render context, so it accounts for the
+    /// window's scale factor as well as any scaling applied by ancestors. If the transform
+    /// scales each axis differently, this is the horizontal scale.
+    pub fn scale(&self) -> f64 {
+        let [a, b, ..] = self.render_ctx.current_transform().as_coeffs();
+        a.hypot(b)
+    }
+
     /// Creates a temporary `PaintCtx` with a new visible region, and calls
     /// the provided function with that `PaintCtx`.
     ///
@@ -868,6 +1068,106 @@ impl PaintCtx<'_, '_, '_> {
         self.z_ops.append(&mut child_ctx.z_ops);
     }
 
+    /// Paint into an offscreen bitmap instead of the window, and return the bitmap.
+    ///
+    /// The bitmap covers `rect`, in this widget's coordinates, at the current
+    /// [`scale`](Self::scale). `f` is called with a `PaintCtx` for the bitmap, with
+    /// the widget's usual coordinates, where the whole bitmap is visible; anything
+    /// painted outside of `rect` is lost.
+    ///
+    /// The offscreen device is kept by the window and reused by later calls.
+    ///
+    /// Returns `None`, without calling `f`, if `rect` is empty or the bitmap can't
+    /// be created.
+    pub fn paint_offscreen(
+        &mut self,
+        rect: Rect,
+        f: impl FnOnce(&mut PaintCtx),
+    ) -> Option<ImageBuf> {
+        let scale = self.scale();
+        let width = (rect.width() * scale).ceil() as usize;
+        let height = (rect.height() * scale).ceil() as usize;
+        if width == 0 || height == 0 {
+            return None;
+        }
+
+        // The device is taken out while it's in use, so that nested offscreen paints
+        // get a device of their own.
+        let reused_device = self
+            .global_state
+            .offscreen_devices
+            .as_mut()
+            .and_then(|devices| devices.pop());
+        let mut device = match reused_device.map(Ok).unwrap_or_else(Device::new) {
+            Ok(device) => device,
+            Err(e) => {
+                error!("Failed to create offscreen device: '{}'", e);
+                return None;
+            }
+        };
+        let image = self.paint_on_device(&mut device, rect, width, height, f);
+        if let Some(devices) = self.global_state.offscreen_devices.as_mut() {
+            devices.push(device);
+        }
+        image
+    }
+
+    fn paint_on_device(
+        &mut self,
+        device: &mut Device,
+        rect: Rect,
+        width: usize,
+        height: usize,
+        f: impl FnOnce(&mut PaintCtx),
+    ) -> Option<ImageBuf> {
+        let mut target = match device.bitmap_target(width, height, self.scale()) {
+            Ok(target) => target,
+            Err(e) => {
+                error!("Failed to create offscreen bitmap: '{}'", e);
+                return None;
+            }
+        };
+
+        {
+            let mut render_ctx = target.render_context();
+            render_ctx.transform(Affine::translate(-rect.origin().to_vec2()));
+            let region = Region::from(rect);
+            let mut ctx = PaintCtx {
+                render_ctx: &mut render_ctx,
+                global_state: self.global_state,
+                widget_state: self.widget_state,
+                z_ops: Vec::new(),
+                region: region.clone(),
+                depth: self.depth,
+            };
+            f(&mut ctx);
+
+            let mut z_ops = std::mem::take(&mut ctx.z_ops);
+            z_ops.sort_by_key(|k| k.z_index);
+            for z_op in z_ops {
+                ctx.with_child_ctx(region.clone(), |ctx| {
+                    ctx.with_save(|ctx| {
+                        ctx.render_ctx.transform(z_op.transform);
+                        (z_op.paint_func)(ctx);
+                    });
+                });
+            }
+
+            if let Err(e) = render_ctx.finish() {
+                error!("Failed to finish offscreen painting: '{}'", e);
+                return None;
+            }
+        }
+
+        match target.to_image_buf(ImageFormat::RgbaPremul) {
+            Ok(image) => Some(image),
+            Err(e) => {
+                error!("Failed to read offscreen bitmap: '{}'", e);
+                None
+            }
+        }
+    }
+
     /// Saves the current context, executes the closures, and restores the context.
     ///
     /// This is useful if you would like to transform or clip or otherwise
@@ -931,6 +1231,8 @@ impl<'a> GlobalPassCtx<'a> {
         window: &'a WindowHandle,
         window_id: WindowId,
         focus_widget: Option<WidgetId>,
+        input_modality: InputModality,
+        focus_visible: bool,
     ) -> Self {
         GlobalPassCtx {
             ext_event_sink,
@@ -939,9 +1241,12 @@ impl<'a> GlobalPassCtx<'a> {
             action_queue,
             timers,
             mock_timer_queue,
+            offscreen_devices: None,
             window,
             window_id,
             focus_widget,
+            input_modality,
+            focus_visible,
             text: window.text(),
         }
     }
@@ -958,6 +1263,14 @@ impl<'a> GlobalPassCtx<'a> {
             .push_back((action, widget_id, self.window_id));
     }
 
+    pub(crate) fn now(&self) -> Instant {
+        match self.mock_timer_queue.as_ref() {
+            // Path taken in unit tests
+            Some(timer_queue) => timer_queue.now(),
+            None => Instant::now(),
+        }
+    }
+
     pub(crate) fn request_timer(&mut self, duration: Duration, widget_id: WidgetId) -> TimerToken {
         trace!("request_timer duration={:?}", duration);
 
@@ -199,6 +199,17 @@ pub struct MissingKeyError {
     key: Arc<str>,
 }
 
+/// The keys whose values were changed by [`Env::merge`].
+///
+/// Widgets get them in [`Widget::env_changed`](crate::Widget::env_changed) when the
+/// app's environment is merged with [`AppRoot::merge_env`](crate::AppRoot::merge_env).
+///
+/// [`Env::merge`]: struct.Env.html#method.merge
+#[derive(Debug, Clone, Default)]
+pub struct ChangedKeys {
+    keys: Vec<ArcStr>,
+}
+
 impl Env {
     /// State for whether or not to paint colorful rectangles for layout
     /// debugging.
@@ -305,6 +316,18 @@ impl Env {
         })
     }
 
+    /// Checks that all of `keys` are present, returning an error for each one that isn't.
+    ///
+    /// Unlike [`try_get`](Self::try_get), this doesn't stop at the first missing key,
+    /// so a theme that lacks several values can be fixed in one go. The types of the
+    /// values that are present aren't checked.
+    pub fn validate_keys(&self, keys: &[&str]) -> Vec<MissingKeyError> {
+        keys.iter()
+            .filter(|key| !self.0.map.contains_key(**key))
+            .map(|key| MissingKeyError { key: (*key).into() })
+            .collect()
+    }
+
     /// Gets the entire contents of the `Env`, in key-value pairs.
     ///
     /// *WARNING:* This is not intended for general use, but only for inspecting an `Env` e.g.
@@ -357,6 +380,39 @@ impl Env {
         Ok(())
     }
 
+    /// Apply all the values of `other` on top of this environment.
+    ///
+    /// This is meant for reloading a theme at runtime. The returned [`ChangedKeys`]
+    /// lists the keys that were added, or whose value differs from the previous one,
+    /// so that only widgets depending on those keys need to be updated. To update the
+    /// environment of a running app, use [`AppRoot::merge_env`](crate::AppRoot::merge_env).
+    ///
+    /// Values whose type doesn't match the existing value for the same key are
+    /// skipped, and a warning is logged.
+    pub fn merge(&mut self, other: &Env) -> ChangedKeys {
+        let mut changed = ChangedKeys::default();
+        for (key, value) in other.0.map.iter() {
+            match self.0.map.get(key) {
+                Some(existing) if existing.same(value) => continue,
+                Some(existing) if !existing.is_same_type(value) => {
+                    tracing::warn!(
+                        "Cannot merge key '{}': expected {:?}, found {:?}",
+                        key,
+                        existing,
+                        value
+                    );
+                    continue;
+                }
+                _ => {}
+            }
+            Arc::make_mut(&mut self.0)
+                .map
+                .insert(key.clone(), value.clone());
+            changed.keys.push(key.clone());
+        }
+        changed
+    }
+
     /// Given an id, returns one of 18 distinct colors
     #[doc(hidden)]
     pub fn get_debug_color(&self, id: u64) -> Color {
@@ -371,6 +427,29 @@ impl std::fmt::Debug for Env {
     }
 }
 
+impl ChangedKeys {
+    /// Returns `true` if the value for `key` changed.
+    pub fn contains<V>(&self, key: impl Borrow<Key<V>>) -> bool {
+        let key = key.borrow().key;
+        self.keys.iter().any(|changed| changed.as_ref() == key)
+    }
+
+    /// Returns `true` if no value changed.
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+
+    /// Returns the number of changed keys.
+    pub fn len(&self) -> usize {
+        self.keys.len()
+    }
+
+    /// Iterate over the raw strings of the changed keys.
+    pub fn iter(&self) -> impl Iterator<Item = &str> {
+        self.keys.iter().map(|key| key.as_ref())
+    }
+}
+
 impl<T> Key<T> {
     /// Create a new strongly typed `Key` with the given string value.
     /// The type of the key will be inferred.
@@ -614,6 +693,16 @@ impl<T: 'static + Send + Sync> ValueType for Arc<T> {
     }
 }
 
+impl<T> KeyOrValue<T> {
+    /// The raw key, if this is a key rather than a concrete value.
+    pub(crate) fn raw_key(&self) -> Option<&'static str> {
+        match self {
+            KeyOrValue::Concrete(_) => None,
+            KeyOrValue::Key(key) => Some(key.key),
+        }
+    }
+}
+
 impl<T: ValueType> KeyOrValue<T> {
     /// Resolve the concrete type `T` from this `KeyOrValue`, using the provided
     /// [`Env`] if required.
@@ -685,6 +774,44 @@ mod tests {
         assert_eq!(key.resolve(&env), value.resolve(&env));
     }
 
+    #[test]
+    fn merge_reports_changed_keys() {
+        const COLOR_A: Key<Color> = Key::new("org.linebender.test.color-a");
+        const COLOR_B: Key<Color> = Key::new("org.linebender.test.color-b");
+        const WIDTH: Key<f64> = Key::new("org.linebender.test.width");
+
+        let mut env = Env::empty()
+            .adding(COLOR_A, Color::BLACK)
+            .adding(COLOR_B, Color::WHITE)
+            .adding(WIDTH, 2.0);
+        let theme = Env::empty()
+            .adding(COLOR_A, Color::BLACK)
+            .adding(COLOR_B, Color::rgb8(0x12, 0x34, 0x56))
+            .adding(WIDTH, 2.0);
+
+        let changed = env.merge(&theme);
+
+        assert_eq!(changed.len(), 1);
+        assert!(changed.contains(COLOR_B));
+        assert!(!changed.contains(COLOR_A));
+        assert!(!changed.contains(WIDTH));
+        assert_eq!(env.get(COLOR_B), Color::rgb8(0x12, 0x34, 0x56));
+
+        assert!(env.merge(&theme).is_empty());
+    }
+
+    #[test]
+    fn merge_skips_mismatched_types() {
+        const KEY: Key<f64> = Key::new("org.linebender.test.mismatched");
+        const SAME_KEY_AS_BOOL: Key<bool> = Key::new("org.linebender.test.mismatched");
+
+        let mut env = Env::empty().adding(KEY, 1.0);
+        let changed = env.merge(&Env::empty().adding(SAME_KEY_AS_BOOL, true));
+
+        assert!(changed.is_empty());
+        assert_eq!(env.get(KEY), 1.0);
+    }
+
     #[test]
     fn key_is_send_and_sync() {
         fn assert_send_sync<T: Send + Sync>() {}
@@ -10,7 +10,7 @@ use crate::kurbo::{Rect, Size};
 use crate::mouse::MouseEvent;
 // TODO - See issue #14
 use crate::promise::PromiseResult;
-use crate::{Command, Notification, WidgetId};
+use crate::{ChangedKeys, Command, Notification, WidgetId};
 
 /// An event, propagated downwards during event flow.
 ///
@@ -254,6 +254,14 @@ pub enum LifeCycle {
     /// [`EventCtx::request_pan_to_this`](crate::EventCtx::request_pan_to_this).
     RequestPanToChild(Rect),
 
+    /// Sent to every widget when values in the [`Env`](crate::Env) changed, eg when a
+    /// theme is reloaded with [`AppRoot::merge_env`](crate::AppRoot::merge_env).
+    ///
+    /// [`WidgetPod`](crate::WidgetPod) calls [`Widget::env_changed`](crate::Widget::env_changed)
+    /// with the changed keys before passing this event on. Container widgets must forward
+    /// it to their children.
+    EnvChanged(ChangedKeys),
+
     /// Internal Masonry lifecycle event.
     ///
     /// This should always be passed down to descendant [`WidgetPod`]s.
@@ -315,6 +323,21 @@ pub enum StatusChange {
     FocusChanged(bool),
 }
 
+/// The kind of device the user last interacted with.
+///
+/// Masonry tracks this per window, to tell focus that was given with the keyboard from
+/// focus that followed a click. See [`EventCtx::is_focus_visible`].
+///
+/// [`EventCtx::is_focus_visible`]: crate::EventCtx::is_focus_visible
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum InputModality {
+    /// A mouse, pen or touch input. This is the modality before any input.
+    #[default]
+    Pointer,
+    /// A key press.
+    Keyboard,
+}
+
 impl Event {
     /// Whether this event should be sent to widgets which are currently not visible and not
     /// accessible.
@@ -408,6 +431,7 @@ impl LifeCycle {
             LifeCycle::DisabledChanged(_) => true,
             LifeCycle::BuildFocusChain => false,
             LifeCycle::RequestPanToChild(_) => false,
+            LifeCycle::EnvChanged(_) => true,
         }
     }
 
@@ -426,6 +450,7 @@ impl LifeCycle {
             LifeCycle::DisabledChanged(_) => "DisabledChanged",
             LifeCycle::BuildFocusChain => "BuildFocusChain",
             LifeCycle::RequestPanToChild(_) => "RequestPanToChild",
+            LifeCycle::EnvChanged(_) => "EnvChanged",
         }
     }
 }
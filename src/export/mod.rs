@@ -0,0 +1,10 @@
+// This software is licensed under Apache License 2.0 and distributed on an
+// "as-is" basis without warranties of any kind. See the LICENSE file for
+// details.
+
+//! Widgets are painted through cairo's vector surfaces, so this is only available on
+//! platforms where piet paints with cairo.
+
+mod svg;
+
+pub use svg::{render_to_svg, SvgExportError};
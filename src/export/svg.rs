@@ -0,0 +1,152 @@
+// This software is licensed under Apache License 2.0 and distributed on an
+// "as-is" basis without warranties of any kind. See the LICENSE file for
+// details.
+
+//! Rendering widgets to SVG.
+
+use std::collections::VecDeque;
+
+use cairo::{Context, SvgSurface};
+use druid_shell::Region;
+use tracing::error;
+
+use crate::debug_logger::DebugLogger;
+use crate::ext_event::ExtEventQueue;
+use crate::kurbo::Size;
+use crate::piet::{Piet, RenderContext};
+use crate::{Env, Event, Widget, WindowId, WindowRoot, WindowSizePolicy};
+
+/// Lay out `widget` in a window of the given size, and render it to an SVG document.
+///
+/// The widget tree is painted in its initial state, before any user input, through
+/// the same [`paint`](Widget::paint) methods as on screen, into a cairo SVG surface.
+/// The window background isn't painted, so the document is transparent wherever no
+/// widget paints.
+///
+/// Cairo writes text as glyph outlines rather than `<text>` elements, so the result
+/// doesn't depend on the fonts available to whatever displays the SVG.
+///
+/// # Errors
+///
+/// Returns an error if cairo can't create the SVG surface or write the document.
+pub fn render_to_svg(widget: impl Widget, size: Size, env: &Env) -> Result<String, SvgExportError> {
+    let event_queue = ExtEventQueue::new();
+    let mut window = WindowRoot::new(
+        WindowId::next(),
+        Default::default(),
+        event_queue.make_sink(),
+        Box::new(widget),
+        "Masonry SVG export".into(),
+        true,
+        WindowSizePolicy::User,
+        None,
+    );
+    let mut debug_logger = DebugLogger::new(false);
+    let mut command_queue = VecDeque::new();
+    let mut action_queue = VecDeque::new();
+
+    for event in [Event::WindowConnected, Event::WindowSize(size)] {
+        window.event(
+            event,
+            &mut debug_logger,
+            &mut command_queue,
+            &mut action_queue,
+            env,
+        );
+    }
+
+    let surface = SvgSurface::for_stream(size.width, size.height, Vec::<u8>::new())
+        .map_err(SvgExportError::Cairo)?;
+    {
+        let context = Context::new(&surface).map_err(SvgExportError::Cairo)?;
+        let mut piet = Piet::new(&context);
+        window.do_paint(
+            &mut piet,
+            &Region::from(size.to_rect()),
+            &mut debug_logger,
+            &mut command_queue,
+            &mut action_queue,
+            env,
+        );
+        if let Err(err) = piet.finish() {
+            error!("piet finish failed: {}", err);
+        }
+    }
+
+    let stream = surface
+        .finish_output_stream()
+        .map_err(|err| SvgExportError::Io(err.error))?;
+    // The surface was created with a `Vec<u8>` stream above.
+    let bytes = stream.downcast::<Vec<u8>>().unwrap();
+    Ok(String::from_utf8_lossy(&bytes).into_owned())
+}
+
+/// An error returned by [`render_to_svg`] when the SVG document can't be produced.
+#[derive(Debug)]
+pub enum SvgExportError {
+    /// Cairo failed to create the SVG surface or its drawing context.
+    Cairo(cairo::Error),
+    /// Writing the SVG document failed.
+    Io(std::io::Error),
+}
+
+impl std::fmt::Display for SvgExportError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            SvgExportError::Cairo(err) => write!(f, "Failed to render SVG: {}", err),
+            SvgExportError::Io(err) => write!(f, "Failed to write SVG: {}", err),
+        }
+    }
+}
+
+impl std::error::Error for SvgExportError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            SvgExportError::Cairo(err) => Some(err),
+            SvgExportError::Io(err) => Some(err),
+        }
+    }
+}
+
+// --- Tests ---
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::piet::Color;
+    use crate::widget::{Flex, Label, SizedBox};
+
+    #[test]
+    fn bordered_box() {
+        let widget = SizedBox::empty()
+            .width(40.0)
+            .height(20.0)
+            .background(Color::rgb8(0x11, 0x22, 0x33))
+            .border(Color::WHITE, 2.0)
+            .rounded(4.0);
+
+        let svg = render_to_svg(widget, Size::new(40.0, 20.0), &Env::with_theme()).unwrap();
+
+        assert!(svg.contains("<svg "));
+        assert!(svg.contains(r#"width="40""#));
+        // The background is filled, and the border stroked.
+        assert!(svg.matches("<path").count() >= 2);
+        assert!(svg.contains("stroke-width"));
+    }
+
+    #[test]
+    fn label_and_clip() {
+        let widget = Flex::column().with_child(
+            SizedBox::new(Label::new("a < b"))
+                .width(40.0)
+                .height(10.0)
+                .clip_child(true),
+        );
+
+        let svg = render_to_svg(widget, Size::new(100.0, 100.0), &Env::with_theme()).unwrap();
+
+        assert!(svg.contains("<clipPath"));
+        // The label's glyphs are painted as outlines.
+        assert!(svg.contains("<use"));
+    }
+}
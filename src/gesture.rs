@@ -0,0 +1,414 @@
+// This software is licensed under Apache License 2.0 and distributed on an
+// "as-is" basis without warranties of any kind. See the LICENSE file for
+// details.
+
+//! Helpers for widgets that respond to pointer gestures.
+//!
+//! [`VelocityTracker`] estimates how fast the pointer was moving at the end of a drag,
+//! and [`Fling`] turns that velocity into a decelerating motion which can be stepped
+//! forward on each [`Event::AnimFrame`](crate::Event::AnimFrame). Together they give
+//! [`Portal::flick_scroll`](crate::widget::Portal::flick_scroll) its momentum.
+//!
+//! [`ActivePointers`] keeps track of several pointers being held down at once, eg
+//! fingers on a touch screen, for gestures like pinch-to-zoom.
+
+use std::collections::VecDeque;
+use std::time::Duration;
+
+use instant::Instant;
+
+use crate::{Event, Point, PointerId, Vec2};
+
+/// Only samples this recent (relative to the last one) are used to estimate velocity.
+const VELOCITY_HORIZON: Duration = Duration::from_millis(100);
+
+/// If the pointer didn't move for this long before the last sample, it is considered
+/// to have stopped, and the velocity is zero.
+const STOP_THRESHOLD: Duration = Duration::from_millis(40);
+
+/// The maximum number of samples kept.
+const MAX_SAMPLES: usize = 20;
+
+/// The default deceleration of a [`Fling`], in pixels per second squared.
+pub const DEFAULT_FLING_DECELERATION: f64 = 2000.0;
+
+/// Records pointer positions during a drag, and estimates the pointer's velocity.
+///
+/// Call [`add_sample`](Self::add_sample) on each mouse move while dragging, then
+/// [`velocity`](Self::velocity) when the mouse button is released.
+///
+/// The estimate only takes the last hundred milliseconds of movement into account,
+/// and is computed with a least-squares fit, so it isn't thrown off by a single
+/// jittery sample.
+#[derive(Debug, Clone, Default)]
+pub struct VelocityTracker {
+    samples: VecDeque<(Instant, Point)>,
+}
+
+impl VelocityTracker {
+    /// Create an empty tracker.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record the pointer position at the given time.
+    ///
+    /// Samples must be added in chronological order.
+    pub fn add_sample(&mut self, time: Instant, pos: Point) {
+        if self.samples.len() == MAX_SAMPLES {
+            self.samples.pop_front();
+        }
+        self.samples.push_back((time, pos));
+    }
+
+    /// Forget all recorded samples, eg when a new drag starts.
+    pub fn reset(&mut self) {
+        self.samples.clear();
+    }
+
+    /// The estimated velocity of the pointer, in pixels per second.
+    ///
+    /// Returns zero if there aren't enough recent samples, or if the pointer stopped
+    /// moving before the last sample.
+    pub fn velocity(&self) -> Vec2 {
+        let (last_time, last_pos) = match self.samples.back() {
+            Some(sample) => *sample,
+            None => return Vec2::ZERO,
+        };
+
+        let recent: Vec<(f64, Point)> = self
+            .samples
+            .iter()
+            .filter(|(time, _)| last_time.duration_since(*time) <= VELOCITY_HORIZON)
+            .map(|(time, pos)| (-last_time.duration_since(*time).as_secs_f64(), *pos))
+            .collect();
+        if recent.len() < 2 {
+            return Vec2::ZERO;
+        }
+
+        // If the pointer rested before being released, there's nothing to fling.
+        let moved_recently = self.samples.iter().rev().skip(1).any(|(time, pos)| {
+            last_time.duration_since(*time) <= STOP_THRESHOLD && *pos != last_pos
+        });
+        if !moved_recently {
+            return Vec2::ZERO;
+        }
+
+        // Least-squares fit of position against time, on each axis.
+        let n = recent.len() as f64;
+        let mean_t = recent.iter().map(|(t, _)| t).sum::<f64>() / n;
+        let mean_x = recent.iter().map(|(_, p)| p.x).sum::<f64>() / n;
+        let mean_y = recent.iter().map(|(_, p)| p.y).sum::<f64>() / n;
+
+        let mut var_t = 0.0;
+        let mut cov_x = 0.0;
+        let mut cov_y = 0.0;
+        for (t, p) in &recent {
+            let dt = t - mean_t;
+            var_t += dt * dt;
+            cov_x += dt * (p.x - mean_x);
+            cov_y += dt * (p.y - mean_y);
+        }
+        if var_t <= f64::EPSILON {
+            return Vec2::ZERO;
+        }
+        Vec2::new(cov_x / var_t, cov_y / var_t)
+    }
+}
+
+/// A motion that starts at some velocity and slows down at a constant rate.
+///
+/// This is used to keep scrolling after a drag is released. Create it with the
+/// velocity from a [`VelocityTracker`], and on each animation frame call
+/// [`advance`](Self::advance) with the frame interval to get how far to move.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Fling {
+    velocity: Vec2,
+    deceleration: f64,
+}
+
+impl Fling {
+    /// Start a fling at `velocity`, in pixels per second.
+    pub fn new(velocity: Vec2) -> Self {
+        Fling {
+            velocity,
+            deceleration: DEFAULT_FLING_DECELERATION,
+        }
+    }
+
+    /// Builder-style method for setting the deceleration, in pixels per second squared.
+    pub fn with_deceleration(mut self, deceleration: f64) -> Self {
+        self.deceleration = deceleration.max(0.0);
+        self
+    }
+
+    /// The current velocity, in pixels per second.
+    pub fn velocity(&self) -> Vec2 {
+        self.velocity
+    }
+
+    /// Whether the motion has come to a stop.
+    pub fn is_finished(&self) -> bool {
+        self.velocity == Vec2::ZERO
+    }
+
+    /// Move the fling forward by `interval` nanoseconds, as given by
+    /// [`Event::AnimFrame`](crate::Event::AnimFrame).
+    ///
+    /// Returns the distance travelled during that interval.
+    pub fn advance(&mut self, interval: u64) -> Vec2 {
+        let speed = self.velocity.hypot();
+        if speed == 0.0 {
+            return Vec2::ZERO;
+        }
+        let direction = self.velocity / speed;
+        let dt = interval as f64 * 1e-9;
+
+        // Stop partway through the interval if we run out of speed.
+        let dt = if self.deceleration > 0.0 {
+            dt.min(speed / self.deceleration)
+        } else {
+            dt
+        };
+        let new_speed = (speed - self.deceleration * dt).max(0.0);
+        let distance = (speed + new_speed) / 2.0 * dt;
+
+        self.velocity = if new_speed > 0.0 {
+            direction * new_speed
+        } else {
+            Vec2::ZERO
+        };
+        direction * distance
+    }
+}
+
+/// The pointers currently held down over a widget, and their positions.
+///
+/// The widget's active state (see [`EventCtx::set_active`](crate::EventCtx::set_active))
+/// is a single flag, which doesn't tell pointers apart. A widget handling multi-touch
+/// gestures should instead pass each event it receives to [`update`](Self::update),
+/// and check which pointers are down here.
+///
+/// Pointers are kept in the order they were pressed.
+#[derive(Debug, Clone, Default)]
+pub struct ActivePointers {
+    pointers: Vec<(PointerId, Point)>,
+}
+
+impl ActivePointers {
+    /// Create an empty set of pointers.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Update the pointers from a mouse event.
+    ///
+    /// A pointer becomes active on `MouseDown`, follows `MouseMove` events while it is
+    /// active, and stops being active once `MouseUp` reports no buttons held. Other
+    /// events are ignored.
+    ///
+    /// Returns `true` if the event changed the set of pointers or their positions.
+    pub fn update(&mut self, event: &Event) -> bool {
+        match event {
+            Event::MouseDown(mouse) => {
+                match self.index_of(mouse.pointer_id) {
+                    Some(idx) => self.pointers[idx].1 = mouse.pos,
+                    None => self.pointers.push((mouse.pointer_id, mouse.pos)),
+                }
+                true
+            }
+            Event::MouseMove(mouse) => match self.index_of(mouse.pointer_id) {
+                Some(idx) if self.pointers[idx].1 != mouse.pos => {
+                    self.pointers[idx].1 = mouse.pos;
+                    true
+                }
+                _ => false,
+            },
+            Event::MouseUp(mouse) if mouse.buttons.is_empty() => {
+                match self.index_of(mouse.pointer_id) {
+                    Some(idx) => {
+                        self.pointers.remove(idx);
+                        true
+                    }
+                    None => false,
+                }
+            }
+            _ => false,
+        }
+    }
+
+    /// Forget all pointers, eg when the widget loses its active state.
+    pub fn clear(&mut self) {
+        self.pointers.clear();
+    }
+
+    /// Whether the given pointer is held down.
+    pub fn is_active(&self, pointer_id: PointerId) -> bool {
+        self.index_of(pointer_id).is_some()
+    }
+
+    /// The last known position of the given pointer, if it is held down.
+    pub fn position(&self, pointer_id: PointerId) -> Option<Point> {
+        self.index_of(pointer_id).map(|idx| self.pointers[idx].1)
+    }
+
+    /// The number of pointers held down.
+    pub fn len(&self) -> usize {
+        self.pointers.len()
+    }
+
+    /// Whether no pointer is held down.
+    pub fn is_empty(&self) -> bool {
+        self.pointers.is_empty()
+    }
+
+    /// Iterate over the pointers held down and their positions, in the order they were
+    /// pressed.
+    pub fn iter(&self) -> impl Iterator<Item = (PointerId, Point)> + '_ {
+        self.pointers.iter().copied()
+    }
+
+    /// The distance between the first two pointers, if at least two are held down.
+    ///
+    /// The ratio between two successive values gives the scale factor of a pinch.
+    pub fn spread(&self) -> Option<f64> {
+        match self.pointers.as_slice() {
+            [(_, first), (_, second), ..] => Some(first.distance(*second)),
+            _ => None,
+        }
+    }
+
+    fn index_of(&self, pointer_id: PointerId) -> Option<usize> {
+        self.pointers.iter().position(|(id, _)| *id == pointer_id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::mouse_move;
+    use crate::MouseEvent;
+    use druid_shell::MouseButton;
+
+    fn track(samples: &[(u64, f64, f64)]) -> Vec2 {
+        let start = Instant::now();
+        let mut tracker = VelocityTracker::new();
+        for &(ms, x, y) in samples {
+            tracker.add_sample(start + Duration::from_millis(ms), Point::new(x, y));
+        }
+        tracker.velocity()
+    }
+
+    #[test]
+    fn constant_velocity() {
+        let samples: Vec<_> = (0..10)
+            .map(|i| (i * 10, i as f64 * 10.0, i as f64 * -5.0))
+            .collect();
+        let velocity = track(&samples);
+
+        assert!((velocity.x - 1000.0).abs() < 1e-6);
+        assert!((velocity.y + 500.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn old_samples_are_ignored() {
+        // The pointer moved fast a while ago, then slowly until release.
+        let mut samples = vec![(0, 0.0, 0.0), (10, 100.0, 0.0), (20, 200.0, 0.0)];
+        samples.extend((0..10).map(|i| (300 + i * 10, 200.0 + i as f64, 0.0)));
+        let velocity = track(&samples);
+
+        assert!((velocity.x - 100.0).abs() < 1e-6);
+        assert_eq!(velocity.y, 0.0);
+    }
+
+    #[test]
+    fn jitter_is_smoothed() {
+        let samples: Vec<_> = (0..10)
+            .map(|i| {
+                let jitter = if i % 2 == 0 { 2.0 } else { -2.0 };
+                (i * 10, i as f64 * 10.0 + jitter, 0.0)
+            })
+            .collect();
+        let velocity = track(&samples);
+
+        assert!((velocity.x - 1000.0).abs() < 100.0);
+    }
+
+    #[test]
+    fn stopped_pointer_has_no_velocity() {
+        let samples = [
+            (0, 0.0, 0.0),
+            (10, 50.0, 0.0),
+            (20, 100.0, 0.0),
+            (80, 100.0, 0.0),
+        ];
+
+        assert_eq!(track(&samples), Vec2::ZERO);
+        assert_eq!(track(&[(0, 10.0, 10.0)]), Vec2::ZERO);
+        assert_eq!(track(&[]), Vec2::ZERO);
+    }
+
+    #[test]
+    fn fling_decelerates() {
+        let mut fling = Fling::new(Vec2::new(0.0, 1000.0)).with_deceleration(2000.0);
+        let frame = Duration::from_millis(100).as_nanos() as u64;
+
+        let first = fling.advance(frame);
+        assert!((first.y - 90.0).abs() < 1e-6);
+        assert!((fling.velocity().y - 800.0).abs() < 1e-6);
+
+        let mut total = first;
+        while !fling.is_finished() {
+            total += fling.advance(frame);
+        }
+
+        // v² / 2a
+        assert!((total.y - 250.0).abs() < 1e-6);
+        assert_eq!(total.x, 0.0);
+        assert_eq!(fling.advance(frame), Vec2::ZERO);
+    }
+
+    fn pointer_event(pointer_id: u64, pos: (f64, f64), pressed: bool) -> MouseEvent {
+        let mut event = mouse_move(pos);
+        event.pointer_id = PointerId(pointer_id);
+        event.button = MouseButton::Left;
+        if pressed {
+            event.buttons.insert(MouseButton::Left);
+        }
+        event
+    }
+
+    #[test]
+    fn pointers_are_tracked_independently() {
+        let mut pointers = ActivePointers::new();
+
+        assert!(pointers.update(&Event::MouseDown(pointer_event(1, (0.0, 0.0), true))));
+        assert!(pointers.update(&Event::MouseDown(pointer_event(2, (30.0, 40.0), true))));
+        assert_eq!(pointers.len(), 2);
+        assert_eq!(pointers.spread(), Some(50.0));
+
+        // Moving one pointer doesn't affect the other.
+        let mut moved = pointer_event(2, (60.0, 80.0), true);
+        moved.button = MouseButton::None;
+        assert!(pointers.update(&Event::MouseMove(moved)));
+        assert_eq!(pointers.position(PointerId(1)), Some(Point::ZERO));
+        assert_eq!(
+            pointers.position(PointerId(2)),
+            Some(Point::new(60.0, 80.0))
+        );
+        assert_eq!(pointers.spread(), Some(100.0));
+
+        // Releasing one pointer leaves the other active.
+        assert!(pointers.update(&Event::MouseUp(pointer_event(1, (0.0, 0.0), false))));
+        assert!(!pointers.is_active(PointerId(1)));
+        assert!(pointers.is_active(PointerId(2)));
+        assert_eq!(pointers.spread(), None);
+
+        // Pointers that aren't down are ignored.
+        assert!(!pointers.update(&Event::MouseMove(mouse_move((5.0, 5.0)))));
+        assert!(!pointers.is_active(PointerId::MOUSE));
+
+        let ids: Vec<_> = pointers.iter().map(|(id, _)| id).collect();
+        assert_eq!(ids, vec![PointerId(2)]);
+    }
+}
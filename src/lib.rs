@@ -99,6 +99,7 @@ pub use druid_shell::{kurbo, piet};
 #[macro_use]
 mod util;
 
+mod accessibility;
 mod action;
 mod app_delegate;
 mod app_launcher;
@@ -110,7 +111,18 @@ mod contexts;
 mod data;
 pub mod env;
 mod event;
+/// Rendering widgets to vector formats, eg for documentation.
+///
+/// This module is only compiled on Linux, OpenBSD, FreeBSD and NetBSD.
+#[cfg(any(
+    target_os = "linux",
+    target_os = "openbsd",
+    target_os = "freebsd",
+    target_os = "netbsd"
+))]
+pub mod export;
 pub mod ext_event;
+pub mod gesture;
 mod mouse;
 mod platform;
 pub mod promise;
@@ -123,6 +135,7 @@ pub mod widget;
 pub mod debug_logger;
 pub mod debug_values;
 
+pub use accessibility::{Accessibility, AccessibilityNode, Role};
 pub use action::Action;
 pub use app_delegate::{AppDelegate, DelegateCtx};
 pub use app_launcher::AppLauncher;
@@ -132,14 +145,16 @@ pub use command::{Command, Notification, Selector, SingleUse, Target};
 pub use contexts::{EventCtx, LayoutCtx, LifeCycleCtx, PaintCtx, WidgetCtx};
 pub use data::Data;
 pub use druid_shell::Error as PlatformError;
-pub use env::{Env, Key, KeyOrValue, Value, ValueType, ValueTypeError};
-pub use event::{Event, InternalEvent, InternalLifeCycle, LifeCycle, StatusChange};
+pub use env::{
+    ChangedKeys, Env, Key, KeyOrValue, MissingKeyError, Value, ValueType, ValueTypeError,
+};
+pub use event::{Event, InputModality, InternalEvent, InternalLifeCycle, LifeCycle, StatusChange};
 pub use kurbo::{Affine, Insets, Point, Rect, Size, Vec2};
-pub use mouse::MouseEvent;
+pub use mouse::{MouseEvent, PointerId};
 pub use piet::{Color, ImageBuf, LinearGradient, RadialGradient, RenderContext, UnitPoint};
 pub use platform::{
     MasonryWinHandler, WindowConfig, WindowDescription, WindowId, WindowSizePolicy,
 };
 pub use text::ArcStr;
 pub use util::{AsAny, Handled};
-pub use widget::{BackgroundBrush, Widget, WidgetId, WidgetPod, WidgetState};
+pub use widget::{BackgroundBrush, Widget, WidgetExt, WidgetId, WidgetPod, WidgetState};
@@ -10,6 +10,25 @@ use druid_shell::{Modifiers, MouseButton, MouseButtons};
 
 use crate::kurbo::{Point, Vec2};
 
+/// Identifies one of several pointers that can be down at the same time.
+///
+/// A mouse is a single pointer, but on a touch screen, each finger is a separate
+/// pointer. Widgets that support multi-touch gestures use this id to tell the
+/// pointers apart; see [`ActivePointers`](crate::gesture::ActivePointers).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct PointerId(pub u64);
+
+impl PointerId {
+    /// The id of the mouse pointer.
+    pub const MOUSE: PointerId = PointerId(0);
+}
+
+impl Default for PointerId {
+    fn default() -> Self {
+        PointerId::MOUSE
+    }
+}
+
 /// The state of the mouse for a click, mouse-up, move, or wheel event.
 ///
 /// In Masonry, unlike in `druid_shell`, we treat the widget's coordinate
@@ -27,6 +46,11 @@ use crate::kurbo::{Point, Vec2};
 /// [`Event::MouseMove`]: enum.Event.html#variant.MouseMove
 #[derive(Debug, Clone)]
 pub struct MouseEvent {
+    /// The pointer that triggered the event.
+    ///
+    /// This is always [`PointerId::MOUSE`] for events coming from a mouse; other ids
+    /// identify simultaneous pointers, like fingers on a touch screen.
+    pub pointer_id: PointerId,
     /// The position of the mouse in the coordinate space of the receiver.
     pub pos: Point,
     /// The position of the mouse in the coordinate space of the window.
@@ -76,6 +100,7 @@ impl From<druid_shell::MouseEvent> for MouseEvent {
             wheel_delta,
         } = src;
         MouseEvent {
+            pointer_id: PointerId::MOUSE,
             pos,
             window_pos: pos,
             buttons,
@@ -16,6 +16,7 @@ use image::io::Reader as ImageReader;
 use instant::Duration;
 use shell::text::Selection;
 
+use super::layout_tree::LayoutNode;
 use super::screenshots::{get_image_diff, get_rgba_image};
 use super::snapshot_utils::get_cargo_workspace;
 use super::MockTimerQueue;
@@ -114,6 +115,7 @@ pub struct TestHarness {
     mock_app: MockAppRoot,
     mouse_state: MouseEvent,
     window_size: Size,
+    scale: f64,
 }
 
 /// Assert a snapshot of a rendered frame of your app.
@@ -177,6 +179,7 @@ impl TestHarness {
         );
 
         let mouse_state = MouseEvent {
+            pointer_id: PointerId::MOUSE,
             pos: Point::ZERO,
             window_pos: Point::ZERO,
             buttons: MouseButtons::default(),
@@ -197,6 +200,7 @@ impl TestHarness {
             },
             mouse_state,
             window_size,
+            scale: 1.0,
         };
 
         // verify that all widgets are marked as having children_changed
@@ -266,9 +270,9 @@ impl TestHarness {
         let mut device = Device::new().expect("harness failed to get device");
         let mut render_target = device
             .bitmap_target(
-                self.window_size.width as usize,
-                self.window_size.height as usize,
-                1.0,
+                (self.window_size.width * self.scale) as usize,
+                (self.window_size.height * self.scale) as usize,
+                self.scale,
             )
             .expect("failed to create bitmap_target");
 
@@ -378,6 +382,26 @@ impl TestHarness {
         self.process_state_after_event();
     }
 
+    /// Set whether the root widget is [active](crate::EventCtx::is_active).
+    ///
+    /// This is meant for checking what a widget looks like while it's pressed, without
+    /// going through [`mouse_button_press`](Self::mouse_button_press). The state is
+    /// changed through the root's [`WidgetMut`], as in
+    /// [`edit_root_widget`](Self::edit_root_widget); the widget isn't sent any event,
+    /// it's only repainted.
+    pub fn set_active(&mut self, active: bool) {
+        self.edit_root_widget(|mut root, _| {
+            let (widget, ctx) = Box::<dyn Widget>::get_widget_and_ctx(&mut root.inner);
+            let child_active = widget
+                .children()
+                .iter()
+                .any(|child| child.state().has_active);
+            ctx.widget_state.is_active = active;
+            ctx.widget_state.has_active = active || child_active;
+            ctx.request_paint();
+        });
+    }
+
     #[doc(alias = "send_command")]
     /// Send a command to a target.
     pub fn submit_command(&mut self, command: impl Into<Command>) {
@@ -407,6 +431,45 @@ impl TestHarness {
         }
     }
 
+    /// Change a value in the [`Env`] the widget tree is run with.
+    ///
+    /// This simulates a theme change: the whole tree is laid out again with the new
+    /// value, and the whole window is repainted on the next [`render`](Self::render).
+    pub fn set_env_value<V: ValueType>(&mut self, key: Key<V>, value: impl Into<V>) {
+        self.mock_app.env.set(key, value);
+        self.mock_app.layout();
+        *self.window_mut().invalid_mut() = Region::from(self.window_size.to_rect());
+    }
+
+    /// The widgets exposed to assistive technologies, see [`AccessibilityNode::tree`].
+    pub fn accessibility_tree(&self) -> Vec<AccessibilityNode> {
+        AccessibilityNode::tree(self.root_widget())
+    }
+
+    /// Apply the values of `theme` on top of the [`Env`] the widget tree is run with.
+    ///
+    /// This simulates reloading a theme at runtime: widgets receive
+    /// [`LifeCycle::EnvChanged`] with the keys whose values changed, and only those
+    /// which request it are laid out and repainted again.
+    pub fn merge_env(&mut self, theme: &Env) -> ChangedKeys {
+        let changed = self.mock_app.env.merge(theme);
+        if !changed.is_empty() {
+            self.mock_app
+                .lifecycle(LifeCycle::EnvChanged(changed.clone()));
+            self.process_state_after_event();
+        }
+        changed
+    }
+
+    /// Render with the given scale factor, eg `2.0` to simulate a high-DPI display.
+    ///
+    /// Rendered images are `scale` times as wide and tall as the window. The default
+    /// scale factor is `1.0`.
+    pub fn set_scale(&mut self, scale: f64) {
+        self.scale = scale;
+        *self.window_mut().invalid_mut() = Region::from(self.window_size.to_rect());
+    }
+
     // --- Getters ---
 
     /// Return the mocked window.
@@ -441,12 +504,50 @@ impl TestHarness {
         self.mock_app.window.find_widget_by_id(id)
     }
 
+    /// Return the paint insets the widget with the given id set during its last layout.
+    ///
+    /// This is the area outside its layout rect the widget reported painting to, eg a
+    /// shadow, as set with [`LayoutCtx::set_paint_insets`]. It doesn't include the paint
+    /// rects of its children.
+    ///
+    /// ## Panics
+    ///
+    /// Panics if no Widget with this id can be found.
+    pub fn get_paint_insets(&self, id: WidgetId) -> Insets {
+        self.get_widget(id).state().paint_insets
+    }
+
     // TODO - link to focus documentation.
     /// Return the widget that receives keyboard events.
     pub fn focused_widget(&self) -> Option<WidgetRef<'_, dyn Widget>> {
         self.mock_app.window.focused_widget()
     }
 
+    /// Return the computed layout of every widget in the widget tree.
+    ///
+    /// The returned [`LayoutNode`] implements `Debug` and `Serialize` in a stable way,
+    /// which makes it a good fit for snapshot tests of layout code.
+    pub fn layout_tree(&self) -> LayoutNode {
+        LayoutNode::from_widget(self.root_widget())
+    }
+
+    /// Return the id of every widget in the widget tree, root included.
+    ///
+    /// Ids are listed in tree order: each widget comes before its children, and
+    /// children are in the order their parent lists them.
+    pub fn all_ids(&self) -> Vec<WidgetId> {
+        fn collect(widget: WidgetRef<'_, dyn Widget>, ids: &mut Vec<WidgetId>) {
+            ids.push(widget.id());
+            for child in widget.deref().children() {
+                collect(child, ids);
+            }
+        }
+
+        let mut ids = Vec::new();
+        collect(self.root_widget(), &mut ids);
+        ids
+    }
+
     /// Call the provided visitor on every widget in the widget tree.
     pub fn inspect_widgets(&mut self, f: impl Fn(WidgetRef<'_, dyn Widget>) + 'static) {
         fn inspect(
@@ -484,6 +585,8 @@ impl TestHarness {
                 &window.handle,
                 window.id,
                 window.focus,
+                window.input_modality,
+                window.focus_visible,
             );
             fake_widget_state = window.root.state.clone();
 
@@ -556,15 +659,15 @@ impl TestHarness {
         let mut device = Device::new().expect("harness failed to get device");
         let mut render_target = device
             .bitmap_target(
-                self.window_size.width as usize,
-                self.window_size.height as usize,
-                1.0,
+                (self.window_size.width * self.scale) as usize,
+                (self.window_size.height * self.scale) as usize,
+                self.scale,
             )
             .expect("failed to create bitmap_target");
 
         self.render_to(&mut render_target);
 
-        let new_image = get_rgba_image(&mut render_target, self.window_size);
+        let new_image = get_rgba_image(&mut render_target, self.window_size * self.scale);
 
         let workspace_path = get_cargo_workspace(manifest_dir);
         let test_file_path_abs = workspace_path.join(test_file_path);
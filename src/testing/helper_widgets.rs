@@ -300,6 +300,10 @@ impl<W: Widget> Widget for Recorder<W> {
         self.child.lifecycle(ctx, event, env)
     }
 
+    fn env_changed(&mut self, ctx: &mut LifeCycleCtx, changed: &ChangedKeys, env: &Env) {
+        self.child.env_changed(ctx, changed, env)
+    }
+
     fn layout(&mut self, ctx: &mut LayoutCtx, bc: &BoxConstraints, env: &Env) -> Size {
         let size = self.child.layout(ctx, bc, env);
         self.recording.push(Record::Layout(size));
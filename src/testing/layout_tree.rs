@@ -0,0 +1,101 @@
+// This software is licensed under Apache License 2.0 and distributed on an
+// "as-is" basis without warranties of any kind. See the LICENSE file for
+// details.
+
+//! A structured dump of the computed layout of a widget tree.
+
+use std::fmt;
+
+use serde::ser::SerializeStruct;
+use serde::{Serialize, Serializer};
+
+use crate::widget::WidgetRef;
+use crate::{Insets, Rect, Widget, WidgetId};
+
+/// The computed layout of a widget and its descendants.
+///
+/// This is returned by [`TestHarness::layout_tree`](super::TestHarness::layout_tree), and
+/// is meant for golden testing: it catches layout regressions without having to render
+/// anything.
+///
+/// The `Debug` and `Serialize` representations leave out widget ids, since those
+/// depend on the order in which widgets were created, and would make snapshots flaky.
+/// Everything else is written in a stable order.
+#[derive(Clone, PartialEq)]
+pub struct LayoutNode {
+    /// The id of the widget.
+    pub id: WidgetId,
+    /// The abridged type name of the widget, eg `"SizedBox"`.
+    pub type_name: &'static str,
+    /// The layout rect of the widget, in its parent's coordinate space.
+    pub layout_rect: Rect,
+    /// The distance from the bottom of the widget to its baseline.
+    pub baseline_offset: f64,
+    /// The area outside the layout rect the widget paints to.
+    pub paint_insets: Insets,
+    /// The nodes of the widget's children, in order.
+    pub children: Vec<LayoutNode>,
+}
+
+impl LayoutNode {
+    pub(crate) fn from_widget(widget: WidgetRef<'_, dyn Widget>) -> Self {
+        let state = widget.state();
+        LayoutNode {
+            id: widget.id(),
+            type_name: widget.deref().short_type_name(),
+            layout_rect: state.layout_rect(),
+            baseline_offset: state.baseline_offset,
+            paint_insets: state.paint_insets,
+            children: widget
+                .children()
+                .into_iter()
+                .map(LayoutNode::from_widget)
+                .collect(),
+        }
+    }
+
+    /// Find the node of the widget with the given id, if it's in this subtree.
+    pub fn find(&self, id: WidgetId) -> Option<&LayoutNode> {
+        if self.id == id {
+            return Some(self);
+        }
+        self.children.iter().find_map(|child| child.find(id))
+    }
+}
+
+fn rect_tuple(rect: Rect) -> (f64, f64, f64, f64) {
+    (rect.x0, rect.y0, rect.x1, rect.y1)
+}
+
+fn insets_tuple(insets: Insets) -> (f64, f64, f64, f64) {
+    (insets.x0, insets.y0, insets.x1, insets.y1)
+}
+
+impl fmt::Debug for LayoutNode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut node = f.debug_struct(self.type_name);
+        node.field("layout_rect", &rect_tuple(self.layout_rect));
+        if self.baseline_offset != 0.0 {
+            node.field("baseline_offset", &self.baseline_offset);
+        }
+        if self.paint_insets != Insets::ZERO {
+            node.field("paint_insets", &insets_tuple(self.paint_insets));
+        }
+        if !self.children.is_empty() {
+            node.field("children", &self.children);
+        }
+        node.finish()
+    }
+}
+
+impl Serialize for LayoutNode {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut node = serializer.serialize_struct("LayoutNode", 5)?;
+        node.serialize_field("type_name", self.type_name)?;
+        node.serialize_field("layout_rect", &rect_tuple(self.layout_rect))?;
+        node.serialize_field("baseline_offset", &self.baseline_offset)?;
+        node.serialize_field("paint_insets", &insets_tuple(self.paint_insets))?;
+        node.serialize_field("children", &self.children)?;
+        node.end()
+    }
+}
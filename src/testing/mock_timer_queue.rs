@@ -5,7 +5,7 @@
 use std::collections::VecDeque;
 
 use druid_shell::TimerToken;
-use instant::Duration;
+use instant::{Duration, Instant};
 
 /// Handles timers for unit tests.
 ///
@@ -21,6 +21,8 @@ use instant::Duration;
 /// To avoid polluting the code with `#[cfg(test)]` annotations, MockTimerQueue is also
 /// present in non-test code, but it's always empty.
 pub(crate) struct MockTimerQueue {
+    /// The instant the harness clock started at, see [`now`](Self::now).
+    pub start: Instant,
     pub current_time: Duration,
     pub queue: VecDeque<(Duration, TimerToken)>,
 }
@@ -28,11 +30,17 @@ pub(crate) struct MockTimerQueue {
 impl MockTimerQueue {
     pub(crate) fn new() -> Self {
         MockTimerQueue {
+            start: Instant::now(),
             current_time: Duration::ZERO,
             queue: VecDeque::new(),
         }
     }
 
+    /// The time on the harness clock, which only moves forward with the timers.
+    pub(crate) fn now(&self) -> Instant {
+        self.start + self.current_time
+    }
+
     #[must_use]
     pub(crate) fn add_timer(&mut self, duration: Duration) -> TimerToken {
         let deadline = self.current_time + duration;
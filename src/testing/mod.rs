@@ -11,6 +11,8 @@ mod harness;
 #[cfg(not(tarpaulin_include))]
 mod helper_widgets;
 #[cfg(not(tarpaulin_include))]
+mod layout_tree;
+#[cfg(not(tarpaulin_include))]
 mod mock_timer_queue;
 #[cfg(not(tarpaulin_include))]
 mod screenshots;
@@ -22,15 +24,17 @@ pub use harness::{TestHarness, HARNESS_DEFAULT_SIZE};
 pub use helper_widgets::{
     ModularWidget, Record, Recorder, Recording, ReplaceChild, TestWidgetExt, REPLACE_CHILD,
 };
+pub use layout_tree::LayoutNode;
 pub(crate) use mock_timer_queue::MockTimerQueue;
 
 use crate::kurbo::{Point, Vec2};
-use crate::{MouseEvent, WidgetId};
+use crate::{MouseEvent, PointerId, WidgetId};
 
 /// Helper function to construct a "move to this position" mouse event.
 pub fn mouse_move(p: impl Into<Point>) -> MouseEvent {
     let pos = p.into();
     MouseEvent {
+        pointer_id: PointerId::MOUSE,
         pos,
         window_pos: pos,
         buttons: MouseButtons::default(),
@@ -46,6 +50,7 @@ pub fn mouse_move(p: impl Into<Point>) -> MouseEvent {
 pub fn mouse_scroll(p: impl Into<Point>, delta: impl Into<Vec2>) -> MouseEvent {
     let pos = p.into();
     MouseEvent {
+        pointer_id: PointerId::MOUSE,
         pos,
         window_pos: pos,
         buttons: MouseButtons::default(),
@@ -62,6 +67,13 @@ pub fn widget_ids<const N: usize>() -> [WidgetId; N] {
     std::array::from_fn(|_| WidgetId::next())
 }
 
+/// Return the premultiplied RGBA value of the pixel at `(x, y)` in an image returned by
+/// [`TestHarness::render`], for a window `width` pixels wide.
+pub fn pixel_at(image: &[u8], width: usize, x: usize, y: usize) -> [u8; 4] {
+    let offset = (y * width + x) * 4;
+    image[offset..offset + 4].try_into().unwrap()
+}
+
 /// This function creates a temporary directory and returns a PathBuf to it.
 ///
 /// This directory will be created relative to the executable and will therefor
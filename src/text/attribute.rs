@@ -127,8 +127,9 @@ impl AttributeSpans {
         let mut items = Vec::new();
         for Span { range, attr } in self.font_descriptor.iter() {
             let font = attr.resolve(env);
+            let size = font.resolved_size(env);
             items.push((range.clone(), PietAttr::FontFamily(font.family)));
-            items.push((range.clone(), PietAttr::FontSize(font.size)));
+            items.push((range.clone(), PietAttr::FontSize(size)));
             items.push((range.clone(), PietAttr::Weight(font.weight)));
             items.push((range.clone(), PietAttr::Style(font.style)));
         }
@@ -4,8 +4,8 @@
 
 //! Font attributes
 
-use crate::piet::{FontFamily, FontStyle, FontWeight};
-use crate::Data;
+use crate::piet::{FontFamily, FontStyle, FontWeight, PietText, Text as _};
+use crate::{theme, Data, Env};
 
 /// A collection of attributes that describe a font.
 ///
@@ -15,14 +15,31 @@ use crate::Data;
 pub struct FontDescriptor {
     /// The font's [`FontFamily`](struct.FontFamily.html).
     pub family: FontFamily,
-    /// The font's size.
+    /// The font's size, in points.
+    ///
+    /// For a size set with [`with_size_rem`](Self::with_size_rem), this is the size with
+    /// the default theme's base text size; the text is laid out with the
+    /// [`resolved_size`](Self::resolved_size) instead.
     pub size: f64,
+    // Set through `with_size` and `with_size_rem`, which keep it in sync with `size`.
+    size_unit: FontSizeUnit,
     /// The font's [`FontWeight`](struct.FontWeight.html).
     pub weight: FontWeight,
     /// The font's [`FontStyle`](struct.FontStyle.html).
     pub style: FontStyle,
 }
 
+/// How a [`FontDescriptor`]'s size is resolved.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum FontSizeUnit {
+    /// The size is [`FontDescriptor::size`], in points.
+    Absolute,
+    /// The size is this multiple of [`theme::TEXT_SIZE_NORMAL`].
+    ///
+    /// Changing that key in the env scales all text sized this way.
+    Rem(f64),
+}
+
 impl FontDescriptor {
     /// Create a new descriptor with the provided [`FontFamily`].
     ///
@@ -31,6 +48,7 @@ impl FontDescriptor {
         FontDescriptor {
             family,
             size: crate::piet::util::DEFAULT_FONT_SIZE,
+            size_unit: FontSizeUnit::Absolute,
             weight: FontWeight::REGULAR,
             style: FontStyle::Regular,
         }
@@ -39,9 +57,29 @@ impl FontDescriptor {
     /// Buider-style method to set the descriptor's font size.
     pub const fn with_size(mut self, size: f64) -> Self {
         self.size = size;
+        self.size_unit = FontSizeUnit::Absolute;
+        self
+    }
+
+    /// Buider-style method to set the descriptor's font size as a multiple of the
+    /// base text size, [`theme::TEXT_SIZE_NORMAL`].
+    ///
+    /// The actual size is computed when the text is laid out, so changing the base
+    /// size in the env rescales the text.
+    pub const fn with_size_rem(mut self, multiplier: f64) -> Self {
+        self.size = multiplier * theme::DEFAULT_TEXT_SIZE_NORMAL;
+        self.size_unit = FontSizeUnit::Rem(multiplier);
         self
     }
 
+    /// The font size in points, resolving relative sizes in `env`.
+    pub fn resolved_size(&self, env: &Env) -> f64 {
+        match self.size_unit {
+            FontSizeUnit::Absolute => self.size,
+            FontSizeUnit::Rem(multiplier) => multiplier * env.get(theme::TEXT_SIZE_NORMAL),
+        }
+    }
+
     /// Buider-style method to set the descriptor's [`FontWeight`].
     ///
     /// [`FontWeight`]: struct.FontWeight.html
@@ -57,6 +95,24 @@ impl FontDescriptor {
         self.style = style;
         self
     }
+
+    /// Check whether the descriptor's font family is installed on the system.
+    ///
+    /// See [`font_family_available`] for details.
+    pub fn is_available(&self, text: &mut PietText) -> bool {
+        font_family_available(text, &self.family)
+    }
+}
+
+/// Check whether a font family is installed on the system.
+///
+/// Generic families like [`FontFamily::SYSTEM_UI`] or [`FontFamily::SERIF`] are always
+/// available. Named families are looked up in the platform font database through the
+/// provided text factory, which can be obtained from any context with `ctx.text()`.
+///
+/// This is useful to pick a fallback font before building a widget.
+pub fn font_family_available(text: &mut PietText, family: &FontFamily) -> bool {
+    family.is_generic() || text.font_family(family.name()).is_some()
 }
 
 impl Default for FontDescriptor {
@@ -66,6 +122,7 @@ impl Default for FontDescriptor {
             weight: Default::default(),
             style: Default::default(),
             size: crate::piet::util::DEFAULT_FONT_SIZE,
+            size_unit: FontSizeUnit::Absolute,
         }
     }
 }
@@ -74,7 +131,52 @@ impl Data for FontDescriptor {
     fn same(&self, other: &Self) -> bool {
         self.family == other.family
             && self.size == other.size
+            && self.size_unit == other.size_unit
             && self.weight == other.weight
             && self.style == other.style
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    use super::*;
+    use crate::testing::{ModularWidget, TestHarness};
+    use crate::LifeCycle;
+
+    #[test]
+    fn font_availability() {
+        let results: Rc<Cell<Option<(bool, bool)>>> = Default::default();
+        let widget = ModularWidget::new(results.clone()).lifecycle_fn(|results, ctx, event, _| {
+            if let LifeCycle::WidgetAdded = event {
+                let missing = FontDescriptor::new(FontFamily::new_unchecked(
+                    "This Font Family Really Does Not Exist",
+                ));
+                let generic = FontDescriptor::new(FontFamily::SANS_SERIF);
+                results.set(Some((
+                    missing.is_available(ctx.text()),
+                    generic.is_available(ctx.text()),
+                )));
+            }
+        });
+
+        let _harness = TestHarness::create(widget);
+
+        assert_eq!(results.get(), Some((false, true)));
+    }
+
+    #[test]
+    fn rem_size() {
+        let env = Env::with_theme().adding(theme::TEXT_SIZE_NORMAL, 16.0);
+        let relative = FontDescriptor::new(FontFamily::SYSTEM_UI).with_size_rem(1.5);
+        let absolute = relative.clone().with_size(12.0);
+
+        assert_eq!(relative.resolved_size(&env), 24.0);
+        assert_eq!(absolute.resolved_size(&env), 12.0);
+        // The public size stays in points.
+        assert_eq!(relative.size, 22.5);
+        assert_eq!(absolute.size, 12.0);
+    }
+}
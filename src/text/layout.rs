@@ -45,10 +45,35 @@ pub struct TextLayout<T> {
     text_size_override: Option<KeyOrValue<f64>>,
     text_color: KeyOrValue<Color>,
     layout: Option<PietTextLayout>,
+    // The font and color `layout` was built with, after resolving them in the env.
+    // Keys can resolve to different values from one pass to the next, so we keep
+    // these around to know whether the layout is still valid.
+    resolved_style: Option<(FontDescriptor, Color)>,
     wrap_width: f64,
     alignment: TextAlignment,
+    alignment_direction: AlignmentDirection,
     links: Rc<[(Rect, usize)]>,
+    // Whether the first strong character of the text is right-to-left.
     text_is_rtl: bool,
+    #[cfg(test)]
+    pub(crate) rebuild_count: usize,
+}
+
+/// Which sides [`TextAlignment::Start`] and [`TextAlignment::End`] are on.
+///
+/// This only flips the alignment. The paragraph direction used to order mixed
+/// left-to-right and right-to-left text still comes from the first strong character
+/// of the text, since piet doesn't let us set it.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum AlignmentDirection {
+    /// Start is on the left, as in English.
+    Ltr,
+    /// Start is on the right, as in Arabic or Hebrew.
+    Rtl,
+    /// Follow the direction of the first strong character of the text, or left to
+    /// right if it has none. This is the default.
+    #[default]
+    Auto,
 }
 
 /// Metrics describing the layout text.
@@ -58,6 +83,10 @@ pub struct LayoutMetrics {
     pub size: Size,
     /// The distance from the nominal top of the layout to the first baseline.
     pub first_baseline: f64,
+    /// The distance from the nominal top of the layout to the last baseline.
+    ///
+    /// For single-line text, this is the same as `first_baseline`.
+    pub last_baseline: f64,
     /// The width of the layout, inclusive of trailing whitespace.
     pub trailing_whitespace_width: f64,
     //TODO: add inking_rect
@@ -76,10 +105,14 @@ impl<T> TextLayout<T> {
             text_color: crate::theme::TEXT_COLOR.into(),
             text_size_override: None,
             layout: None,
+            resolved_style: None,
             wrap_width: f64::INFINITY,
             alignment: Default::default(),
+            alignment_direction: Default::default(),
             links: Rc::new([]),
             text_is_rtl: false,
+            #[cfg(test)]
+            rebuild_count: 0,
         }
     }
 
@@ -146,6 +179,14 @@ impl<T> TextLayout<T> {
         }
     }
 
+    /// Set the [`AlignmentDirection`] for this layout.
+    pub fn set_alignment_direction(&mut self, direction: AlignmentDirection) {
+        if self.alignment_direction != direction {
+            self.alignment_direction = direction;
+            self.layout = None;
+        }
+    }
+
     /// Returns `true` if this layout's text appears to be right-to-left.
     ///
     /// See [`piet::util::first_strong_rtl`] for more information.
@@ -154,6 +195,26 @@ impl<T> TextLayout<T> {
     pub fn text_is_rtl(&self) -> bool {
         self.text_is_rtl
     }
+
+    /// The alignment to give the backend.
+    ///
+    /// Backends align from the direction of the first strong character, so when the
+    /// alignment direction is forced the other way, the start and end are swapped.
+    fn backend_alignment(&self) -> TextAlignment {
+        let aligns_rtl = match self.alignment_direction {
+            AlignmentDirection::Ltr => false,
+            AlignmentDirection::Rtl => true,
+            AlignmentDirection::Auto => self.text_is_rtl,
+        };
+        if aligns_rtl == self.text_is_rtl {
+            return self.alignment;
+        }
+        match self.alignment {
+            TextAlignment::Start => TextAlignment::End,
+            TextAlignment::End => TextAlignment::Start,
+            alignment => alignment,
+        }
+    }
 }
 
 impl<T: TextStorage> TextLayout<T> {
@@ -233,10 +294,13 @@ impl<T: TextStorage> TextLayout<T> {
 
         if let Some(layout) = self.layout.as_ref() {
             let first_baseline = layout.line_metric(0).unwrap().baseline;
+            let last_line = layout.line_metric(layout.line_count() - 1).unwrap();
+            let last_baseline = last_line.y_offset + last_line.baseline;
             let size = layout.size();
             LayoutMetrics {
                 size,
                 first_baseline,
+                last_baseline,
                 trailing_whitespace_width: layout.trailing_whitespace_width(),
             }
         } else {
@@ -280,6 +344,23 @@ impl<T: TextStorage> TextLayout<T> {
             .unwrap_or_default()
     }
 
+    /// The font the current layout was built with, as resolved in the [`Env`].
+    ///
+    /// This includes any size set with [`set_text_size`](Self::set_text_size), and
+    /// relative sizes are converted to points. Returns `None` if the layout hasn't been
+    /// built yet.
+    pub fn resolved_font(&self) -> Option<&FontDescriptor> {
+        self.resolved_style.as_ref().map(|(font, _)| font)
+    }
+
+    /// The default text color the current layout was built with, as resolved in the
+    /// [`Env`].
+    ///
+    /// Returns `None` if the layout hasn't been built yet.
+    pub fn resolved_text_color(&self) -> Option<Color> {
+        self.resolved_style.as_ref().map(|(_, color)| *color)
+    }
+
     /// Return a line suitable for underlining a range of text.
     ///
     /// This is really only intended to be used to indicate the composition
@@ -342,28 +423,39 @@ impl<T: TextStorage> TextLayout<T> {
     /// A simple way to ensure this is correct is to always call this method
     /// as part of your widget's [`layout`] method.
     ///
+    /// The existing layout is reused as long as the text, the wrap width, and the font
+    /// and color (as resolved in `env`) are unchanged, so calling this on every layout
+    /// pass is cheap.
+    ///
     /// [`layout`]: trait.Widget.html#method.layout
     pub fn rebuild_if_needed(&mut self, factory: &mut PietText, env: &Env) {
         if let Some(text) = &self.text {
-            if self.layout.is_none() {
-                let font = self.font.resolve(env);
-                let color = self.text_color.resolve(env);
-                let size_override = self.text_size_override.as_ref().map(|key| key.resolve(env));
-
-                let descriptor = if let Some(size) = size_override {
-                    font.with_size(size)
-                } else {
-                    font
-                };
+            let font = self.font.resolve(env);
+            let color = self.text_color.resolve(env);
+            let size_override = self.text_size_override.as_ref().map(|key| key.resolve(env));
+
+            let descriptor = if let Some(size) = size_override {
+                font.with_size(size)
+            } else {
+                let size = font.resolved_size(env);
+                font.with_size(size)
+            };
+
+            let style = (descriptor, color);
+            if self.resolved_style.as_ref() != Some(&style) {
+                self.layout = None;
+            }
 
+            if self.layout.is_none() {
+                let (descriptor, color) = &style;
                 let builder = factory
                     .new_text_layout(text.clone())
                     .max_width(self.wrap_width)
-                    .alignment(self.alignment)
+                    .alignment(self.backend_alignment())
                     .font(descriptor.family.clone(), descriptor.size)
                     .default_attribute(descriptor.weight)
                     .default_attribute(descriptor.style)
-                    .default_attribute(TextAttribute::TextColor(color));
+                    .default_attribute(TextAttribute::TextColor(*color));
                 let layout = text.add_attributes(builder, env).build().unwrap();
 
                 self.links = text
@@ -379,6 +471,11 @@ impl<T: TextStorage> TextLayout<T> {
                     .collect();
 
                 self.layout = Some(layout);
+                self.resolved_style = Some(style);
+                #[cfg(test)]
+                {
+                    self.rebuild_count += 1;
+                }
             }
         }
     }
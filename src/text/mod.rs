@@ -32,7 +32,7 @@ pub use storage::{ArcStr, TextStorage};
 pub use self::attribute::{Attribute, AttributeSpans, Link};
 pub use self::backspace::offset_for_delete_backwards;
 pub use self::editable_text::{EditableText, EditableTextCursor, StringCursor};
-pub use self::font_descriptor::FontDescriptor;
-pub use self::layout::{LayoutMetrics, TextLayout};
+pub use self::font_descriptor::{font_family_available, FontDescriptor};
+pub use self::layout::{AlignmentDirection, LayoutMetrics, TextLayout};
 pub use self::movement::movement;
 pub use crate::piet::{FontFamily, FontStyle, FontWeight, TextAlignment};
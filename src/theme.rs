@@ -6,6 +6,7 @@
 
 #![allow(missing_docs)]
 
+use crate::kurbo::RoundedRectRadii;
 use crate::piet::{Color, FontFamily, FontStyle, FontWeight};
 use crate::text::FontDescriptor;
 use crate::{Env, Insets, Key};
@@ -38,6 +39,11 @@ pub const BUTTON_BORDER_RADIUS: Key<f64> = Key::new("org.masonry.theme.button_ra
 pub const BUTTON_BORDER_WIDTH: Key<f64> = Key::new("org.masonry.theme.button_border_width");
 pub const BORDER_DARK: Key<Color> = Key::new("org.masonry.theme.border_dark");
 pub const BORDER_LIGHT: Key<Color> = Key::new("org.masonry.theme.border_light");
+/// The color used instead of solid background colors when a widget is disabled.
+pub const DISABLED_BACKGROUND_COLOR: Key<Color> =
+    Key::new("org.masonry.theme.disabled_background_color");
+/// The color used instead of border colors when a widget is disabled.
+pub const DISABLED_BORDER_COLOR: Key<Color> = Key::new("org.masonry.theme.disabled_border_color");
 #[deprecated(since = "0.8.0", note = "use SELECTED_TEXT_BACKGROUND_COLOR instead")]
 pub const SELECTION_COLOR: Key<Color> = SELECTED_TEXT_BACKGROUND_COLOR;
 pub const SELECTED_TEXT_BACKGROUND_COLOR: Key<Color> =
@@ -47,7 +53,63 @@ pub const SELECTED_TEXT_INACTIVE_BACKGROUND_COLOR: Key<Color> =
 pub const SELECTION_TEXT_COLOR: Key<Color> = Key::new("org.masonry.theme.selection_text_color");
 pub const CURSOR_COLOR: Key<Color> = Key::new("org.masonry.theme.cursor_color");
 
+/// The background color of containers like cards and panels.
+pub const SURFACE_COLOR: Key<Color> = Key::new("org.masonry.theme.surface_color");
+/// A background color for containers that should stand out from [`SURFACE_COLOR`].
+pub const SURFACE_VARIANT_COLOR: Key<Color> = Key::new("org.masonry.theme.surface_variant_color");
+/// The background color of elements signaling an error.
+pub const ERROR_COLOR: Key<Color> = Key::new("org.masonry.theme.error_color");
+/// The background of an [icon button](crate::widget::SizedBox::icon_button) under the mouse.
+pub const ICON_BUTTON_HOVER_COLOR: Key<Color> =
+    Key::new("org.masonry.theme.icon_button_hover_color");
+/// The corner radius of [icon buttons](crate::widget::SizedBox::icon_button).
+pub const ICON_BUTTON_RADIUS: Key<RoundedRectRadii> =
+    Key::new("org.masonry.theme.icon_button_radius");
+
+/// A factor applied to the duration of animations, like background transitions.
+///
+/// `1.0` is the normal speed, larger values slow animations down, and `0.0` disables
+/// them entirely, which is useful to implement a "reduce motion" setting.
+pub const ANIMATION_SCALE: Key<f64> = Key::new("org.masonry.theme.animation_scale");
+/// Whether the user asked for reduced motion.
+///
+/// When `true`, animations jump to their final state instead of playing, regardless
+/// of [`ANIMATION_SCALE`].
+pub const REDUCED_MOTION: Key<bool> = Key::new("org.masonry.theme.reduced_motion");
+
+/// The semantic role of a background, which maps to a theme color.
+///
+/// Widgets painting a background by role, eg with
+/// [`BackgroundBrush::role`](crate::widget::BackgroundBrush::role), follow theme
+/// changes without knowing which key holds each color.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum BackgroundRole {
+    /// The default background of containers; [`SURFACE_COLOR`].
+    Surface,
+    /// A background set apart from the default one; [`SURFACE_VARIANT_COLOR`].
+    SurfaceVariant,
+    /// The accent color of the theme; [`PRIMARY_DARK`].
+    Primary,
+    /// A background signaling an error; [`ERROR_COLOR`].
+    Error,
+}
+
+impl BackgroundRole {
+    /// The key of the color this role maps to.
+    pub const fn key(self) -> Key<Color> {
+        match self {
+            BackgroundRole::Surface => SURFACE_COLOR,
+            BackgroundRole::SurfaceVariant => SURFACE_VARIANT_COLOR,
+            BackgroundRole::Primary => PRIMARY_DARK,
+            BackgroundRole::Error => ERROR_COLOR,
+        }
+    }
+}
+
 pub const TEXT_SIZE_NORMAL: Key<f64> = Key::new("org.masonry.theme.text_size_normal");
+/// The value of [`TEXT_SIZE_NORMAL`] in the default theme.
+pub(crate) const DEFAULT_TEXT_SIZE_NORMAL: f64 = 15.0;
 pub const TEXT_SIZE_LARGE: Key<f64> = Key::new("org.masonry.theme.text_size_large");
 pub const BASIC_WIDGET_HEIGHT: Key<f64> = Key::new("org.masonry.theme.basic_widget_height");
 
@@ -89,6 +151,17 @@ pub const SCROLLBAR_EDGE_WIDTH: Key<f64> = Key::new("org.masonry.theme.scrollbar
 /// scrollbar's primary axis.
 pub const SCROLLBAR_MIN_SIZE: Key<f64> = Key::new("org.linebender.theme.scrollbar_min_size");
 
+/// The factor to apply to animation durations, taking [`REDUCED_MOTION`] into account.
+///
+/// A result of `0.0` means animations should be skipped entirely.
+pub(crate) fn animation_scale(env: &Env) -> f64 {
+    if env.get(REDUCED_MOTION) {
+        0.0
+    } else {
+        env.get(ANIMATION_SCALE).max(0.0)
+    }
+}
+
 /// An initial theme.
 pub(crate) fn add_to_env(env: Env) -> Env {
     env.adding(WINDOW_BACKGROUND_COLOR, Color::rgb8(0x29, 0x29, 0x29))
@@ -112,6 +185,8 @@ pub(crate) fn add_to_env(env: Env) -> Env {
         .adding(BUTTON_BORDER_WIDTH, 2.)
         .adding(BORDER_DARK, Color::rgb8(0x3a, 0x3a, 0x3a))
         .adding(BORDER_LIGHT, Color::rgb8(0xa1, 0xa1, 0xa1))
+        .adding(DISABLED_BACKGROUND_COLOR, Color::grey8(0x38))
+        .adding(DISABLED_BORDER_COLOR, Color::grey8(0x5a))
         .adding(
             SELECTED_TEXT_BACKGROUND_COLOR,
             Color::rgb8(0x43, 0x70, 0xA8),
@@ -119,7 +194,17 @@ pub(crate) fn add_to_env(env: Env) -> Env {
         .adding(SELECTED_TEXT_INACTIVE_BACKGROUND_COLOR, Color::grey8(0x74))
         .adding(SELECTION_TEXT_COLOR, Color::rgb8(0x00, 0x00, 0x00))
         .adding(CURSOR_COLOR, Color::WHITE)
-        .adding(TEXT_SIZE_NORMAL, 15.0)
+        .adding(ANIMATION_SCALE, 1.0)
+        .adding(REDUCED_MOTION, false)
+        .adding(SURFACE_COLOR, Color::rgb8(0x31, 0x31, 0x31))
+        .adding(SURFACE_VARIANT_COLOR, Color::rgb8(0x3a, 0x3a, 0x3a))
+        .adding(ERROR_COLOR, Color::rgb8(0xcf, 0x66, 0x79))
+        .adding(ICON_BUTTON_HOVER_COLOR, Color::rgb8(0x3a, 0x3a, 0x3a))
+        .adding(
+            ICON_BUTTON_RADIUS,
+            RoundedRectRadii::from_single_radius(4.0),
+        )
+        .adding(TEXT_SIZE_NORMAL, DEFAULT_TEXT_SIZE_NORMAL)
         .adding(TEXT_SIZE_LARGE, 24.0)
         .adding(BASIC_WIDGET_HEIGHT, 18.0)
         .adding(WIDE_WIDGET_WIDTH, 100.)
@@ -0,0 +1,1193 @@
+// This software is licensed under Apache License 2.0 and distributed on an
+// "as-is" basis without warranties of any kind. See the LICENSE file for
+// details.
+
+//! Backgrounds painted by a [`SizedBox`](crate::widget::SizedBox).
+
+use std::borrow::Cow;
+use std::hash::{Hash, Hasher};
+use std::rc::Rc;
+use std::time::Duration;
+
+use smallvec::{smallvec, SmallVec};
+use tracing::warn;
+
+use crate::kurbo::{Affine, Rect, RoundedRectRadii, Shape};
+use crate::piet::{
+    Color, Error as PietError, FixedGradient, FixedLinearGradient, GradientStop, GradientStops,
+    ImageBuf, ImageFormat, InterpolationMode, IntoBrush, LinearGradient, NullImage, NullText,
+    NullTextLayout, PaintBrush, PietImage, RadialGradient, StrokeStyle, UnitPoint,
+};
+use crate::theme::BackgroundRole;
+use crate::widget::sized_box::check_border_width;
+use crate::{Env, Key, KeyOrValue, PaintCtx, Point, RenderContext, Size};
+
+/// Something that can be used as the background for a widget.
+#[non_exhaustive]
+#[allow(missing_docs)]
+#[allow(clippy::type_complexity)]
+pub enum BackgroundBrush {
+    Color(KeyOrValue<Color>),
+    Linear(LinearGradient),
+    Radial(RadialGradient),
+    Fixed(FixedGradient),
+    PainterFn(Box<dyn FnMut(&mut PaintCtx, &Env)>),
+    Custom(Box<dyn CustomBackground>),
+    Shimmer(Shimmer),
+    Rotating(RotatingGradient),
+    Image(ImageBuf),
+}
+
+/// A reusable background painter, which can be stored in a [`BackgroundBrush`].
+///
+/// This is an alternative to [`BackgroundBrush::PainterFn`] for backgrounds that
+/// carry their own state, or that you want to give a name.
+pub trait CustomBackground {
+    /// Paint the background into `bounds`, which covers the whole widget.
+    fn paint(&mut self, ctx: &mut PaintCtx, bounds: Rect, env: &Env);
+
+    /// Whether `paint` always covers all of `bounds` with opaque colors.
+    ///
+    /// See [`BackgroundBrush::is_opaque`]. The default is `false`, which is always safe.
+    fn is_opaque(&self, env: &Env) -> bool {
+        let _ = env;
+        false
+    }
+}
+
+/// An animated background which sweeps a band of light across the widget.
+///
+/// This is mostly useful for placeholder "skeleton" boxes shown while content is
+/// loading. Create one with [`BackgroundBrush::shimmer`].
+///
+/// A [`SizedBox`](crate::widget::SizedBox) with a shimmer background requests animation frames for as long as
+/// it's painted. If the box is scrolled out of view (and therefore not painted) the
+/// animation stops, and starts again the next time the box receives an event.
+///
+/// The sweep speed is divided by [`theme::ANIMATION_SCALE`]. If that key is `0.0` or
+/// [`theme::REDUCED_MOTION`] is set, the band doesn't move.
+///
+/// [`theme::ANIMATION_SCALE`]: crate::theme::ANIMATION_SCALE
+/// [`theme::REDUCED_MOTION`]: crate::theme::REDUCED_MOTION
+pub struct Shimmer {
+    base: KeyOrValue<Color>,
+    highlight: KeyOrValue<Color>,
+    speed: f64,
+    degrees: f64,
+    pub(crate) progress: f64,
+}
+
+/// An animated linear gradient which turns around the center of the widget.
+///
+/// This gives a rotating sheen, eg for highlighted or active elements. Create one
+/// with [`BackgroundBrush::rotating_linear`].
+///
+/// Like a [`Shimmer`], it's only animated while the box is painted, it follows
+/// [`theme::ANIMATION_SCALE`] and [`theme::REDUCED_MOTION`], and it can be paused with
+/// [`SizedBoxMut::set_animation_paused`].
+///
+/// [`SizedBoxMut::set_animation_paused`]: crate::widget::sized_box::SizedBoxMut::set_animation_paused
+/// [`theme::ANIMATION_SCALE`]: crate::theme::ANIMATION_SCALE
+/// [`theme::REDUCED_MOTION`]: crate::theme::REDUCED_MOTION
+pub struct RotatingGradient {
+    stops: Vec<GradientStop>,
+    period: Duration,
+    /// The current direction of the gradient, in degrees as in
+    /// [`BackgroundBrush::linear_angle`].
+    pub(crate) degrees: f64,
+}
+
+/// A dashed outline of a [`SizedBox`](crate::widget::SizedBox), whose dashes may move.
+///
+/// This is used both for marching ants, painted over the child, and for dashed borders,
+/// painted under it.
+pub(crate) struct MarchingAnts {
+    pub(crate) color: KeyOrValue<Color>,
+    pub(crate) width: f64,
+    pub(crate) dashes: Rc<[f64]>,
+    /// How far the dashes move per second, in pixels.
+    pub(crate) speed: f64,
+    /// The current dash offset, in pixels.
+    pub(crate) offset: f64,
+}
+
+/// The backend image made for an [image background](BackgroundBrush::Image).
+pub(crate) struct ImageCache {
+    /// The image `image` was made from. The cache is only used for the same buffer.
+    pub(crate) source: ImageBuf,
+    image: PietImage,
+}
+
+/// An error returned by [`BackgroundBrush::linear_stops`] when the stops are invalid.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[non_exhaustive]
+pub enum GradientError {
+    /// There were no stops.
+    NoStops,
+    /// A stop offset was NaN.
+    NanOffset,
+}
+
+/// An error returned by [`BackgroundBrush::from_png_bytes`] and
+/// [`BackgroundBrush::from_jpeg_bytes`] when the image can't be decoded.
+#[cfg(any(feature = "png", feature = "jpeg"))]
+#[derive(Debug)]
+pub struct ImageDecodeError(image::ImageError);
+
+/// The color space the colors of a gradient are mixed in.
+///
+/// This changes what a gradient looks like between its stops. Mixing red and blue
+/// in sRGB goes through a dark purple, while OkLab keeps the lightness more even.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum ColorSpace {
+    /// Mix the sRGB components of the colors, like piet does. This is the default.
+    #[default]
+    Srgb,
+    /// Mix the colors in the perceptual [OkLab] color space.
+    ///
+    /// [OkLab]: https://bottosson.github.io/posts/oklab/
+    Oklab,
+}
+
+// --- BackgroundBrush ---
+
+impl BackgroundBrush {
+    /// Create an animated brush sweeping a band of `highlight` color over a `base` color.
+    ///
+    /// `speed` is the number of sweeps per second. The band goes left to right by
+    /// default; use [`Shimmer::with_angle`] to change its direction.
+    ///
+    /// The returned [`Shimmer`] can be passed anywhere a `BackgroundBrush` is expected.
+    pub fn shimmer(
+        base: impl Into<KeyOrValue<Color>>,
+        highlight: impl Into<KeyOrValue<Color>>,
+        speed: f64,
+    ) -> Shimmer {
+        Shimmer {
+            base: base.into(),
+            highlight: highlight.into(),
+            speed,
+            degrees: 90.0,
+            progress: 0.0,
+        }
+    }
+
+    /// A darker version of this color brush, eg for the hover state of a button.
+    ///
+    /// The lightness of the color is multiplied by `1.0 - factor` in OkLab, so that
+    /// `darken(0.2)` looks about 20% darker whatever the hue. `factor` is clamped to
+    /// `0.0..=1.0`; `1.0` gives black.
+    ///
+    /// Only concrete colors can be adjusted: other brushes, including colors read from
+    /// the [`Env`], are returned unchanged with a warning.
+    pub fn darken(self, factor: f64) -> Self {
+        let factor = clamp_factor(factor);
+        self.map_lightness("darken", |lightness| lightness * (1.0 - factor))
+    }
+
+    /// A lighter version of this color brush.
+    ///
+    /// This is the opposite of [`darken`](Self::darken): the distance between the
+    /// lightness of the color and white is multiplied by `1.0 - factor`, so `1.0`
+    /// gives white.
+    pub fn lighten(self, factor: f64) -> Self {
+        let factor = clamp_factor(factor);
+        self.map_lightness("lighten", |lightness| {
+            1.0 - (1.0 - lightness) * (1.0 - factor)
+        })
+    }
+
+    fn map_lightness(self, name: &str, map: impl Fn(f64) -> f64) -> Self {
+        match self {
+            BackgroundBrush::Color(KeyOrValue::Concrete(color)) => {
+                let [lightness, a, b, alpha] = srgb_to_oklab(color);
+                let color = oklab_to_srgb([map(lightness), a, b, alpha]);
+                BackgroundBrush::Color(KeyOrValue::Concrete(color))
+            }
+            brush => {
+                warn!("BackgroundBrush::{} only applies to concrete colors", name);
+                brush
+            }
+        }
+    }
+
+    /// Decode a PNG image into a brush which stretches it over the whole box.
+    ///
+    /// This is meant for images embedded with `include_bytes!`, so they don't need a
+    /// decoder of their own. It's only available with the `png` feature.
+    #[cfg(feature = "png")]
+    pub fn from_png_bytes(bytes: &[u8]) -> Result<Self, ImageDecodeError> {
+        decode_image(bytes, image::ImageFormat::Png)
+    }
+
+    /// Decode a JPEG image into a brush which stretches it over the whole box.
+    ///
+    /// See [`from_png_bytes`](Self::from_png_bytes). It's only available with the
+    /// `jpeg` feature.
+    #[cfg(feature = "jpeg")]
+    pub fn from_jpeg_bytes(bytes: &[u8]) -> Result<Self, ImageDecodeError> {
+        decode_image(bytes, image::ImageFormat::Jpeg)
+    }
+
+    /// Create a linear gradient which makes a full turn every `period`.
+    ///
+    /// The gradient starts going from left to right, and turns clockwise. A zero
+    /// `period` is ignored with a warning, and the gradient doesn't turn.
+    ///
+    /// The returned [`RotatingGradient`] can be passed anywhere a `BackgroundBrush` is
+    /// expected.
+    pub fn rotating_linear(stops: impl GradientStops, period: Duration) -> RotatingGradient {
+        if period.is_zero() {
+            warn!("Rotating gradient has a zero period, it won't turn.");
+        }
+        RotatingGradient {
+            stops: stops.to_vec(),
+            period,
+            degrees: 90.0,
+        }
+    }
+
+    /// Create a linear gradient from a list of `(offset, color)` stops.
+    ///
+    /// `degrees` is the direction of the gradient, as in
+    /// [`linear_angle`](Self::linear_angle): `90.0` goes from left to right.
+    ///
+    /// Unlike a raw [`LinearGradient`], the stops don't need to be sorted: they're
+    /// sorted by offset, and offsets outside of `0.0..=1.0` are clamped to that range.
+    /// Stops with the same offset are kept in the order given, with a warning.
+    ///
+    /// Returns an error if `stops` is empty or an offset is NaN.
+    pub fn linear_stops(
+        degrees: f64,
+        stops: Vec<(f64, Color)>,
+    ) -> Result<BackgroundBrush, GradientError> {
+        Self::linear_stops_in(degrees, stops, ColorSpace::Srgb)
+    }
+
+    /// Create a linear gradient from a list of `(offset, color)` stops, mixing the
+    /// colors in `space`.
+    ///
+    /// This is the same as [`linear_stops`](Self::linear_stops), except for the color
+    /// space. piet always mixes colors in sRGB, so for other color spaces extra stops
+    /// are added between the given ones, with colors mixed in that space.
+    pub fn linear_stops_in(
+        degrees: f64,
+        stops: Vec<(f64, Color)>,
+        space: ColorSpace,
+    ) -> Result<BackgroundBrush, GradientError> {
+        if stops.is_empty() {
+            return Err(GradientError::NoStops);
+        }
+        if stops.iter().any(|(offset, _)| offset.is_nan()) {
+            return Err(GradientError::NanOffset);
+        }
+
+        let mut stops: Vec<GradientStop> = stops
+            .into_iter()
+            .map(|(offset, color)| GradientStop {
+                pos: offset.clamp(0.0, 1.0) as f32,
+                color,
+            })
+            .collect();
+        // The sort is stable, so stops with the same offset keep their order.
+        stops.sort_by(|a, b| a.pos.total_cmp(&b.pos));
+        if stops.windows(2).any(|pair| pair[0].pos == pair[1].pos) {
+            warn!("Gradient has several stops at the same offset: {:?}", stops);
+        }
+        let stops = match space {
+            ColorSpace::Srgb => stops,
+            ColorSpace::Oklab => oklab_stops(&stops),
+        };
+
+        Ok(BackgroundBrush::Linear(linear_gradient_at_angle(
+            degrees, stops,
+        )))
+    }
+
+    /// Create a linear gradient going in the direction of `degrees`.
+    ///
+    /// As in CSS, `0.0` points up, and angles increase clockwise: `90.0` goes from
+    /// left to right. The gradient goes through the center of the box and stretches
+    /// with it, so a `45.0` gradient goes from the bottom left corner to the top right
+    /// one whatever the box's aspect ratio.
+    pub fn linear_angle(degrees: f64, stops: impl GradientStops) -> BackgroundBrush {
+        BackgroundBrush::Linear(linear_gradient_at_angle(degrees, stops.to_vec()))
+    }
+
+    /// Create a brush painting the theme color for the given role.
+    ///
+    /// The color is looked up in the [`Env`] when painting, so it follows theme changes.
+    pub fn role(role: BackgroundRole) -> BackgroundBrush {
+        BackgroundBrush::Color(role.key().into())
+    }
+
+    /// Whether this brush covers the whole widget with fully opaque colors.
+    ///
+    /// This is a hint for compositing: nothing behind an opaque background can be
+    /// seen, so it doesn't need to be painted. It's conservative: a `false` result
+    /// doesn't mean the brush is transparent anywhere.
+    ///
+    /// Colors and shimmers are opaque if their colors are, and fixed and rotating
+    /// gradients if all of their stops are. Images are opaque if they have no alpha
+    /// channel. Piet doesn't expose the stops of
+    /// [`LinearGradient`] and [`RadialGradient`], and `PainterFn` brushes can't be
+    /// inspected, so those are never opaque. `Custom` brushes are opaque if they say so
+    /// with [`CustomBackground::is_opaque`].
+    pub fn is_opaque(&self, env: &Env) -> bool {
+        fn is_opaque_color(color: Color) -> bool {
+            color.as_rgba8().3 == u8::MAX
+        }
+
+        match self {
+            Self::Color(color) => is_opaque_color(color.resolve(env)),
+            Self::Fixed(FixedGradient::Linear(gradient)) => gradient
+                .stops
+                .iter()
+                .all(|stop| is_opaque_color(stop.color)),
+            Self::Fixed(FixedGradient::Radial(gradient)) => gradient
+                .stops
+                .iter()
+                .all(|stop| is_opaque_color(stop.color)),
+            Self::Linear(_) | Self::Radial(_) | Self::PainterFn(_) => false,
+            Self::Custom(custom) => custom.is_opaque(env),
+            Self::Shimmer(shimmer) => {
+                is_opaque_color(shimmer.base.resolve(env))
+                    && is_opaque_color(shimmer.highlight.resolve(env))
+            }
+            Self::Rotating(rotating) => rotating
+                .stops
+                .iter()
+                .all(|stop| is_opaque_color(stop.color)),
+            Self::Image(image) => {
+                matches!(image.format(), ImageFormat::Rgb | ImageFormat::Grayscale)
+            }
+        }
+    }
+
+    /// Whether this brush changes over time, and needs animation frames.
+    pub fn is_animated(&self) -> bool {
+        matches!(self, Self::Shimmer(_) | Self::Rotating(_))
+    }
+
+    /// The raw [`Env`] keys this brush resolves when painting.
+    pub(crate) fn raw_keys(&self) -> Vec<&'static str> {
+        match self {
+            Self::Color(color) => color.raw_key().into_iter().collect(),
+            Self::Shimmer(shimmer) => [&shimmer.base, &shimmer.highlight]
+                .into_iter()
+                .filter_map(KeyOrValue::raw_key)
+                .collect(),
+            _ => Vec::new(),
+        }
+    }
+
+    /// Feed this brush's settings to `state`, with keys resolved in `env`.
+    ///
+    /// The progress of animated brushes is left out.
+    pub(crate) fn hash_resolved(&self, env: &Env, state: &mut impl Hasher) {
+        let kind: u8 = match self {
+            Self::Color(_) => 0,
+            Self::Linear(_) => 1,
+            Self::Radial(_) => 2,
+            Self::Fixed(_) => 3,
+            Self::PainterFn(_) => 4,
+            Self::Custom(_) => 5,
+            Self::Shimmer(_) => 6,
+            Self::Rotating(_) => 7,
+            Self::Image(_) => 8,
+        };
+        kind.hash(state);
+        match self {
+            Self::Color(color) => color.resolve(env).as_rgba_u32().hash(state),
+            Self::Linear(gradient) => {
+                for gradient in resolve_in_reference_rects(gradient) {
+                    hash_fixed_gradient(&gradient, state);
+                }
+            }
+            Self::Radial(gradient) => {
+                for gradient in resolve_in_reference_rects(gradient) {
+                    hash_fixed_gradient(&gradient, state);
+                }
+            }
+            Self::Fixed(gradient) => hash_fixed_gradient(gradient, state),
+            Self::Shimmer(shimmer) => {
+                shimmer.base.resolve(env).as_rgba_u32().hash(state);
+                shimmer.highlight.resolve(env).as_rgba_u32().hash(state);
+                shimmer.speed.to_bits().hash(state);
+                shimmer.degrees.to_bits().hash(state);
+            }
+            Self::Rotating(rotating) => {
+                hash_stops(&rotating.stops, state);
+                rotating.period.as_nanos().hash(state);
+            }
+            // See PartialEq: images are compared by their shared pixel buffer.
+            Self::Image(image) => (image.raw_pixels().as_ptr() as usize).hash(state),
+            Self::PainterFn(_) | Self::Custom(_) => {}
+        }
+    }
+
+    /// Move animated brushes forward by `interval` nanoseconds.
+    pub(crate) fn advance(&mut self, interval: u64) {
+        match self {
+            Self::Shimmer(shimmer) => shimmer.advance(interval),
+            Self::Rotating(rotating) => rotating.advance(interval),
+            _ => {}
+        }
+    }
+
+    /// Like [`paint`](Self::paint), but images are only made into backend images once.
+    ///
+    /// `cache` keeps the backend image for the last image brush painted with it.
+    pub(crate) fn paint_cached(
+        &mut self,
+        ctx: &mut PaintCtx,
+        env: &Env,
+        cache: &mut Option<ImageCache>,
+    ) {
+        let source = match self {
+            Self::Image(source) => source,
+            _ => return self.paint(ctx, env),
+        };
+        let cache = match cache {
+            Some(cache) if cache.source.ptr_eq(source) => cache,
+            _ => cache.insert(ImageCache {
+                source: source.clone(),
+                image: source.to_image(ctx.render_ctx),
+            }),
+        };
+        let bounds = ctx.size().to_rect();
+        ctx.draw_image(&cache.image, bounds, InterpolationMode::Bilinear);
+    }
+
+    /// Draw this brush into a provided [`PaintCtx`].
+    pub fn paint(&mut self, ctx: &mut PaintCtx, env: &Env) {
+        let bounds = ctx.size().to_rect();
+        match self {
+            Self::Color(color) => ctx.fill(bounds, &color.resolve(env)),
+            Self::Linear(grad) => ctx.fill(bounds, grad),
+            Self::Radial(grad) => ctx.fill(bounds, grad),
+            Self::Fixed(grad) => ctx.fill(bounds, grad),
+            Self::PainterFn(painter) => painter(ctx, env),
+            Self::Custom(custom) => custom.paint(ctx, bounds, env),
+            Self::Shimmer(shimmer) => shimmer.paint(ctx, bounds, env),
+            Self::Rotating(rotating) => {
+                let gradient = linear_gradient_at_angle(rotating.degrees, rotating.stops.clone());
+                ctx.fill(bounds, &gradient);
+            }
+            Self::Image(image) => {
+                let image = image.to_image(ctx.render_ctx);
+                ctx.draw_image(&image, bounds, InterpolationMode::Bilinear);
+            }
+        }
+    }
+}
+
+// --- Shimmer ---
+
+/// The width of the shimmer band, as a fraction of the gradient length.
+const SHIMMER_BAND_WIDTH: f64 = 0.3;
+
+impl Shimmer {
+    /// Builder-style method for setting the direction the band sweeps in, in degrees.
+    ///
+    /// This uses the same convention as [`BackgroundBrush::linear_angle`]: `90.0`
+    /// sweeps from left to right, and `180.0` from top to bottom.
+    pub fn with_angle(mut self, degrees: f64) -> Self {
+        self.degrees = degrees;
+        self
+    }
+
+    fn advance(&mut self, interval: u64) {
+        let elapsed = (interval as f64) * 1e-9;
+        self.progress = (self.progress + elapsed * self.speed).rem_euclid(1.0);
+    }
+
+    fn paint(&self, ctx: &mut PaintCtx, bounds: Rect, env: &Env) {
+        let base = self.base.resolve(env);
+        let highlight = self.highlight.resolve(env);
+
+        // The band starts fully before the gradient and ends fully after it.
+        let center = self.progress * (1.0 + 2.0 * SHIMMER_BAND_WIDTH) - SHIMMER_BAND_WIDTH;
+        let stops = vec![
+            GradientStop {
+                pos: (center - SHIMMER_BAND_WIDTH).clamp(0.0, 1.0) as f32,
+                color: base,
+            },
+            GradientStop {
+                pos: center.clamp(0.0, 1.0) as f32,
+                color: highlight,
+            },
+            GradientStop {
+                pos: (center + SHIMMER_BAND_WIDTH).clamp(0.0, 1.0) as f32,
+                color: base,
+            },
+        ];
+
+        ctx.fill(bounds, &linear_gradient_at_angle(self.degrees, stops));
+    }
+}
+
+// --- RotatingGradient ---
+
+impl RotatingGradient {
+    fn advance(&mut self, interval: u64) {
+        if self.period.is_zero() {
+            return;
+        }
+        let turns = interval as f64 / self.period.as_nanos() as f64;
+        self.degrees = (self.degrees + turns * 360.0).rem_euclid(360.0);
+    }
+}
+
+// --- MarchingAnts ---
+
+impl MarchingAnts {
+    /// Build an outline, or `None` with a warning if the width or dashes are invalid.
+    pub(crate) fn new(
+        color: KeyOrValue<Color>,
+        width: f64,
+        dashes: &[f64],
+        speed: f64,
+    ) -> Option<Self> {
+        if let Err(err) = check_border_width(width) {
+            warn!("Invalid marching ants width: {}", err);
+            return None;
+        }
+        let is_valid_dash = |length: &f64| length.is_finite() && *length >= 0.0;
+        if !dashes.iter().all(is_valid_dash) || dashes.iter().sum::<f64>() <= 0.0 {
+            warn!("Invalid marching ants dash pattern: {:?}", dashes);
+            return None;
+        }
+        Some(MarchingAnts {
+            color,
+            width,
+            dashes: dashes.into(),
+            speed: if speed.is_finite() { speed } else { 0.0 },
+            offset: 0.0,
+        })
+    }
+
+    /// Stroke the outline inside a box of `size`.
+    pub(crate) fn paint(
+        &self,
+        rc: &mut impl RenderContext,
+        size: Size,
+        corner_radius: RoundedRectRadii,
+        color: Color,
+    ) {
+        let mut style = StrokeStyle::new();
+        style.set_dash_pattern(self.dashes.clone());
+        style.set_dash_offset(self.offset);
+        let outline = size
+            .to_rect()
+            .inset(-self.width / 2.0)
+            .to_rounded_rect(corner_radius);
+        rc.stroke_styled(outline, &color, self.width, &style);
+    }
+
+    /// Move the dashes forward by `interval` nanoseconds.
+    pub(crate) fn advance(&mut self, interval: u64) {
+        let period: f64 = self.dashes.iter().sum();
+        let distance = self.speed * Duration::from_nanos(interval).as_secs_f64();
+        self.offset = (self.offset + distance).rem_euclid(period);
+    }
+}
+
+impl std::fmt::Display for GradientError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            GradientError::NoStops => write!(f, "Gradient has no stops"),
+            GradientError::NanOffset => write!(f, "Gradient stop offset is NaN"),
+        }
+    }
+}
+
+impl std::error::Error for GradientError {}
+
+#[cfg(any(feature = "png", feature = "jpeg"))]
+impl std::fmt::Display for ImageDecodeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "Failed to decode background image: {}", self.0)
+    }
+}
+
+#[cfg(any(feature = "png", feature = "jpeg"))]
+impl std::error::Error for ImageDecodeError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&self.0)
+    }
+}
+
+/// The number of sRGB segments each pair of stops is split into to mix them in OkLab.
+const OKLAB_SEGMENTS: usize = 16;
+
+/// Stops which look like `stops` mixed in OkLab, when piet mixes them in sRGB.
+fn oklab_stops(stops: &[GradientStop]) -> Vec<GradientStop> {
+    let mut result = Vec::with_capacity(stops.len() * OKLAB_SEGMENTS);
+    for pair in stops.windows(2) {
+        let (start, end) = (srgb_to_oklab(pair[0].color), srgb_to_oklab(pair[1].color));
+        for i in 0..OKLAB_SEGMENTS {
+            let t = i as f64 / OKLAB_SEGMENTS as f64;
+            let mut mixed = [0.0; 4];
+            for (component, (start, end)) in mixed.iter_mut().zip(start.iter().zip(&end)) {
+                *component = start + (end - start) * t;
+            }
+            result.push(GradientStop {
+                pos: pair[0].pos + (pair[1].pos - pair[0].pos) * t as f32,
+                color: oklab_to_srgb(mixed),
+            });
+        }
+    }
+    result.extend(stops.last().cloned());
+    result
+}
+
+/// Clamp the factor of `darken` and `lighten` to `0.0..=1.0`, NaN counting as zero.
+fn clamp_factor(factor: f64) -> f64 {
+    if factor.is_nan() {
+        0.0
+    } else {
+        factor.clamp(0.0, 1.0)
+    }
+}
+
+/// Convert a color to OkLab, as `[lightness, a, b, alpha]`.
+fn srgb_to_oklab(color: Color) -> [f64; 4] {
+    fn to_linear(c: f64) -> f64 {
+        if c <= 0.04045 {
+            c / 12.92
+        } else {
+            ((c + 0.055) / 1.055).powf(2.4)
+        }
+    }
+
+    let (r, g, b, alpha) = color.as_rgba();
+    let (r, g, b) = (to_linear(r), to_linear(g), to_linear(b));
+    let l = (0.412_221_470_8 * r + 0.536_332_536_3 * g + 0.051_445_992_9 * b).cbrt();
+    let m = (0.211_903_498_2 * r + 0.680_699_545_1 * g + 0.107_396_956_6 * b).cbrt();
+    let s = (0.088_302_461_9 * r + 0.281_718_837_6 * g + 0.629_978_700_5 * b).cbrt();
+    [
+        0.210_454_255_3 * l + 0.793_617_785_0 * m - 0.004_072_046_8 * s,
+        1.977_998_495_1 * l - 2.428_592_205_0 * m + 0.450_593_709_9 * s,
+        0.025_904_037_1 * l + 0.782_771_766_2 * m - 0.808_675_766_0 * s,
+        alpha,
+    ]
+}
+
+/// Convert `[lightness, a, b, alpha]` in OkLab to a color, clamping it to sRGB.
+fn oklab_to_srgb([lightness, a, b, alpha]: [f64; 4]) -> Color {
+    fn from_linear(c: f64) -> f64 {
+        let c = c.clamp(0.0, 1.0);
+        if c <= 0.003_130_8 {
+            12.92 * c
+        } else {
+            1.055 * c.powf(1.0 / 2.4) - 0.055
+        }
+    }
+
+    let l = (lightness + 0.396_337_777_4 * a + 0.215_803_757_3 * b).powi(3);
+    let m = (lightness - 0.105_561_345_8 * a - 0.063_854_172_8 * b).powi(3);
+    let s = (lightness - 0.089_484_177_5 * a - 1.291_485_548_0 * b).powi(3);
+    Color::rgba(
+        from_linear(4.076_741_662_1 * l - 3.307_711_591_3 * m + 0.230_969_929_2 * s),
+        from_linear(-1.268_438_004_6 * l + 2.609_757_401_1 * m - 0.341_319_396_5 * s),
+        from_linear(-0.004_196_086_3 * l - 0.703_418_614_7 * m + 1.707_614_701_0 * s),
+        alpha,
+    )
+}
+
+/// Decode `bytes` as an image in `format`, keeping the alpha channel only if it has one.
+#[cfg(any(feature = "png", feature = "jpeg"))]
+fn decode_image(
+    bytes: &[u8],
+    format: image::ImageFormat,
+) -> Result<BackgroundBrush, ImageDecodeError> {
+    let image = image::load_from_memory_with_format(bytes, format).map_err(ImageDecodeError)?;
+    let (width, height) = (image.width() as usize, image.height() as usize);
+    let image = if image.color().has_alpha() {
+        let pixels = image.into_rgba8().into_raw();
+        ImageBuf::from_raw(pixels, ImageFormat::RgbaSeparate, width, height)
+    } else {
+        let pixels = image.into_rgb8().into_raw();
+        ImageBuf::from_raw(pixels, ImageFormat::Rgb, width, height)
+    };
+    Ok(BackgroundBrush::Image(image))
+}
+
+/// A gradient going through the center of the painted area, in the direction of `degrees`.
+///
+/// As in CSS, `0.0` points up, and angles increase clockwise.
+fn linear_gradient_at_angle(degrees: f64, stops: Vec<GradientStop>) -> LinearGradient {
+    // An angle of zero radians points right, and y goes down.
+    let angle = (degrees - 90.0).to_radians();
+    let (dx, dy) = (angle.cos() / 2.0, angle.sin() / 2.0);
+    LinearGradient::new(
+        UnitPoint::new(0.5 - dx, 0.5 - dy),
+        UnitPoint::new(0.5 + dx, 0.5 + dy),
+        stops,
+    )
+}
+
+/// Brushes are equal if they would paint the same thing.
+///
+/// Gradients are compared stop by stop, and shimmers by their settings (ignoring how far
+/// along the animation is). Images are equal if they share their pixel buffer, eg when
+/// one is a clone of the other. `PainterFn` and `Custom` brushes can't be inspected, so
+/// they are never equal, not even to themselves.
+impl PartialEq for BackgroundBrush {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Self::Color(a), Self::Color(b)) => a == b,
+            (Self::Linear(a), Self::Linear(b)) => {
+                resolve_in_reference_rects(a) == resolve_in_reference_rects(b)
+            }
+            (Self::Radial(a), Self::Radial(b)) => {
+                resolve_in_reference_rects(a) == resolve_in_reference_rects(b)
+            }
+            (Self::Fixed(a), Self::Fixed(b)) => same_fixed_gradient(a, b),
+            (Self::Shimmer(a), Self::Shimmer(b)) => {
+                a.base == b.base
+                    && a.highlight == b.highlight
+                    && a.speed == b.speed
+                    && a.degrees == b.degrees
+            }
+            (Self::Rotating(a), Self::Rotating(b)) => {
+                same_stops(&a.stops, &b.stops) && a.period == b.period
+            }
+            (Self::Image(a), Self::Image(b)) => a.ptr_eq(b),
+            _ => false,
+        }
+    }
+}
+
+/// Whether two lists of gradient stops are the same, stop by stop.
+fn same_stops(a: &[GradientStop], b: &[GradientStop]) -> bool {
+    a.len() == b.len()
+        && a.iter()
+            .zip(b)
+            .all(|(a, b)| a.pos.to_bits() == b.pos.to_bits() && a.color == b.color)
+}
+
+/// The numbers describing the geometry of a fixed gradient, without its stops.
+fn fixed_gradient_geometry(gradient: &FixedGradient) -> SmallVec<[f64; 5]> {
+    match gradient {
+        FixedGradient::Linear(gradient) => smallvec![
+            gradient.start.x,
+            gradient.start.y,
+            gradient.end.x,
+            gradient.end.y,
+        ],
+        FixedGradient::Radial(gradient) => smallvec![
+            gradient.center.x,
+            gradient.center.y,
+            gradient.origin_offset.x,
+            gradient.origin_offset.y,
+            gradient.radius,
+        ],
+    }
+}
+
+fn fixed_gradient_stops(gradient: &FixedGradient) -> &[GradientStop] {
+    match gradient {
+        FixedGradient::Linear(gradient) => &gradient.stops,
+        FixedGradient::Radial(gradient) => &gradient.stops,
+    }
+}
+
+/// Whether two fixed gradients are the same, comparing floats bit by bit.
+fn same_fixed_gradient(a: &FixedGradient, b: &FixedGradient) -> bool {
+    let same_kind = matches!(
+        (a, b),
+        (FixedGradient::Linear(_), FixedGradient::Linear(_))
+            | (FixedGradient::Radial(_), FixedGradient::Radial(_))
+    );
+    same_kind
+        && fixed_gradient_geometry(a)
+            .iter()
+            .map(|value| value.to_bits())
+            .eq(fixed_gradient_geometry(b)
+                .iter()
+                .map(|value| value.to_bits()))
+        && same_stops(fixed_gradient_stops(a), fixed_gradient_stops(b))
+}
+
+fn hash_fixed_gradient(gradient: &FixedGradient, state: &mut impl Hasher) {
+    matches!(gradient, FixedGradient::Linear(_)).hash(state);
+    for value in fixed_gradient_geometry(gradient) {
+        value.to_bits().hash(state);
+    }
+    hash_stops(fixed_gradient_stops(gradient), state);
+}
+
+fn hash_stops(stops: &[GradientStop], state: &mut impl Hasher) {
+    for stop in stops {
+        stop.pos.to_bits().hash(state);
+        stop.color.as_rgba_u32().hash(state);
+    }
+}
+
+/// A unit-space gradient resolved in rects which tell its settings apart.
+///
+/// In the unit square, the resolved points and radius are the gradient's own settings.
+/// The wider rect tells apart radial gradients which only differ in their
+/// [`ScaleMode`](crate::piet::ScaleMode).
+#[derive(Debug)]
+struct ResolvedGradients([FixedGradient; 2]);
+
+impl PartialEq for ResolvedGradients {
+    fn eq(&self, other: &Self) -> bool {
+        self.0
+            .iter()
+            .zip(&other.0)
+            .all(|(a, b)| same_fixed_gradient(a, b))
+    }
+}
+
+impl IntoIterator for ResolvedGradients {
+    type Item = FixedGradient;
+    type IntoIter = std::array::IntoIter<FixedGradient, 2>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter()
+    }
+}
+
+fn resolve_in_reference_rects(gradient: &impl IntoBrush<GradientCapture>) -> ResolvedGradients {
+    let resolve = |rect: Rect| {
+        let mut capture = GradientCapture(NullText);
+        let brush = gradient.make_brush(&mut capture, || rect);
+        brush.into_owned().0
+    };
+    ResolvedGradients([
+        resolve(Rect::new(0.0, 0.0, 1.0, 1.0)),
+        resolve(Rect::new(0.0, 0.0, 2.0, 1.0)),
+    ])
+}
+
+/// A render context which only keeps the gradients it's asked to make.
+///
+/// piet keeps the settings of unit-space gradients private, and only resolves them to
+/// a [`FixedGradient`], whose settings can be read, when making a brush for a render
+/// context. Making the brush for this one is how they're compared and hashed.
+struct GradientCapture(NullText);
+
+#[derive(Clone)]
+struct CapturedGradient(FixedGradient);
+
+impl IntoBrush<GradientCapture> for CapturedGradient {
+    fn make_brush<'a>(
+        &'a self,
+        _piet: &mut GradientCapture,
+        _bbox: impl FnOnce() -> Rect,
+    ) -> Cow<'a, CapturedGradient> {
+        Cow::Borrowed(self)
+    }
+}
+
+impl RenderContext for GradientCapture {
+    type Brush = CapturedGradient;
+    type Image = NullImage;
+    type Text = NullText;
+    type TextLayout = NullTextLayout;
+
+    fn status(&mut self) -> Result<(), PietError> {
+        Ok(())
+    }
+
+    fn solid_brush(&mut self, color: Color) -> CapturedGradient {
+        // Only gradients are captured; this is never called.
+        CapturedGradient(FixedGradient::Linear(FixedLinearGradient {
+            start: Point::ORIGIN,
+            end: Point::ORIGIN,
+            stops: vec![GradientStop { pos: 0.0, color }],
+        }))
+    }
+
+    fn gradient(
+        &mut self,
+        gradient: impl Into<FixedGradient>,
+    ) -> Result<CapturedGradient, PietError> {
+        Ok(CapturedGradient(gradient.into()))
+    }
+
+    fn clear(&mut self, _region: impl Into<Option<Rect>>, _color: Color) {}
+
+    fn stroke(&mut self, _shape: impl Shape, _brush: &impl IntoBrush<Self>, _width: f64) {}
+
+    fn stroke_styled(
+        &mut self,
+        _shape: impl Shape,
+        _brush: &impl IntoBrush<Self>,
+        _width: f64,
+        _style: &StrokeStyle,
+    ) {
+    }
+
+    fn fill(&mut self, _shape: impl Shape, _brush: &impl IntoBrush<Self>) {}
+
+    fn fill_even_odd(&mut self, _shape: impl Shape, _brush: &impl IntoBrush<Self>) {}
+
+    fn clip(&mut self, _shape: impl Shape) {}
+
+    fn text(&mut self) -> &mut NullText {
+        &mut self.0
+    }
+
+    fn draw_text(&mut self, _layout: &NullTextLayout, _pos: impl Into<Point>) {}
+
+    fn save(&mut self) -> Result<(), PietError> {
+        Ok(())
+    }
+
+    fn restore(&mut self) -> Result<(), PietError> {
+        Ok(())
+    }
+
+    fn finish(&mut self) -> Result<(), PietError> {
+        Ok(())
+    }
+
+    fn transform(&mut self, _transform: Affine) {}
+
+    fn make_image(
+        &mut self,
+        _width: usize,
+        _height: usize,
+        _buf: &[u8],
+        _format: ImageFormat,
+    ) -> Result<NullImage, PietError> {
+        Ok(NullImage)
+    }
+
+    fn draw_image(
+        &mut self,
+        _image: &NullImage,
+        _dst_rect: impl Into<Rect>,
+        _interp: InterpolationMode,
+    ) {
+    }
+
+    fn draw_image_area(
+        &mut self,
+        _image: &NullImage,
+        _src_rect: impl Into<Rect>,
+        _dst_rect: impl Into<Rect>,
+        _interp: InterpolationMode,
+    ) {
+    }
+
+    fn capture_image_area(&mut self, _src_rect: impl Into<Rect>) -> Result<NullImage, PietError> {
+        Ok(NullImage)
+    }
+
+    fn blurred_rect(&mut self, _rect: Rect, _blur_radius: f64, _brush: &impl IntoBrush<Self>) {}
+
+    fn current_transform(&self) -> Affine {
+        Affine::IDENTITY
+    }
+}
+
+impl From<Color> for BackgroundBrush {
+    fn from(src: Color) -> BackgroundBrush {
+        BackgroundBrush::Color(src.into())
+    }
+}
+
+impl From<Key<Color>> for BackgroundBrush {
+    fn from(src: Key<Color>) -> BackgroundBrush {
+        BackgroundBrush::Color(src.into())
+    }
+}
+
+impl From<BackgroundRole> for BackgroundBrush {
+    fn from(src: BackgroundRole) -> BackgroundBrush {
+        BackgroundBrush::role(src)
+    }
+}
+
+impl From<LinearGradient> for BackgroundBrush {
+    fn from(src: LinearGradient) -> BackgroundBrush {
+        BackgroundBrush::Linear(src)
+    }
+}
+
+impl From<RadialGradient> for BackgroundBrush {
+    fn from(src: RadialGradient) -> BackgroundBrush {
+        BackgroundBrush::Radial(src)
+    }
+}
+
+impl From<FixedGradient> for BackgroundBrush {
+    fn from(src: FixedGradient) -> BackgroundBrush {
+        BackgroundBrush::Fixed(src)
+    }
+}
+
+impl<Painter: FnMut(&mut PaintCtx, &Env) + 'static> From<Painter> for BackgroundBrush {
+    fn from(src: Painter) -> BackgroundBrush {
+        BackgroundBrush::PainterFn(Box::new(src))
+    }
+}
+
+impl From<Shimmer> for BackgroundBrush {
+    fn from(src: Shimmer) -> BackgroundBrush {
+        BackgroundBrush::Shimmer(src)
+    }
+}
+
+impl From<RotatingGradient> for BackgroundBrush {
+    fn from(src: RotatingGradient) -> BackgroundBrush {
+        BackgroundBrush::Rotating(src)
+    }
+}
+
+impl From<ImageBuf> for BackgroundBrush {
+    fn from(src: ImageBuf) -> BackgroundBrush {
+        BackgroundBrush::Image(src)
+    }
+}
+
+impl From<Box<dyn CustomBackground>> for BackgroundBrush {
+    fn from(src: Box<dyn CustomBackground>) -> BackgroundBrush {
+        BackgroundBrush::Custom(src)
+    }
+}
+
+impl From<PaintBrush> for BackgroundBrush {
+    fn from(src: PaintBrush) -> BackgroundBrush {
+        match src {
+            PaintBrush::Linear(grad) => BackgroundBrush::Linear(grad),
+            PaintBrush::Radial(grad) => BackgroundBrush::Radial(grad),
+            PaintBrush::Fixed(grad) => BackgroundBrush::Fixed(grad),
+            PaintBrush::Color(color) => BackgroundBrush::Color(color.into()),
+        }
+    }
+}
+
+// --- Tests ---
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::piet::ScaleMode;
+    use crate::theme;
+
+    #[test]
+    fn linear_stops() {
+        let brush = BackgroundBrush::linear_stops(
+            90.0,
+            vec![(1.5, Color::BLUE), (0.5, Color::GREEN), (-1.0, Color::RED)],
+        )
+        .unwrap();
+        let expected = LinearGradient::new(
+            UnitPoint::new(0.0, 0.5),
+            UnitPoint::new(1.0, 0.5),
+            vec![
+                GradientStop {
+                    pos: 0.0,
+                    color: Color::RED,
+                },
+                GradientStop {
+                    pos: 0.5,
+                    color: Color::GREEN,
+                },
+                GradientStop {
+                    pos: 1.0,
+                    color: Color::BLUE,
+                },
+            ],
+        );
+        assert!(brush == BackgroundBrush::Linear(expected));
+
+        assert_eq!(
+            BackgroundBrush::linear_stops(0.0, vec![]).err(),
+            Some(GradientError::NoStops)
+        );
+        assert_eq!(
+            BackgroundBrush::linear_stops(0.0, vec![(f64::NAN, Color::RED)]).err(),
+            Some(GradientError::NanOffset)
+        );
+    }
+
+    #[test]
+    fn darken_and_lighten() {
+        fn concrete_rgba(brush: BackgroundBrush) -> (f64, f64, f64, f64) {
+            match brush {
+                BackgroundBrush::Color(KeyOrValue::Concrete(color)) => color.as_rgba(),
+                _ => panic!("expected a concrete color"),
+            }
+        }
+
+        let gray = Color::grey(0.5).with_alpha(0.5);
+        let (r, g, b, a) = concrete_rgba(BackgroundBrush::from(gray).darken(0.2));
+        assert!(r < 0.5 && r > 0.3, "{} should be a slightly darker gray", r);
+        assert!((r - g).abs() < 1e-3 && (r - b).abs() < 1e-3);
+        assert!((a - 0.5).abs() < 1e-9);
+
+        let (r, _, _, _) = concrete_rgba(BackgroundBrush::from(gray).lighten(0.2));
+        assert!(
+            r > 0.5 && r < 0.7,
+            "{} should be a slightly lighter gray",
+            r
+        );
+
+        let (r, _, _, _) = concrete_rgba(BackgroundBrush::from(gray).darken(0.0));
+        assert!((r - 0.5).abs() < 1e-3);
+        let (r, g, b, _) = concrete_rgba(BackgroundBrush::from(gray).darken(1.0));
+        assert!(r < 1e-3 && g < 1e-3 && b < 1e-3);
+
+        // Colors from the env are only known at paint time.
+        let brush = BackgroundBrush::from(theme::BACKGROUND_LIGHT).darken(0.2);
+        assert!(matches!(brush, BackgroundBrush::Color(KeyOrValue::Key(_))));
+    }
+
+    #[test]
+    fn brush_equality() {
+        let gradient =
+            || LinearGradient::new(UnitPoint::TOP, UnitPoint::BOTTOM, (Color::RED, Color::BLUE));
+
+        assert!(BackgroundBrush::from(Color::RED) == BackgroundBrush::from(Color::RED));
+        assert!(BackgroundBrush::from(Color::RED) != BackgroundBrush::from(Color::BLUE));
+        assert!(
+            BackgroundBrush::from(theme::BACKGROUND_LIGHT)
+                == BackgroundBrush::from(theme::BACKGROUND_LIGHT)
+        );
+        assert!(BackgroundBrush::from(gradient()) == BackgroundBrush::from(gradient()));
+        assert!(
+            BackgroundBrush::from(gradient())
+                != BackgroundBrush::from(LinearGradient::new(
+                    UnitPoint::LEFT,
+                    UnitPoint::RIGHT,
+                    (Color::RED, Color::BLUE)
+                ))
+        );
+
+        let fixed = |end_color| {
+            BackgroundBrush::Fixed(FixedGradient::Linear(FixedLinearGradient {
+                start: Point::ZERO,
+                end: Point::new(10.0, 0.0),
+                stops: (Color::RED, end_color).to_vec(),
+            }))
+        };
+        assert!(fixed(Color::BLUE) == fixed(Color::BLUE));
+        assert!(fixed(Color::BLUE) != fixed(Color::GREEN));
+
+        // Radial gradients which only differ in how they scale to non-square boxes.
+        let radial = |scale_mode| {
+            BackgroundBrush::from(
+                RadialGradient::new(0.5, (Color::RED, Color::BLUE)).with_scale_mode(scale_mode),
+            )
+        };
+        assert!(radial(ScaleMode::Fill) == radial(ScaleMode::Fill));
+        assert!(radial(ScaleMode::Fill) != radial(ScaleMode::Fit));
+
+        // Images are compared by their pixel buffer, not pixel by pixel.
+        let image = || ImageBuf::from_raw(vec![255; 4], ImageFormat::RgbaSeparate, 1, 1);
+        let shared = image();
+        assert!(BackgroundBrush::Image(shared.clone()) == BackgroundBrush::Image(shared));
+        assert!(BackgroundBrush::Image(image()) != BackgroundBrush::Image(image()));
+
+        let painter = || BackgroundBrush::PainterFn(Box::new(|_: &mut PaintCtx, _: &Env| {}));
+        assert!(painter() != painter());
+    }
+}
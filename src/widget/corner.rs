@@ -0,0 +1,32 @@
+// This software is licensed under Apache License 2.0 and distributed on an
+// "as-is" basis without warranties of any kind. See the LICENSE file for
+// details.
+
+//! The corners of a box.
+
+use crate::{Point, Rect, Size};
+
+/// A corner of a box, eg for [`SizedBox::pin`](crate::widget::SizedBox::pin).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Corner {
+    /// The top left corner.
+    TopLeft,
+    /// The top right corner.
+    TopRight,
+    /// The bottom left corner.
+    BottomLeft,
+    /// The bottom right corner.
+    BottomRight,
+}
+
+impl Corner {
+    /// The origin of a child of `size` in this corner of `area`.
+    pub(crate) fn place(self, area: Rect, size: Size) -> Point {
+        match self {
+            Corner::TopLeft => Point::new(area.x0, area.y0),
+            Corner::TopRight => Point::new(area.x1 - size.width, area.y0),
+            Corner::BottomLeft => Point::new(area.x0, area.y1 - size.height),
+            Corner::BottomRight => Point::new(area.x1 - size.width, area.y1 - size.height),
+        }
+    }
+}
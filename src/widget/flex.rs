@@ -481,9 +481,8 @@ impl<'a, 'b> FlexMut<'a, 'b> {
 
 impl Widget for Flex {
     fn on_event(&mut self, ctx: &mut EventCtx, event: &Event, env: &Env) {
-        for child in self.children.iter_mut().filter_map(|x| x.widget_mut()) {
-            child.on_event(ctx, event, env);
-        }
+        let children = self.children.iter_mut().filter_map(|x| x.widget_mut());
+        ctx.dispatch_by_z_index(children, event, env);
     }
 
     fn on_status_change(&mut self, _ctx: &mut LifeCycleCtx, _event: &StatusChange, _env: &Env) {}
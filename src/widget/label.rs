@@ -8,21 +8,30 @@
 // - set text
 // - set text attributes
 
-use druid_shell::Cursor;
+use std::ops::Range;
+
+use druid_shell::{Cursor, HotKey, SysMods};
 use smallvec::SmallVec;
 use tracing::{trace, trace_span, Span};
 
-use crate::kurbo::Vec2;
-use crate::text::{FontDescriptor, TextAlignment, TextLayout};
+use crate::kurbo::{Line, Vec2};
+use crate::piet::TextLayout as _;
+use crate::text::{AlignmentDirection, FontDescriptor, TextAlignment, TextLayout};
 use crate::widget::WidgetRef;
 use crate::{
-    ArcStr, BoxConstraints, Color, Data, Env, Event, EventCtx, KeyOrValue, LayoutCtx, LifeCycle,
-    LifeCycleCtx, PaintCtx, Point, RenderContext, Size, StatusChange, Widget,
+    theme, ArcStr, BoxConstraints, Color, Data, Env, Event, EventCtx, KeyOrValue, LayoutCtx,
+    LifeCycle, LifeCycleCtx, PaintCtx, Point, RenderContext, Size, StatusChange, Widget,
 };
 
 // added padding between the edges of the widget and the text.
 const LABEL_X_PADDING: f64 = 2.0;
 
+// Positions of text decorations, as fractions of the font size. The underline sits
+// below the baseline, the strikethrough around the middle of lowercase letters.
+const UNDERLINE_OFFSET: f64 = 0.12;
+const STRIKETHROUGH_OFFSET: f64 = -0.28;
+const DECORATION_THICKNESS: f64 = 1.0 / 15.0;
+
 /// A widget displaying non-editable text.
 pub struct Label {
     current_text: ArcStr,
@@ -31,6 +40,16 @@ pub struct Label {
 
     disabled: bool,
     default_text_color: KeyOrValue<Color>,
+
+    selectable: bool,
+    selection: Range<usize>,
+
+    underline: bool,
+    strikethrough: bool,
+
+    /// Computes the text of labels built with [`Label::dynamic`].
+    #[allow(clippy::type_complexity)]
+    dynamic_text: Option<Box<dyn Fn(&Env) -> String>>,
 }
 
 crate::declare_widget!(LabelMut, Label);
@@ -61,6 +80,11 @@ impl Label {
             line_break_mode: LineBreaking::Overflow,
             disabled: false,
             default_text_color: crate::theme::TEXT_COLOR.into(),
+            selectable: false,
+            selection: 0..0,
+            underline: false,
+            strikethrough: false,
+            dynamic_text: None,
         }
     }
 
@@ -72,18 +96,46 @@ impl Label {
             line_break_mode: LineBreaking::Overflow,
             disabled: false,
             default_text_color: crate::theme::TEXT_COLOR.into(),
+            selectable: false,
+            selection: 0..0,
+            underline: false,
+            strikethrough: false,
+            dynamic_text: None,
+        }
+    }
+
+    /// Create a label showing a value's [`Display`](std::fmt::Display) output.
+    ///
+    /// The text is computed once. To show a value which changes, use
+    /// [`Label::dynamic`].
+    pub fn from_value(value: impl std::fmt::Display) -> Self {
+        Self::new(value.to_string())
+    }
+
+    /// Create a label whose text is computed by `text`, eg from a shared counter.
+    ///
+    /// `text` is called each time the label is laid out. When what it depends on
+    /// changes, eg in another widget's event handler, call [`LabelMut::refresh`] to
+    /// lay the label out again with the new text.
+    pub fn dynamic(text: impl Fn(&Env) -> String + 'static) -> Self {
+        Self {
+            dynamic_text: Some(Box::new(text)),
+            ..Self::empty()
         }
     }
 
     /// Builder-style method for setting the text string.
     pub fn with_text(mut self, new_text: impl Into<ArcStr>) -> Self {
-        self.text_layout.set_text(new_text.into());
+        self.current_text = new_text.into();
+        self.text_layout.set_text(self.current_text.clone());
         self
     }
 
     /// Builder-style method for setting the text color.
     ///
-    /// The argument can be either a `Color` or a [`Key<Color>`].
+    /// This overrides the [`TEXT_COLOR`](crate::theme::TEXT_COLOR) from the theme for
+    /// this label only. The argument can be either a `Color` or a [`Key<Color>`], which
+    /// is resolved in the [`Env`] when the text is laid out.
     ///
     /// [`Key<Color>`]: ../struct.Key.html
     pub fn with_text_color(mut self, color: impl Into<KeyOrValue<Color>>) -> Self {
@@ -130,6 +182,52 @@ impl Label {
         self
     }
 
+    /// Builder-style method to set the [`AlignmentDirection`].
+    ///
+    /// By default, [`TextAlignment::Start`] is on the side the first strong character
+    /// of the text reads from, which can be wrong for mixed text, eg an Arabic sentence
+    /// starting with a Latin name. This only flips the alignment; the order of mixed
+    /// left-to-right and right-to-left runs is still up to the platform.
+    pub fn with_alignment_direction(mut self, direction: AlignmentDirection) -> Self {
+        self.text_layout.set_alignment_direction(direction);
+        self
+    }
+
+    /// Builder-style method to make the label's text selectable.
+    ///
+    /// A selectable label can be focused by clicking on it, and its whole text can
+    /// then be selected with `Ctrl+A` (`Cmd+A` on macOS).
+    pub fn with_selectable(mut self, selectable: bool) -> Self {
+        self.selectable = selectable;
+        self
+    }
+
+    /// Builder-style method to draw a line under the text.
+    ///
+    /// The line uses the text color, and its thickness scales with the font size.
+    /// Each line of wrapped text is underlined separately.
+    pub fn with_underline(mut self, underline: bool) -> Self {
+        self.underline = underline;
+        self
+    }
+
+    /// Builder-style method to draw a line through the text.
+    ///
+    /// Like the underline, it uses the text color and scales with the font size.
+    pub fn with_strikethrough(mut self, strikethrough: bool) -> Self {
+        self.strikethrough = strikethrough;
+        self
+    }
+
+    /// Return the currently selected text, if any.
+    pub fn selected_text(&self) -> Option<&str> {
+        if self.selection.is_empty() {
+            return None;
+        }
+        let text = self.text_layout.text()?;
+        text.get(self.selection.clone())
+    }
+
     /// Return the current value of the label's text.
     pub fn text(&self) -> ArcStr {
         self.current_text.clone()
@@ -141,6 +239,12 @@ impl Label {
         text_metrics.size.height - text_metrics.first_baseline
     }
 
+    /// Return the offset of the last baseline relative to the bottom of the widget.
+    pub fn last_baseline_offset(&self) -> f64 {
+        let text_metrics = self.text_layout.layout_metrics();
+        text_metrics.size.height - text_metrics.last_baseline
+    }
+
     /// Draw this label's text at the provided `Point`, without internal padding.
     ///
     /// This is a convenience for widgets that want to use Label as a way
@@ -149,18 +253,81 @@ impl Label {
     pub fn draw_at(&self, ctx: &mut PaintCtx, origin: impl Into<Point>) {
         self.text_layout.draw(ctx, origin)
     }
+
+    /// Recompute the text of a dynamic label.
+    fn update_dynamic_text(&mut self, env: &Env) {
+        let text = match &self.dynamic_text {
+            Some(dynamic_text) => dynamic_text(env),
+            None => return,
+        };
+        if *self.current_text != *text {
+            self.current_text = text.into();
+            self.text_layout.set_text(self.current_text.clone());
+            self.selection = 0..0;
+        }
+    }
+
+    /// Draw the underline and strikethrough, if enabled, for every line of text.
+    fn draw_decorations(&self, ctx: &mut PaintCtx, origin: Point) {
+        if !self.underline && !self.strikethrough {
+            return;
+        }
+        let (layout, font, color) = match (
+            self.text_layout.layout(),
+            self.text_layout.resolved_font(),
+            self.text_layout.resolved_text_color(),
+        ) {
+            (Some(layout), Some(font), Some(color)) => (layout, font, color),
+            _ => return,
+        };
+        let thickness = (font.size * DECORATION_THICKNESS).max(1.0);
+
+        for line in 0..layout.line_count() {
+            let metric = layout.line_metric(line).unwrap();
+            let text_range = metric.start_offset..metric.end_offset - metric.trailing_whitespace;
+            let rect = match layout.rects_for_range(text_range).first() {
+                Some(rect) => *rect + origin.to_vec2(),
+                None => continue,
+            };
+            let baseline = origin.y + metric.y_offset + metric.baseline;
+
+            if self.underline {
+                let y = baseline + font.size * UNDERLINE_OFFSET;
+                ctx.stroke(Line::new((rect.x0, y), (rect.x1, y)), &color, thickness);
+            }
+            if self.strikethrough {
+                let y = baseline + font.size * STRIKETHROUGH_OFFSET;
+                ctx.stroke(Line::new((rect.x0, y), (rect.x1, y)), &color, thickness);
+            }
+        }
+    }
 }
 
 impl LabelMut<'_, '_> {
     /// Set the text.
     pub fn set_text(&mut self, new_text: impl Into<ArcStr>) {
-        self.widget.text_layout.set_text(new_text.into());
+        self.widget.current_text = new_text.into();
+        let text = self.widget.current_text.clone();
+        self.widget.text_layout.set_text(text);
+        // The old selection may not even be a valid range of the new text.
+        self.widget.selection = 0..0;
         self.ctx.request_layout();
     }
 
+    /// Compute the text of a [dynamic](Label::dynamic) label again.
+    ///
+    /// This requests a layout, where the label's function is called. It does nothing
+    /// for other labels.
+    pub fn refresh(&mut self) {
+        if self.widget.dynamic_text.is_some() {
+            self.ctx.request_layout();
+        }
+    }
+
     /// Set the text color.
     ///
     /// The argument can be either a `Color` or a [`Key<Color>`].
+    ///
     /// [`Key<Color>`]: ../struct.Key.html
     pub fn set_text_color(&mut self, color: impl Into<KeyOrValue<Color>>) {
         let color = color.into();
@@ -192,6 +359,18 @@ impl LabelMut<'_, '_> {
         self.ctx.request_layout();
     }
 
+    /// Set whether a line is drawn under the text.
+    pub fn set_underline(&mut self, underline: bool) {
+        self.widget.underline = underline;
+        self.ctx.request_paint();
+    }
+
+    /// Set whether a line is drawn through the text.
+    pub fn set_strikethrough(&mut self, strikethrough: bool) {
+        self.widget.strikethrough = strikethrough;
+        self.ctx.request_paint();
+    }
+
     /// Set the [`LineBreaking`] behaviour.
     pub fn set_line_break_mode(&mut self, mode: LineBreaking) {
         self.widget.line_break_mode = mode;
@@ -203,6 +382,14 @@ impl LabelMut<'_, '_> {
         self.widget.text_layout.set_text_alignment(alignment);
         self.ctx.request_layout();
     }
+
+    /// Set the [`AlignmentDirection`].
+    ///
+    /// See [`Label::with_alignment_direction`] for details.
+    pub fn set_alignment_direction(&mut self, direction: AlignmentDirection) {
+        self.widget.text_layout.set_alignment_direction(direction);
+        self.ctx.request_layout();
+    }
 }
 
 // --- TRAIT IMPLS ---
@@ -210,6 +397,26 @@ impl LabelMut<'_, '_> {
 impl Widget for Label {
     fn on_event(&mut self, ctx: &mut EventCtx, event: &Event, _env: &Env) {
         match event {
+            Event::MouseDown(_) if self.selectable && !ctx.is_disabled() => {
+                if !self.selection.is_empty() {
+                    self.selection = 0..0;
+                    ctx.request_paint();
+                }
+                ctx.request_focus();
+            }
+            Event::KeyDown(key)
+                if self.selectable && HotKey::new(SysMods::Cmd, "a").matches(key) =>
+            {
+                ctx.submit_command(crate::command::SELECT_ALL.to(ctx.widget_id()));
+                ctx.set_handled();
+            }
+            Event::Command(cmd)
+                if self.selectable && ctx.is_focused() && cmd.is(crate::command::SELECT_ALL) =>
+            {
+                self.selection = 0..self.text_layout.text_len();
+                ctx.request_paint();
+                ctx.set_handled();
+            }
             Event::MouseUp(event) => {
                 // Account for the padding
                 let pos = event.pos - Vec2::new(LABEL_X_PADDING, 0.0);
@@ -233,11 +440,22 @@ impl Widget for Label {
         }
     }
 
-    fn on_status_change(&mut self, _ctx: &mut LifeCycleCtx, _event: &StatusChange, _env: &Env) {}
+    fn on_status_change(&mut self, ctx: &mut LifeCycleCtx, event: &StatusChange, _env: &Env) {
+        if let StatusChange::FocusChanged(false) = event {
+            if !self.selection.is_empty() {
+                self.selection = 0..0;
+                ctx.request_paint();
+            }
+        }
+    }
 
     fn lifecycle(&mut self, ctx: &mut LifeCycleCtx, event: &LifeCycle, _env: &Env) {
         match event {
+            LifeCycle::BuildFocusChain if self.selectable => {
+                ctx.register_for_focus();
+            }
             LifeCycle::DisabledChanged(disabled) => {
+                self.disabled = *disabled;
                 let color = if *disabled {
                     KeyOrValue::Key(crate::theme::DISABLED_TEXT_COLOR)
                 } else {
@@ -256,27 +474,42 @@ impl Widget for Label {
             _ => f64::INFINITY,
         };
 
+        self.update_dynamic_text(env);
         self.text_layout.set_wrap_width(width);
         self.text_layout.rebuild_if_needed(ctx.text(), env);
 
         let text_metrics = self.text_layout.layout_metrics();
         ctx.set_baseline_offset(text_metrics.size.height - text_metrics.first_baseline);
+        ctx.set_last_baseline_offset(text_metrics.size.height - text_metrics.last_baseline);
+        // An empty label has no text to keep off the edges, so it has no width.
+        let x_padding = if self.current_text.is_empty() {
+            0.0
+        } else {
+            2. * LABEL_X_PADDING
+        };
         let size = bc.constrain(Size::new(
-            text_metrics.size.width + 2. * LABEL_X_PADDING,
+            text_metrics.size.width + x_padding,
             text_metrics.size.height,
         ));
         trace!("Computed size: {}", size);
         size
     }
 
-    fn paint(&mut self, ctx: &mut PaintCtx, _env: &Env) {
+    fn paint(&mut self, ctx: &mut PaintCtx, env: &Env) {
         let origin = Point::new(LABEL_X_PADDING, 0.0);
         let label_size = ctx.size();
 
         if self.line_break_mode == LineBreaking::Clip {
             ctx.clip(label_size.to_rect());
         }
-        self.draw_at(ctx, origin)
+        if !self.selection.is_empty() {
+            let selection_color = env.get(theme::SELECTED_TEXT_BACKGROUND_COLOR);
+            for rect in self.text_layout.rects_for_range(self.selection.clone()) {
+                ctx.fill(rect + origin.to_vec2(), &selection_color);
+            }
+        }
+        self.draw_at(ctx, origin);
+        self.draw_decorations(ctx, origin);
     }
 
     fn children(&self) -> SmallVec<[WidgetRef<'_, dyn Widget>; 16]> {
@@ -300,12 +533,15 @@ impl Data for LineBreaking {
 
 #[cfg(test)]
 mod tests {
+    use std::sync::Arc;
+
     use crate::piet::FontFamily;
     use insta::assert_debug_snapshot;
 
     use super::*;
     use crate::assert_render_snapshot;
-    use crate::testing::TestHarness;
+    use crate::shell::{KeyEvent, RawMods};
+    use crate::testing::{widget_ids, TestHarness};
     use crate::theme::{PRIMARY_DARK, PRIMARY_LIGHT};
     use crate::widget::{Flex, SizedBox};
 
@@ -333,6 +569,59 @@ mod tests {
         assert_render_snapshot!(harness, "styled_label");
     }
 
+    #[test]
+    fn text_color_override() {
+        let widget = Flex::column()
+            .with_child(Label::new("Red label").with_text_color(Color::RED))
+            .with_child(Label::new("Default label"));
+
+        let mut harness = TestHarness::create_with_size(widget, Size::new(200.0, 100.0));
+        let image_1 = harness.render();
+        let is_red = |pixel: &[u8]| pixel[0] > 200 && pixel[1] < 50 && pixel[2] < 50;
+        assert!(image_1.chunks(4).any(is_red));
+
+        let widget = Flex::column()
+            .with_child(Label::new("Red label"))
+            .with_child(Label::new("Default label"));
+
+        let mut harness = TestHarness::create_with_size(widget, Size::new(200.0, 100.0));
+        let image_2 = harness.render();
+
+        // We don't use assert_eq because we don't want rich assert
+        assert!(image_1 != image_2);
+        assert!(!image_2.chunks(4).any(is_red));
+    }
+
+    #[test]
+    fn disabled_text_color() {
+        let normal_image = {
+            let label = Label::new("Hello").with_text_color(Color::RED);
+            let mut harness = TestHarness::create_with_size(label, Size::new(50.0, 50.0));
+            harness.render()
+        };
+        let disabled_image = {
+            let label = Label::new("Hello").with_text_color(theme::DISABLED_TEXT_COLOR);
+            let mut harness = TestHarness::create_with_size(label, Size::new(50.0, 50.0));
+            harness.render()
+        };
+
+        let label = Label::new("Hello").with_text_color(Color::RED);
+        let mut harness = TestHarness::create_with_size(label, Size::new(50.0, 50.0));
+
+        harness.edit_root_widget(|mut label, _| {
+            let mut label = label.downcast::<Label>().unwrap();
+            label.ctx.set_disabled(true);
+        });
+        // We don't use assert_eq because we don't want rich assert
+        assert!(harness.render() == disabled_image);
+
+        harness.edit_root_widget(|mut label, _| {
+            let mut label = label.downcast::<Label>().unwrap();
+            label.ctx.set_disabled(false);
+        });
+        assert!(harness.render() == normal_image);
+    }
+
     #[test]
     fn line_break_modes() {
         let widget = Flex::column()
@@ -367,6 +656,157 @@ mod tests {
         assert_render_snapshot!(harness, "line_break_modes");
     }
 
+    #[test]
+    fn select_all() {
+        let [label_id, other_label_id] = widget_ids();
+        let widget = Flex::column()
+            .with_child_id(Label::new("Hello world").with_selectable(true), label_id)
+            .with_child_id(Label::new("Other").with_selectable(true), other_label_id);
+
+        let mut harness = TestHarness::create(widget);
+        let select_all = KeyEvent::for_test(RawMods::from(SysMods::Cmd), "a");
+
+        let label_text = |harness: &TestHarness, id| {
+            let label = harness.get_widget(id);
+            let label = label.downcast::<Label>().unwrap();
+            label.deref().selected_text().map(str::to_string)
+        };
+
+        harness.mouse_click_on(label_id);
+        assert_eq!(harness.focused_widget().map(|w| w.id()), Some(label_id));
+        assert_eq!(label_text(&harness, label_id), None);
+        let unselected = harness.render();
+
+        harness.process_event(Event::KeyDown(select_all.clone()));
+        assert_eq!(
+            label_text(&harness, label_id),
+            Some("Hello world".to_string())
+        );
+        // The selection is painted.
+        // We don't use assert_eq because we don't want rich assert
+        assert!(harness.render() != unselected);
+
+        // Clicking on another selectable label clears the selection
+        harness.mouse_click_on(other_label_id);
+        assert_eq!(label_text(&harness, label_id), None);
+        assert_eq!(label_text(&harness, other_label_id), None);
+
+        harness.process_event(Event::KeyDown(select_all));
+        assert_eq!(
+            label_text(&harness, other_label_id),
+            Some("Other".to_string())
+        );
+
+        // Clicking on the label itself clears the selection too
+        harness.mouse_click_on(other_label_id);
+        assert_eq!(label_text(&harness, other_label_id), None);
+    }
+
+    #[test]
+    fn set_text_clears_selection() {
+        let mut harness = TestHarness::create(Label::new("Hello world").with_selectable(true));
+        let label_id = harness.root_widget().id();
+        harness.mouse_click_on(label_id);
+        let select_all = KeyEvent::for_test(RawMods::from(SysMods::Cmd), "a");
+        harness.process_event(Event::KeyDown(select_all));
+
+        harness.edit_root_widget(|mut label, _| {
+            let mut label = label.downcast::<Label>().unwrap();
+            label.set_text("Hello world, again");
+        });
+        let label = harness.root_widget();
+        let label = label.downcast::<Label>().unwrap();
+        assert_eq!(label.deref().selected_text(), None);
+    }
+
+    #[test]
+    fn empty_label_has_no_width() {
+        let [label_id] = widget_ids();
+        let widget = Flex::row().with_child_id(Label::empty(), label_id);
+        let mut harness = TestHarness::create(widget);
+        assert_eq!(
+            harness.get_widget(label_id).state().layout_rect().width(),
+            0.0
+        );
+
+        harness.edit_root_widget(|mut flex, _| {
+            let mut flex = flex.downcast::<Flex>().unwrap();
+            let mut label = flex.child_mut(0).unwrap();
+            let mut label = label.downcast::<Label>().unwrap();
+            label.set_text("Hello");
+        });
+        assert!(harness.get_widget(label_id).state().layout_rect().width() > 0.0);
+
+        harness.edit_root_widget(|mut flex, _| {
+            let mut flex = flex.downcast::<Flex>().unwrap();
+            let mut label = flex.child_mut(0).unwrap();
+            let mut label = label.downcast::<Label>().unwrap();
+            label.set_text("");
+        });
+        assert_eq!(
+            harness.get_widget(label_id).state().layout_rect().width(),
+            0.0
+        );
+    }
+
+    #[test]
+    fn select_all_not_selectable() {
+        let [label_id] = widget_ids();
+        let widget = Flex::column().with_child_id(Label::new("Hello world"), label_id);
+
+        let mut harness = TestHarness::create(widget);
+
+        harness.mouse_click_on(label_id);
+        assert!(harness.focused_widget().is_none());
+
+        let select_all = KeyEvent::for_test(RawMods::from(SysMods::Cmd), "a");
+        harness.process_event(Event::KeyDown(select_all));
+
+        let label = harness.get_widget(label_id);
+        let label = label.downcast::<Label>().unwrap();
+        assert_eq!(label.deref().selected_text(), None);
+    }
+
+    #[test]
+    fn alignment_direction() {
+        let render = |text: &str, direction| {
+            let label = Label::new(text)
+                .with_line_break_mode(LineBreaking::WordWrap)
+                .with_alignment_direction(direction);
+            let mut harness = TestHarness::create_with_size(label, Size::new(300.0, 40.0));
+            harness.render()
+        };
+        // Latin first, then Arabic.
+        let latin_first = "Hello \u{645}\u{631}\u{62d}\u{628}\u{627}";
+        // Arabic first, then Latin.
+        let arabic_first = "\u{645}\u{631}\u{62d}\u{628}\u{627} Hello";
+
+        // We don't use assert_eq because we don't want rich assert
+        let ltr = render(latin_first, AlignmentDirection::Ltr);
+        let rtl = render(latin_first, AlignmentDirection::Rtl);
+        assert!(ltr != rtl);
+        assert!(render(latin_first, AlignmentDirection::Auto) == ltr);
+
+        let ltr = render(arabic_first, AlignmentDirection::Ltr);
+        let rtl = render(arabic_first, AlignmentDirection::Rtl);
+        assert!(ltr != rtl);
+        assert!(render(arabic_first, AlignmentDirection::Auto) == rtl);
+
+        // The alignment doesn't change the reported direction of the text.
+        let label = Label::new(latin_first).with_alignment_direction(AlignmentDirection::Rtl);
+        assert!(!label.text_layout.text_is_rtl());
+
+        let mut harness = TestHarness::create_with_size(
+            Label::new(latin_first).with_line_break_mode(LineBreaking::WordWrap),
+            Size::new(300.0, 40.0),
+        );
+        harness.edit_root_widget(|mut label, _| {
+            let mut label = label.downcast::<Label>().unwrap();
+            label.set_alignment_direction(AlignmentDirection::Rtl);
+        });
+        assert!(harness.render() == render(latin_first, AlignmentDirection::Rtl));
+    }
+
     #[test]
     fn edit_label() {
         let image_1 = {
@@ -405,4 +845,228 @@ mod tests {
         // We don't use assert_eq because we don't want rich assert
         assert!(image_1 == image_2);
     }
+
+    #[test]
+    fn dynamic_label() {
+        use std::cell::Cell;
+        use std::rc::Rc;
+
+        let [label_id] = widget_ids();
+        let count = Rc::new(Cell::new(0));
+        let label = Label::dynamic({
+            let count = count.clone();
+            move |_| format!("Count: {}", count.get())
+        });
+        let widget = Flex::row().with_child_id(label, label_id);
+        let mut harness = TestHarness::create(widget);
+
+        let text = |harness: &TestHarness| {
+            let label = harness.get_widget(label_id);
+            label.downcast::<Label>().unwrap().text()
+        };
+        assert_eq!(&*text(&harness), "Count: 0");
+        let old_width = harness.get_widget(label_id).state().layout_rect().width();
+
+        // Events don't recompute the text, refresh does.
+        count.set(1000);
+        harness.mouse_move_to(label_id);
+        assert_eq!(&*text(&harness), "Count: 0");
+        harness.edit_root_widget(|mut flex, _| {
+            let mut flex = flex.downcast::<Flex>().unwrap();
+            let mut label = flex.child_mut(0).unwrap();
+            label.downcast::<Label>().unwrap().refresh();
+        });
+        assert_eq!(&*text(&harness), "Count: 1000");
+        let new_width = harness.get_widget(label_id).state().layout_rect().width();
+        assert!(new_width > old_width);
+
+        assert_eq!(&*Label::from_value(42).text(), "42");
+    }
+
+    #[test]
+    fn rem_font_size_follows_theme() {
+        fn font_size(harness: &TestHarness) -> f64 {
+            let label = harness.root_widget().downcast::<Label>().unwrap();
+            label.deref().text_layout.resolved_font().unwrap().size
+        }
+
+        let font = FontDescriptor::new(FontFamily::SYSTEM_UI).with_size_rem(1.5);
+        let label = Label::new("Hello").with_font(font);
+        let mut harness = TestHarness::create_with_size(label, Size::new(100.0, 100.0));
+
+        harness.set_env_value(crate::theme::TEXT_SIZE_NORMAL, 16.0);
+        assert_eq!(font_size(&harness), 24.0);
+
+        harness.set_env_value(crate::theme::TEXT_SIZE_NORMAL, 20.0);
+        assert_eq!(font_size(&harness), 30.0);
+    }
+
+    #[test]
+    fn text_layout_is_invalidated() {
+        /// Render `label`, and a fresh label built with the same settings.
+        fn render_both(harness: &mut TestHarness, fresh: Label) -> (Arc<[u8]>, Arc<[u8]>) {
+            let size = Size::new(100.0, 100.0);
+            let mut fresh_harness = TestHarness::create_with_size(fresh, size);
+            (harness.render(), fresh_harness.render())
+        }
+        fn rebuild_count(harness: &TestHarness) -> usize {
+            let label = harness.root_widget().downcast::<Label>().unwrap();
+            label.deref().text_layout.rebuild_count
+        }
+
+        let label = Label::new("Hello").with_line_break_mode(LineBreaking::WordWrap);
+        let mut harness = TestHarness::create_with_size(label, Size::new(100.0, 100.0));
+        let first_image = harness.render();
+        assert_eq!(rebuild_count(&harness), 1);
+
+        // Relayout with unchanged inputs paints the same text.
+        harness.edit_root_widget(|mut label, _| {
+            let mut label = label.downcast::<Label>().unwrap();
+            label.set_text("Hello");
+            label.ctx.request_layout();
+        });
+        let image = harness.render();
+        // We don't use assert_eq because we don't want rich assert
+        assert!(image == first_image);
+        // And it reuses the layout.
+        assert_eq!(rebuild_count(&harness), 1);
+
+        // Each change is picked up, as if the label had been built with it.
+        harness.edit_root_widget(|mut label, _| {
+            let mut label = label.downcast::<Label>().unwrap();
+            label.set_text("World");
+        });
+        let (image, fresh_image) = render_both(
+            &mut harness,
+            Label::new("World").with_line_break_mode(LineBreaking::WordWrap),
+        );
+        assert!(image != first_image);
+        assert!(image == fresh_image);
+        assert_eq!(rebuild_count(&harness), 2);
+
+        let monospace = FontDescriptor::new(FontFamily::MONOSPACE);
+        harness.edit_root_widget(|mut label, _| {
+            let mut label = label.downcast::<Label>().unwrap();
+            label.set_font(monospace.clone());
+        });
+        let (image, fresh_image) = render_both(
+            &mut harness,
+            Label::new("World")
+                .with_line_break_mode(LineBreaking::WordWrap)
+                .with_font(monospace.clone()),
+        );
+        assert!(image == fresh_image);
+        assert_eq!(rebuild_count(&harness), 3);
+
+        harness.edit_root_widget(|mut label, _| {
+            let mut label = label.downcast::<Label>().unwrap();
+            label.set_line_break_mode(LineBreaking::Overflow);
+        });
+        let (image, fresh_image) = render_both(
+            &mut harness,
+            Label::new("World")
+                .with_line_break_mode(LineBreaking::Overflow)
+                .with_font(monospace),
+        );
+        assert!(image == fresh_image);
+        assert_eq!(rebuild_count(&harness), 4);
+    }
+
+    /// Render `label` in a window of the given size, returning the image and the first
+    /// and last baselines of the text.
+    fn render_label(label: Label, size: Size) -> (Arc<[u8]>, f64, f64) {
+        let mut harness = TestHarness::create_with_size(label, size);
+        let image = harness.render();
+        let label = harness.root_widget();
+        let metrics = label
+            .downcast::<Label>()
+            .unwrap()
+            .deref()
+            .text_layout
+            .layout_metrics();
+        (image, metrics.first_baseline, metrics.last_baseline)
+    }
+
+    /// The rows of pixels which differ between two images `width` pixels wide.
+    fn changed_rows(image_1: &[u8], image_2: &[u8], width: usize) -> Vec<f64> {
+        let rows_1 = image_1.chunks(width * 4);
+        let rows_2 = image_2.chunks(width * 4);
+        rows_1
+            .zip(rows_2)
+            .enumerate()
+            .filter(|(_, (row_1, row_2))| row_1 != row_2)
+            .map(|(y, _)| y as f64)
+            .collect()
+    }
+
+    #[test]
+    fn underlined_label() {
+        let label = || Label::new("Hello world").with_text_color(PRIMARY_LIGHT);
+        let size = Size::new(100.0, 30.0);
+        let (plain, baseline, _) = render_label(label(), size);
+        let (underlined, _, _) = render_label(label().with_underline(true), size);
+
+        let rows = changed_rows(&plain, &underlined, 100);
+        assert!(!rows.is_empty());
+        assert!(rows.iter().all(|y| y + 1.0 > baseline));
+    }
+
+    #[test]
+    fn struck_through_label() {
+        let label = || Label::new("Hello world").with_text_color(PRIMARY_LIGHT);
+        let size = Size::new(100.0, 30.0);
+        let (plain, baseline, _) = render_label(label(), size);
+        let (struck_through, _, _) = render_label(label().with_strikethrough(true), size);
+
+        let rows = changed_rows(&plain, &struck_through, 100);
+        assert!(!rows.is_empty());
+        assert!(rows.iter().all(|y| *y < baseline));
+    }
+
+    #[test]
+    fn decorated_multiline_label() {
+        let label = || {
+            Label::new("The quick brown fox jumps over the lazy dog")
+                .with_line_break_mode(LineBreaking::WordWrap)
+        };
+        let size = Size::new(100.0, 100.0);
+        let (plain, first_baseline, last_baseline) = render_label(label(), size);
+        let decorated = label().with_underline(true).with_strikethrough(true);
+        let (decorated, _, _) = render_label(decorated, size);
+        assert!(last_baseline > first_baseline);
+
+        // The first line is struck through, and the last one is underlined.
+        let rows = changed_rows(&plain, &decorated, 100);
+        assert!(rows.first().unwrap() + 1.0 < first_baseline);
+        assert!(*rows.last().unwrap() > last_baseline);
+    }
+
+    #[test]
+    fn edit_decorations() {
+        let image_1 = {
+            let label = Label::new("Hello")
+                .with_underline(true)
+                .with_strikethrough(true);
+
+            let mut harness = TestHarness::create_with_size(label, Size::new(50.0, 30.0));
+            harness.render()
+        };
+
+        let image_2 = {
+            let label = Label::new("Hello");
+
+            let mut harness = TestHarness::create_with_size(label, Size::new(50.0, 30.0));
+
+            harness.edit_root_widget(|mut label, _| {
+                let mut label = label.downcast::<Label>().unwrap();
+                label.set_underline(true);
+                label.set_strikethrough(true);
+            });
+
+            harness.render()
+        };
+
+        // We don't use assert_eq because we don't want rich assert
+        assert!(image_1 == image_2);
+    }
 }
@@ -6,6 +6,7 @@
 
 #[allow(clippy::module_inception)]
 mod widget;
+mod widget_ext;
 mod widget_mut;
 mod widget_pod;
 mod widget_ref;
@@ -15,43 +16,58 @@ mod widget_state;
 mod tests;
 
 mod align;
+mod background_brush;
 mod button;
 mod checkbox;
+mod corner;
 mod flex;
 mod image;
 mod label;
+mod placeholder;
 mod portal;
 mod scroll_bar;
+mod separator;
+mod sides;
 mod sized_box;
 mod spinner;
 mod split;
+mod state_styles;
 mod textbox;
 
 pub use align::Align;
 pub use button::Button;
 pub use checkbox::Checkbox;
+pub use corner::Corner;
 pub use flex::{Axis, CrossAxisAlignment, Flex, FlexParams, MainAxisAlignment};
 pub use label::{Label, LineBreaking};
+pub use placeholder::Placeholder;
 pub use portal::Portal;
 pub use scroll_bar::ScrollBar;
+pub use separator::Separator;
+pub use sides::Sides;
 pub use sized_box::SizedBox;
 pub use spinner::Spinner;
 pub use split::Split;
+pub use state_styles::{StateStyle, StateStyles};
 pub use textbox::TextBox;
 pub use widget::StoreInWidgetMut;
 #[doc(hidden)]
 pub use widget::{Widget, WidgetId};
-//#[doc(hidden)]
-//pub use widget_ext::WidgetExt;
+pub use widget_ext::WidgetExt;
 //pub use widget_wrapper::WidgetWrapper;
 pub use widget_mut::WidgetMut;
-pub use widget_pod::WidgetPod;
+pub use widget_pod::{WidgetPod, MAX_CHILD_LAYOUTS};
 pub use widget_ref::WidgetRef;
 pub use widget_state::WidgetState;
 
 pub use self::image::Image;
 
-pub use sized_box::BackgroundBrush;
+#[cfg(any(feature = "png", feature = "jpeg"))]
+pub use background_brush::ImageDecodeError;
+pub use background_brush::{
+    BackgroundBrush, ColorSpace, CustomBackground, GradientError, RotatingGradient, Shimmer,
+};
+pub use sized_box::{BorderError, BoxConfigError, BoxSizing, SharedSize, StyleBrush, StyleBundle};
 
 /// Methods by which a widget can attempt to change focus state.
 #[derive(Debug, Clone, Copy)]
@@ -0,0 +1,90 @@
+// This software is licensed under Apache License 2.0 and distributed on an
+// "as-is" basis without warranties of any kind. See the LICENSE file for
+// details.
+
+//! A widget that stands for "nothing here".
+
+use smallvec::SmallVec;
+use tracing::{trace_span, Span};
+
+use crate::widget::WidgetRef;
+use crate::{
+    BoxConstraints, Env, Event, EventCtx, LayoutCtx, LifeCycle, LifeCycleCtx, PaintCtx, Size,
+    StatusChange, Widget,
+};
+
+/// A widget with no content, which takes as little space as it's allowed to.
+///
+/// This is meant to fill a child slot that has nothing to show, eg as the default
+/// child of a container before the real content is available.
+///
+/// Unlike [`SizedBox::empty`](crate::widget::SizedBox::empty), which is a spacer that
+/// can be given a size, a background or a border, `Placeholder` has no state at all:
+/// it always reports the smallest size its constraints allow, and paints nothing.
+/// With loose constraints its size is zero, so it isn't even visited during paint.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Placeholder;
+
+crate::declare_widget!(PlaceholderMut, Placeholder);
+
+impl Placeholder {
+    /// Create a new placeholder.
+    pub fn new() -> Self {
+        Placeholder
+    }
+}
+
+impl Widget for Placeholder {
+    fn on_event(&mut self, _ctx: &mut EventCtx, _event: &Event, _env: &Env) {}
+
+    fn on_status_change(&mut self, _ctx: &mut LifeCycleCtx, _event: &StatusChange, _env: &Env) {}
+
+    fn lifecycle(&mut self, _ctx: &mut LifeCycleCtx, _event: &LifeCycle, _env: &Env) {}
+
+    fn layout(&mut self, _ctx: &mut LayoutCtx, bc: &BoxConstraints, _env: &Env) -> Size {
+        bc.min()
+    }
+
+    fn paint(&mut self, _ctx: &mut PaintCtx, _env: &Env) {}
+
+    fn children(&self) -> SmallVec<[WidgetRef<'_, dyn Widget>; 16]> {
+        SmallVec::new()
+    }
+
+    fn make_trace_span(&self) -> Span {
+        trace_span!("Placeholder")
+    }
+}
+
+// --- Tests ---
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{widget_ids, Record, TestHarness, TestWidgetExt as _};
+    use crate::widget::Flex;
+
+    #[test]
+    fn placeholder_is_zero_sized() {
+        let [placeholder_id] = widget_ids();
+        let widget = Flex::column().with_child_id(Placeholder::new(), placeholder_id);
+
+        let harness = TestHarness::create_with_size(widget, Size::new(100.0, 100.0));
+        let rect = harness.get_widget(placeholder_id).state().layout_rect();
+
+        assert_eq!(rect.size(), Size::ZERO);
+    }
+
+    #[test]
+    fn placeholder_skips_paint() {
+        let recording = Default::default();
+        let widget = Flex::column().with_child(Placeholder::new().record(&recording));
+
+        let mut harness = TestHarness::create_with_size(widget, Size::new(100.0, 100.0));
+        recording.clear();
+        harness.render();
+
+        let records = recording.drain();
+        assert!(!records.iter().any(|record| matches!(record, Record::Paint)));
+    }
+}
@@ -9,6 +9,9 @@ use std::ops::Range;
 use smallvec::{smallvec, SmallVec};
 use tracing::{trace_span, Span};
 
+use druid_shell::{KbKey, KeyEvent};
+
+use crate::gesture::{Fling, VelocityTracker};
 use crate::kurbo::{Point, Rect, Size, Vec2};
 use crate::widget::scroll_bar::SCROLLBAR_MOVED;
 use crate::widget::{Axis, ScrollBar, StoreInWidgetMut, WidgetMut, WidgetRef};
@@ -17,10 +20,19 @@ use crate::{
     RenderContext, StatusChange, Widget, WidgetPod,
 };
 
+/// How far the arrow keys scroll, in pixels.
+const KEYBOARD_LINE_STEP: f64 = 40.0;
+
 // TODO - refactor - see issue #15
 // TODO - rename "Portal" to "ScrollPortal"?
 // Conceptually, a Portal is a Widget giving a restricted view of a child widget
 // Imagine a very large widget, and a rect that represents the part of the widget we see
+/// A scrollable view of a larger child widget.
+///
+/// The portal doesn't take focus itself, so it can only be scrolled with the keyboard
+/// while one of its descendants has focus: key presses are sent to the focused widget
+/// and its ancestors. The arrow keys, Page Up/Down, Home and End then scroll the
+/// portal, unless the focused widget handled them or a modifier key is held.
 pub struct Portal<W: Widget> {
     child: WidgetPod<W>,
     // TODO - differentiate between the "explicit" viewport pos determined
@@ -36,6 +48,11 @@ pub struct Portal<W: Widget> {
     scrollbar_horizontal_visible: bool,
     scrollbar_vertical: WidgetPod<ScrollBar>,
     scrollbar_vertical_visible: bool,
+    flick_scroll: bool,
+    /// The last pointer position while the content is dragged.
+    drag_pos: Option<Point>,
+    velocity_tracker: VelocityTracker,
+    fling: Option<Fling>,
 }
 
 crate::declare_widget!(PortalMut, Portal<W: (Widget)>);
@@ -53,6 +70,10 @@ impl<W: Widget> Portal<W> {
             scrollbar_horizontal_visible: false,
             scrollbar_vertical: WidgetPod::new(ScrollBar::new(Axis::Vertical, 1.0, 1.0)),
             scrollbar_vertical_visible: false,
+            flick_scroll: false,
+            drag_pos: None,
+            velocity_tracker: VelocityTracker::new(),
+            fling: None,
         }
     }
 
@@ -101,6 +122,18 @@ impl<W: Widget> Portal<W> {
         self.must_fill = must_fill;
         self
     }
+
+    /// Builder-style method to set whether the content can be dragged to scroll.
+    ///
+    /// If `true`, pressing the left mouse button on content which doesn't handle the
+    /// press and dragging scrolls the portal. When the button is released, the portal
+    /// keeps scrolling at the pointer's speed and slows down to a stop, see [`Fling`].
+    ///
+    /// The default is `false`.
+    pub fn flick_scroll(mut self, flick_scroll: bool) -> Self {
+        self.flick_scroll = flick_scroll;
+        self
+    }
 }
 
 fn compute_pan_range(mut viewport: Range<f64>, target: Range<f64>) -> Range<f64> {
@@ -130,6 +163,34 @@ fn compute_pan_range(mut viewport: Range<f64>, target: Range<f64>) -> Range<f64>
 }
 
 impl<W: Widget> Portal<W> {
+    /// The viewport position a key press should scroll to, if it is a scrolling key.
+    ///
+    /// Arrow keys scroll by a line, Page Up/Down by the height of the portal, and
+    /// Home/End to the start/end of the content. Keys pressed with modifiers are left
+    /// alone, since they usually mean something else, eg Shift+Arrow to select text.
+    fn viewport_pos_for_key(
+        &self,
+        key: &KeyEvent,
+        portal_size: Size,
+        content_size: Size,
+    ) -> Option<Point> {
+        if !key.mods.is_empty() {
+            return None;
+        }
+        // TODO - horizontal scrolling?
+        let y = self.viewport_pos.y;
+        let new_y = match key.key {
+            KbKey::ArrowUp => y - KEYBOARD_LINE_STEP,
+            KbKey::ArrowDown => y + KEYBOARD_LINE_STEP,
+            KbKey::PageUp => y - portal_size.height,
+            KbKey::PageDown => y + portal_size.height,
+            KbKey::Home => 0.0,
+            KbKey::End => content_size.height,
+            _ => return None,
+        };
+        Some(Point::new(self.viewport_pos.x, new_y))
+    }
+
     // TODO - rename
     fn set_viewport_pos_raw(&mut self, portal_size: Size, content_size: Size, pos: Point) -> bool {
         let viewport_max_pos =
@@ -146,6 +207,85 @@ impl<W: Widget> Portal<W> {
             false
         }
     }
+
+    /// Move the viewport by `delta`, and the scrollbars with it.
+    ///
+    /// Returns `false` if the viewport didn't move, eg because it reached the end of the
+    /// content.
+    fn scroll_by(
+        &mut self,
+        ctx: &mut EventCtx,
+        portal_size: Size,
+        content_size: Size,
+        delta: Vec2,
+    ) -> bool {
+        let old_pos = self.viewport_pos;
+        if !self.set_viewport_pos_raw(portal_size, content_size, old_pos + delta) {
+            return false;
+        }
+        // Content that fits in the portal can't scroll on that axis, so the position
+        // only changes on axes with a positive range.
+        let scroll_range = content_size - portal_size;
+        if self.viewport_pos.x != old_pos.x && scroll_range.width > 0.0 {
+            ctx.get_mut(&mut self.scrollbar_horizontal)
+                .set_cursor_progress(self.viewport_pos.x / scroll_range.width);
+        }
+        if self.viewport_pos.y != old_pos.y && scroll_range.height > 0.0 {
+            ctx.get_mut(&mut self.scrollbar_vertical)
+                .set_cursor_progress(self.viewport_pos.y / scroll_range.height);
+        }
+        true
+    }
+
+    /// Scroll while the content is dragged, and keep scrolling once it's released.
+    fn handle_flick(
+        &mut self,
+        ctx: &mut EventCtx,
+        event: &Event,
+        portal_size: Size,
+        content_size: Size,
+    ) {
+        match event {
+            Event::MouseDown(mouse) if mouse.button.is_left() && !ctx.is_handled() => {
+                ctx.set_active(true);
+                ctx.set_handled();
+                self.drag_pos = Some(mouse.pos);
+                self.fling = None;
+                self.velocity_tracker.reset();
+                self.velocity_tracker.add_sample(ctx.now(), mouse.pos);
+            }
+            Event::MouseMove(mouse) => {
+                if let Some(drag_pos) = self.drag_pos {
+                    self.velocity_tracker.add_sample(ctx.now(), mouse.pos);
+                    // The content follows the pointer.
+                    self.scroll_by(ctx, portal_size, content_size, drag_pos - mouse.pos);
+                    self.drag_pos = Some(mouse.pos);
+                }
+            }
+            Event::MouseUp(mouse) if mouse.button.is_left() && self.drag_pos.is_some() => {
+                ctx.set_active(false);
+                self.drag_pos = None;
+                let fling = Fling::new(-self.velocity_tracker.velocity());
+                if !fling.is_finished() {
+                    self.fling = Some(fling);
+                    ctx.request_anim_frame();
+                }
+            }
+            Event::AnimFrame(interval) => {
+                if let Some(mut fling) = self.fling.take() {
+                    let delta = fling.advance(*interval);
+                    // The fling stops at the ends of the content.
+                    if self.scroll_by(ctx, portal_size, content_size, delta) && !fling.is_finished()
+                    {
+                        self.fling = Some(fling);
+                        ctx.request_anim_frame();
+                    }
+                }
+            }
+            Event::Wheel(_) => self.fling = None,
+            _ => {}
+        }
+    }
 }
 
 impl<'a, 'b, W: Widget> PortalMut<'a, 'b, W> {
@@ -196,6 +336,17 @@ impl<'a, 'b, W: Widget> PortalMut<'a, 'b, W> {
         self.ctx.request_layout();
     }
 
+    /// Set whether the content can be dragged to scroll.
+    ///
+    /// See [`Portal::flick_scroll`] for more details.
+    pub fn set_flick_scroll(&mut self, flick_scroll: bool) {
+        self.widget.flick_scroll = flick_scroll;
+        if !flick_scroll {
+            self.widget.drag_pos = None;
+            self.widget.fling = None;
+        }
+    }
+
     pub fn set_viewport_pos(&mut self, position: Point) -> bool {
         let portal_size = self.ctx.widget_state.layout_rect().size();
         let content_size = self.widget.child.layout_rect().size();
@@ -243,7 +394,6 @@ impl<W: Widget> Widget for Portal<W> {
         let portal_size = ctx.size();
         let content_size = self.child.layout_rect().size();
 
-        // TODO - handle Home/End keys, etc
         match event {
             Event::Wheel(wheel_event) => {
                 self.set_viewport_pos_raw(
@@ -270,8 +420,26 @@ impl<W: Widget> Widget for Portal<W> {
         }
 
         self.child.on_event(ctx, event, env);
+
+        // Key presses reach us if a descendant has focus; we only scroll if that
+        // descendant didn't use the key itself.
+        if let Event::KeyDown(key) = event {
+            if !ctx.is_handled() {
+                if let Some(pos) = self.viewport_pos_for_key(key, portal_size, content_size) {
+                    let delta = pos - self.viewport_pos;
+                    self.scroll_by(ctx, portal_size, content_size, delta);
+                    ctx.set_handled();
+                }
+            }
+        }
+
         self.scrollbar_horizontal.on_event(ctx, event, env);
         self.scrollbar_vertical.on_event(ctx, event, env);
+
+        // Presses the content or the scrollbars handled aren't drags.
+        if self.flick_scroll {
+            self.handle_flick(ctx, event, portal_size, content_size);
+        }
         ctx.request_layout();
     }
 
@@ -377,12 +545,15 @@ impl<W: Widget> Widget for Portal<W> {
 
 #[cfg(test)]
 mod tests {
+    use std::time::Duration;
+
+    use druid_shell::{MouseButton, RawMods};
     use insta::assert_debug_snapshot;
 
     use super::*;
     use crate::assert_render_snapshot;
     use crate::testing::{widget_ids, TestHarness};
-    use crate::widget::{Button, Flex, SizedBox};
+    use crate::widget::{Button, Flex, Label, SizedBox};
 
     fn button(text: &str) -> impl Widget {
         SizedBox::new(Button::new(text)).width(70.0).height(40.0)
@@ -453,6 +624,75 @@ mod tests {
         assert_render_snapshot!(harness, "button_list_scroll_to_item_13");
     }
 
+    #[test]
+    fn flick_scroll() {
+        let widget =
+            Portal::new(Flex::column().with_child(SizedBox::empty().width(100.0).height(1000.0)))
+                .flick_scroll(true);
+
+        let mut harness = TestHarness::create_with_size(widget, Size::new(200., 200.));
+        let viewport_y = |harness: &TestHarness| {
+            let portal = harness.root_widget();
+            let portal = portal.downcast::<Portal<Flex>>().unwrap();
+            portal.get_viewport_pos().y
+        };
+
+        harness.mouse_move(Point::new(50.0, 150.0));
+        harness.mouse_button_press(MouseButton::Left);
+        // The velocity is measured on the harness clock.
+        harness.move_timers_forward(Duration::from_millis(16));
+        harness.mouse_move(Point::new(50.0, 100.0));
+        harness.move_timers_forward(Duration::from_millis(16));
+        harness.mouse_move(Point::new(50.0, 50.0));
+        assert_eq!(viewport_y(&harness), 100.0);
+
+        // After the release, the portal keeps scrolling in the same direction.
+        harness.mouse_button_release(MouseButton::Left);
+        assert!(harness.window().wants_animation_frame());
+        harness.process_event(Event::AnimFrame(16_000_000));
+        assert!(viewport_y(&harness) > 100.0);
+    }
+
+    #[test]
+    fn keyboard_scroll() {
+        let [label_id] = widget_ids();
+        let widget = Portal::new(
+            Flex::column()
+                .with_child_id(Label::new("Focus me").with_selectable(true), label_id)
+                .with_child(SizedBox::empty().width(100.0).height(1000.0)),
+        );
+
+        let mut harness = TestHarness::create_with_size(widget, Size::new(200., 200.));
+        // Key presses are sent to the focused widget and its parents.
+        harness.mouse_click_on(label_id);
+
+        let mut press = |key: KbKey| {
+            let event = KeyEvent::for_test(RawMods::None, key);
+            harness.process_event(Event::KeyDown(event));
+            let portal = harness.root_widget();
+            let portal = portal.downcast::<Portal<Flex>>().unwrap();
+            portal.get_viewport_pos().y
+        };
+
+        assert_eq!(press(KbKey::PageDown), 200.0);
+        assert_eq!(press(KbKey::ArrowDown), 200.0 + KEYBOARD_LINE_STEP);
+        assert_eq!(press(KbKey::ArrowUp), 200.0);
+        assert_eq!(press(KbKey::PageUp), 0.0);
+        // Scrolling is clamped to the content.
+        assert_eq!(press(KbKey::PageUp), 0.0);
+        let end = press(KbKey::End);
+        assert!(end > 800.0);
+        assert_eq!(press(KbKey::PageDown), end);
+        assert_eq!(press(KbKey::Home), 0.0);
+
+        // Keys with modifiers don't scroll.
+        let event = KeyEvent::for_test(RawMods::Shift, KbKey::PageDown);
+        harness.process_event(Event::KeyDown(event));
+        let portal = harness.root_widget();
+        let portal = portal.downcast::<Portal<Flex>>().unwrap();
+        assert_eq!(portal.get_viewport_pos().y, 0.0);
+    }
+
     // Helper function for panning tests
     fn make_range(repr: &str) -> Range<f64> {
         let repr = &repr[repr.find('_').unwrap()..];
@@ -0,0 +1,244 @@
+// This software is licensed under Apache License 2.0 and distributed on an
+// "as-is" basis without warranties of any kind. See the LICENSE file for
+// details.
+
+//! A thin line used to visually separate groups of widgets.
+
+use smallvec::SmallVec;
+use tracing::{trace, trace_span, Span};
+
+use crate::widget::{Axis, WidgetRef};
+use crate::{
+    theme, BoxConstraints, Color, Env, Event, EventCtx, Insets, KeyOrValue, LayoutCtx, LifeCycle,
+    LifeCycleCtx, PaintCtx, RenderContext, Size, StatusChange, Widget,
+};
+
+/// A thin colored line, used between list items or groups of toolbar buttons.
+///
+/// A separator expands along its axis to fill the space its parent gives it, and has
+/// a fixed thickness on the cross axis. Optional insets add empty margins around the line.
+///
+/// This is roughly equivalent to an empty [`SizedBox`](crate::widget::SizedBox) with a
+/// background color and a fixed width or height, but doesn't require picking the
+/// expanded dimension by hand.
+pub struct Separator {
+    axis: Axis,
+    thickness: KeyOrValue<f64>,
+    color: KeyOrValue<Color>,
+    insets: Insets,
+}
+
+crate::declare_widget!(SeparatorMut, Separator);
+
+impl Separator {
+    /// Create a separator along the given axis.
+    ///
+    /// A horizontal separator is a horizontal line, which separates widgets stacked
+    /// vertically.
+    pub fn new(axis: Axis) -> Self {
+        Separator {
+            axis,
+            thickness: 1.0.into(),
+            color: theme::BORDER_LIGHT.into(),
+            insets: Insets::ZERO,
+        }
+    }
+
+    /// Create a horizontal line.
+    pub fn horizontal() -> Self {
+        Self::new(Axis::Horizontal)
+    }
+
+    /// Create a vertical line.
+    pub fn vertical() -> Self {
+        Self::new(Axis::Vertical)
+    }
+
+    /// Builder-style method for setting the thickness of the line.
+    ///
+    /// The argument can be either an `f64` or a [`Key<f64>`](crate::Key).
+    pub fn with_thickness(mut self, thickness: impl Into<KeyOrValue<f64>>) -> Self {
+        self.thickness = thickness.into();
+        self
+    }
+
+    /// Builder-style method for setting the color of the line.
+    ///
+    /// The argument can be either a `Color` or a [`Key<Color>`](crate::Key).
+    pub fn with_color(mut self, color: impl Into<KeyOrValue<Color>>) -> Self {
+        self.color = color.into();
+        self
+    }
+
+    /// Builder-style method for adding empty margins around the line.
+    pub fn with_insets(mut self, insets: impl Into<Insets>) -> Self {
+        self.insets = insets.into();
+        self
+    }
+}
+
+impl<'a, 'b> SeparatorMut<'a, 'b> {
+    /// Set the axis of the line.
+    pub fn set_axis(&mut self, axis: Axis) {
+        self.widget.axis = axis;
+        self.ctx.request_layout();
+    }
+
+    /// Set the thickness of the line.
+    ///
+    /// The argument can be either an `f64` or a [`Key<f64>`](crate::Key).
+    pub fn set_thickness(&mut self, thickness: impl Into<KeyOrValue<f64>>) {
+        self.widget.thickness = thickness.into();
+        self.ctx.request_layout();
+    }
+
+    /// Set the color of the line.
+    ///
+    /// The argument can be either a `Color` or a [`Key<Color>`](crate::Key).
+    pub fn set_color(&mut self, color: impl Into<KeyOrValue<Color>>) {
+        self.widget.color = color.into();
+        self.ctx.request_paint();
+    }
+
+    /// Set the empty margins around the line.
+    pub fn set_insets(&mut self, insets: impl Into<Insets>) {
+        self.widget.insets = insets.into();
+        self.ctx.request_layout();
+    }
+}
+
+impl Widget for Separator {
+    fn on_event(&mut self, _ctx: &mut EventCtx, _event: &Event, _env: &Env) {}
+
+    fn on_status_change(&mut self, _ctx: &mut LifeCycleCtx, _event: &StatusChange, _env: &Env) {}
+
+    fn lifecycle(&mut self, _ctx: &mut LifeCycleCtx, _event: &LifeCycle, _env: &Env) {}
+
+    fn layout(&mut self, _ctx: &mut LayoutCtx, bc: &BoxConstraints, env: &Env) -> Size {
+        let thickness = self.thickness.resolve(env);
+        let insets_size = self.insets.size();
+
+        // Along our axis, we take all the space we're given, but no more than that.
+        let major = if self.axis.major(bc.max()).is_finite() {
+            self.axis.major(bc.max())
+        } else {
+            self.axis.major(bc.min()).max(self.axis.major(insets_size))
+        };
+        let minor = thickness + self.axis.minor(insets_size);
+
+        let size = bc.constrain(self.axis.pack(major, minor));
+        trace!("Computed size: {}", size);
+        size
+    }
+
+    fn paint(&mut self, ctx: &mut PaintCtx, env: &Env) {
+        let line = ctx.size().to_rect() - self.insets;
+        if line.width() > 0.0 && line.height() > 0.0 {
+            ctx.fill(line, &self.color.resolve(env));
+        }
+    }
+
+    fn children(&self) -> SmallVec<[WidgetRef<'_, dyn Widget>; 16]> {
+        SmallVec::new()
+    }
+
+    fn make_trace_span(&self) -> Span {
+        trace_span!("Separator")
+    }
+}
+
+// --- Tests ---
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{pixel_at, widget_ids, TestHarness};
+    use crate::widget::{Flex, Label};
+
+    #[test]
+    fn horizontal_separator() {
+        let [separator_id] = widget_ids();
+        let widget = Flex::column()
+            .with_child(Label::new("hello"))
+            .with_child_id(
+                Separator::horizontal()
+                    .with_thickness(2.0)
+                    .with_color(Color::RED)
+                    .with_insets((0.0, 3.0)),
+                separator_id,
+            )
+            .with_child(Label::new("world"));
+
+        let mut harness = TestHarness::create_with_size(widget, Size::new(100.0, 100.0));
+        let rect = harness.get_widget(separator_id).state().layout_rect();
+
+        assert_eq!(rect.size(), Size::new(100.0, 8.0));
+
+        // The line is drawn between the vertical insets, across the whole width.
+        let image = harness.render();
+        let y = rect.y0 as usize;
+        let red = [255, 0, 0, 255];
+        assert_eq!(pixel_at(&image, 100, 50, y + 4), red);
+        assert_eq!(pixel_at(&image, 100, 0, y + 4), red);
+        assert_ne!(pixel_at(&image, 100, 50, y + 1), red);
+        assert_ne!(pixel_at(&image, 100, 50, y + 6), red);
+    }
+
+    #[test]
+    fn vertical_separator() {
+        let [separator_id] = widget_ids();
+        let widget = Flex::row()
+            .with_child(Label::new("hello"))
+            .with_child_id(
+                Separator::vertical()
+                    .with_thickness(2.0)
+                    .with_color(Color::RED)
+                    .with_insets((3.0, 10.0)),
+                separator_id,
+            )
+            .with_child(Label::new("world"));
+
+        let mut harness = TestHarness::create_with_size(widget, Size::new(100.0, 100.0));
+        let rect = harness.get_widget(separator_id).state().layout_rect();
+
+        assert_eq!(rect.size(), Size::new(8.0, 100.0));
+
+        // The line is drawn between the horizontal insets, and the vertical ones.
+        let image = harness.render();
+        let x = rect.x0 as usize;
+        let red = [255, 0, 0, 255];
+        assert_eq!(pixel_at(&image, 100, x + 4, 50), red);
+        assert_ne!(pixel_at(&image, 100, x + 1, 50), red);
+        assert_ne!(pixel_at(&image, 100, x + 6, 50), red);
+        assert_ne!(pixel_at(&image, 100, x + 4, 5), red);
+    }
+
+    #[test]
+    fn edit_separator() {
+        let image_1 = {
+            let separator = Separator::horizontal()
+                .with_thickness(4.0)
+                .with_color(Color::PURPLE);
+
+            let mut harness = TestHarness::create_with_size(separator, Size::new(30.0, 30.0));
+            harness.render()
+        };
+
+        let image_2 = {
+            let separator = Separator::horizontal();
+
+            let mut harness = TestHarness::create_with_size(separator, Size::new(30.0, 30.0));
+
+            harness.edit_root_widget(|mut separator, _| {
+                let mut separator = separator.downcast::<Separator>().unwrap();
+                separator.set_thickness(4.0);
+                separator.set_color(Color::PURPLE);
+            });
+
+            harness.render()
+        };
+
+        // We don't use assert_eq because we don't want rich assert
+        assert!(image_1 == image_2);
+    }
+}
@@ -0,0 +1,83 @@
+// This software is licensed under Apache License 2.0 and distributed on an
+// "as-is" basis without warranties of any kind. See the LICENSE file for
+// details.
+
+//! Sets of the edges of a box.
+
+/// A set of edges of a box, eg for
+/// [`SizedBox::edge_fade`](crate::widget::SizedBox::edge_fade).
+///
+/// Combine the constants with `|`, eg `Sides::TOP | Sides::LEFT`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct Sides {
+    /// Whether the top edge is included.
+    pub top: bool,
+    /// Whether the right edge is included.
+    pub right: bool,
+    /// Whether the bottom edge is included.
+    pub bottom: bool,
+    /// Whether the left edge is included.
+    pub left: bool,
+}
+
+impl Sides {
+    /// No edges.
+    pub const NONE: Sides = Sides {
+        top: false,
+        right: false,
+        bottom: false,
+        left: false,
+    };
+    /// The top edge.
+    pub const TOP: Sides = Sides {
+        top: true,
+        ..Sides::NONE
+    };
+    /// The right edge.
+    pub const RIGHT: Sides = Sides {
+        right: true,
+        ..Sides::NONE
+    };
+    /// The bottom edge.
+    pub const BOTTOM: Sides = Sides {
+        bottom: true,
+        ..Sides::NONE
+    };
+    /// The left edge.
+    pub const LEFT: Sides = Sides {
+        left: true,
+        ..Sides::NONE
+    };
+    /// The top and bottom edges.
+    pub const VERTICAL: Sides = Sides {
+        top: true,
+        bottom: true,
+        ..Sides::NONE
+    };
+    /// The left and right edges.
+    pub const HORIZONTAL: Sides = Sides {
+        right: true,
+        left: true,
+        ..Sides::NONE
+    };
+    /// All four edges.
+    pub const ALL: Sides = Sides {
+        top: true,
+        right: true,
+        bottom: true,
+        left: true,
+    };
+}
+
+impl std::ops::BitOr for Sides {
+    type Output = Sides;
+
+    fn bitor(self, other: Sides) -> Sides {
+        Sides {
+            top: self.top || other.top,
+            right: self.right || other.right,
+            bottom: self.bottom || other.bottom,
+            left: self.left || other.left,
+        }
+    }
+}
@@ -4,41 +4,188 @@
 
 //! A widget with predefined size.
 
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
 use std::f64::INFINITY;
+use std::hash::{Hash, Hasher};
+use std::rc::Rc;
+use std::sync::Arc;
+use std::time::Duration;
 
+use druid_shell::{Cursor, KbKey};
+use fnv::FnvHasher;
 use smallvec::{smallvec, SmallVec};
 use tracing::{trace, trace_span, warn, Span};
 
-use crate::kurbo::RoundedRectRadii;
-use crate::piet::{Color, FixedGradient, LinearGradient, PaintBrush, RadialGradient};
-use crate::widget::{WidgetId, WidgetMut, WidgetPod, WidgetRef};
+use crate::kurbo::{Affine, BezPath, Rect, RoundedRect, RoundedRectRadii, Vec2};
+use crate::piet::{Color, ImageBuf, InterpolationMode, PaintBrush, PietImage};
+use crate::theme::BackgroundRole;
+use crate::widget::background_brush::{ImageCache, MarchingAnts};
+use crate::widget::{
+    BackgroundBrush, Corner, Sides, StateStyles, WidgetId, WidgetMut, WidgetPod, WidgetRef,
+};
 use crate::{
-    BoxConstraints, Env, Event, EventCtx, Key, KeyOrValue, LayoutCtx, LifeCycle, LifeCycleCtx,
-    PaintCtx, Point, RenderContext, Size, StatusChange, Widget,
+    theme, Accessibility, BoxConstraints, ChangedKeys, Data, Env, Event, EventCtx, Insets, Key,
+    KeyOrValue, LayoutCtx, LifeCycle, LifeCycleCtx, MissingKeyError, MouseEvent, PaintCtx, Point,
+    RenderContext, Role, Selector, Size, StatusChange, Widget,
 };
 
 // FIXME - Improve all doc in this module ASAP.
 
-/// Something that can be used as the background for a widget.
-#[non_exhaustive]
-#[allow(missing_docs)]
-#[allow(clippy::type_complexity)]
-pub enum BackgroundBrush {
-    Color(KeyOrValue<Color>),
-    Linear(LinearGradient),
-    Radial(RadialGradient),
-    Fixed(FixedGradient),
-    PainterFn(Box<dyn FnMut(&mut PaintCtx, &Env)>),
-}
+/// Sent to the boxes sharing a [`SharedSize`] when its width changes.
+const SHARED_SIZE_CHANGED: Selector = Selector::new("masonry-builtin.shared-size-changed");
+
+/// The thickness of the line under a hovered [link](SizedBox::as_link).
+const LINK_UNDERLINE_WIDTH: f64 = 1.0;
+
+/// The smallest size recommended for controls which are tapped with a finger.
+const MIN_TOUCH_TARGET: f64 = 44.0;
 
 /// Something that can be used as the border for a widget.
 struct BorderStyle {
     width: KeyOrValue<f64>,
     color: KeyOrValue<Color>,
+    /// Whether the border is painted one device pixel wide, whatever `width` is.
+    hairline: bool,
+    /// Whether an invalid width read from the [`Env`] was already reported.
+    warned_invalid_width: Cell<bool>,
+}
+
+/// A shadow painted inside a [`SizedBox`], as if the box was pressed in.
+struct InnerShadow {
+    offset: Vec2,
+    blur: f64,
+    color: KeyOrValue<Color>,
+}
+
+/// A background, border and corner radius which are resolved together.
+///
+/// Store one in the [`Env`] under a single key and pass that key to
+/// [`SizedBox::styled`]: since the whole bundle is one env value, a theme switch
+/// replaces the background and border at once, and a box can never be painted with
+/// the background of one theme and the border of another.
+///
+/// Bundles implement [`Data`], so they can also be kept in app state; `same` compares
+/// every field, see [`StyleBrush`] for how brushes are compared.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StyleBundle {
+    /// The background color, painted if there is no [`brush`](Self::brush).
+    pub background: Color,
+    /// A brush painted instead of the background color.
+    pub brush: Option<StyleBrush>,
+    /// The border color.
+    pub border_color: Color,
+    /// The border width. Negative and NaN widths are treated as zero.
+    pub border_width: f64,
+    /// The corner radius. Negative and NaN radii are treated as zero.
+    pub corner_radius: RoundedRectRadii,
+}
+
+/// A background brush in a [`StyleBundle`].
+///
+/// Unlike [`BackgroundBrush`], it can be shared between threads, so bundles can be
+/// stored in the [`Env`].
+///
+/// Brushes are equal if they would paint the same thing, as with [`BackgroundBrush`].
+/// Painter functions can't be inspected, so they are never equal, not even to
+/// themselves.
+#[derive(Clone)]
+pub enum StyleBrush {
+    /// A color or gradient.
+    Brush(PaintBrush),
+    /// A function painting the background into the whole box.
+    #[allow(clippy::type_complexity)]
+    Painter(Arc<dyn Fn(&mut PaintCtx, &Env) + Send + Sync>),
+}
+
+/// The second child of a responsive [`SizedBox`], shown when the box is narrow.
+struct Responsive {
+    breakpoint: f64,
+    narrow: WidgetPod<Box<dyn Widget>>,
+}
+
+/// A width shared by several [`SizedBox`]es, eg the cells of a table column.
+///
+/// Pass clones of the same handle to [`SizedBox::shared_width`]. Each box measures
+/// the width it would like, and they all take the largest of those widths, so the
+/// column is as wide as its widest cell.
+///
+/// When a box changes the shared width during layout, the other boxes are asked to
+/// lay out again, so the cells laid out before it catch up on the next pass.
+#[derive(Debug, Clone, Default)]
+pub struct SharedSize {
+    widths: Rc<RefCell<HashMap<WidgetId, f64>>>,
+}
+
+/// A box's membership in a [`SharedSize`].
+///
+/// The box's width is taken out of the shared width when this is dropped, eg when the
+/// box is removed from the tree or stops sharing its width.
+struct SharedWidth {
+    handle: SharedSize,
+    /// The box's id, once it has contributed a width.
+    id: Option<WidgetId>,
+}
+
+/// Whether the width and height of a [`SizedBox`] include its border and padding.
+///
+/// The names match the CSS `box-sizing` property.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum BoxSizing {
+    /// The width and height are the size of the child; the border and padding are
+    /// added around it, so the box ends up larger.
+    ContentBox,
+    /// The width and height are the size of the whole box; the child gets what's
+    /// left inside the border and padding. This is the default.
+    #[default]
+    BorderBox,
+}
+
+/// A fade of the child to transparent along some edges of a [`SizedBox`].
+struct EdgeFade {
+    sides: Sides,
+    length: f64,
+}
+
+/// A [`SizedBox`] or its child, as painted into an offscreen bitmap.
+struct RenderCache {
+    image: PietImage,
+    /// The area covered by `image`, in the box's coordinates.
+    rect: Rect,
+    scale: f64,
+}
+
+/// An in-progress crossfade between the previous background color and the current one.
+struct ColorTransition {
+    from: Color,
+    elapsed: Duration,
+}
+
+/// An error returned by [`SizedBox::try_border`] when the border width is invalid.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[non_exhaustive]
+pub enum BorderError {
+    /// The border width was negative.
+    NegativeWidth(f64),
+    /// The border width was NaN.
+    NanWidth,
+}
+
+/// An error returned by [`SizedBox::try_build`] when a setting is invalid.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[non_exhaustive]
+pub enum BoxConfigError {
+    /// The width was negative or NaN.
+    InvalidWidth(f64),
+    /// The height was negative or NaN.
+    InvalidHeight(f64),
+    /// The border width was invalid.
+    Border(BorderError),
+    /// A corner radius was negative or NaN.
+    InvalidCornerRadius(f64),
 }
 
 // TODO - Have Widget type as generic argument
-// TODO - Add Padding
 
 /// A widget with predefined size.
 ///
@@ -51,10 +198,84 @@ struct BorderStyle {
 /// it will be treated as zero.
 pub struct SizedBox {
     child: Option<WidgetPod<Box<dyn Widget>>>,
+    responsive: Option<Responsive>,
     width: Option<f64>,
     height: Option<f64>,
+    fill_width: bool,
+    fill_height: bool,
+    aspect_ratio: Option<f64>,
+    /// Whether the aspect ratio covers the maximum size instead of fitting in it.
+    aspect_cover: bool,
+    loosen_child: bool,
+    /// The corner the child is placed in, and its distance from the edges.
+    pin: Option<(Corner, Insets)>,
+    clip_child: bool,
+    transform: Option<Affine>,
+    rotation: f64,
+    snap_child_to_pixels: bool,
+    baseline_grid: Option<f64>,
+    box_sizing: BoxSizing,
+    padding: Insets,
+    hit_test_inset: Insets,
+    measure_intrinsic_width: bool,
+    child_intrinsic_width: f64,
+    /// Where the last layout pass placed the child.
+    child_origin: Option<Point>,
+    shared_width: Option<SharedWidth>,
+    z_index: i32,
+    constraints: Option<BoxConstraints>,
+    /// The minimum size set with `min_touch_target_size`, applied on top of `constraints`.
+    min_touch_target: Option<Size>,
     background: Option<BackgroundBrush>,
+    animation_painted: bool,
+    animation_paused: bool,
+    /// Whether the last paint pass drew a background, a border or a child.
+    painted_content: bool,
+    background_over_child: bool,
+    hover_background: Option<BackgroundBrush>,
+    hover_repaints_child: bool,
+    focusable: bool,
+    is_link: bool,
+    cursor: Option<Cursor>,
+    #[allow(clippy::type_complexity)]
+    context_menu_handler: Option<Box<dyn FnMut(&mut EventCtx, Point)>>,
+    #[allow(clippy::type_complexity)]
+    wheel_handler: Option<Box<dyn FnMut(&mut EventCtx, Vec2) -> bool>>,
+    #[allow(clippy::type_complexity)]
+    click_handler: Option<Box<dyn FnMut(&mut EventCtx)>>,
+    #[allow(clippy::type_complexity)]
+    env_scope: Option<Box<dyn Fn(&mut Env)>>,
+    /// The env passed to the box, and the result of `env_scope` applied to it.
+    scoped_env: Option<(Env, Env)>,
+    accessibility_label: Option<String>,
+    accessibility_role: Option<Role>,
+    antialias: bool,
+    transition_duration: Option<Duration>,
+    color_transition: Option<ColorTransition>,
+    painted_color: Option<Color>,
     border: Option<BorderStyle>,
+    inner_shadow: Option<InnerShadow>,
+    marching_ants: Option<MarchingAnts>,
+    dashed_border: Option<MarchingAnts>,
+    focus_ring: Option<BorderStyle>,
+    cache_rendering: bool,
+    render_cache: Option<RenderCache>,
+    /// The last image background painted, made into a backend image.
+    image_cache: Option<ImageCache>,
+    /// The whole box painted with its opacity, if that's below `1.0`.
+    opacity_cache: Option<RenderCache>,
+    collapse_when_empty: bool,
+    /// Whether the last layout pass collapsed the box because the child was empty.
+    collapsed: bool,
+    visible_if: Option<Key<bool>>,
+    /// Whether we already warned that the `visible_if` key is missing from the [`Env`].
+    warned_missing_visible_if: bool,
+    /// Whether the last layout pass hid the box because `visible_if` was false.
+    hidden: bool,
+    edge_fade: Option<EdgeFade>,
+    opacity: f64,
+    style: Option<KeyOrValue<Arc<StyleBundle>>>,
+    state_styles: Option<StateStyles>,
     corner_radius: KeyOrValue<RoundedRectRadii>,
 }
 crate::declare_widget!(SizedBoxMut, SizedBox);
@@ -62,25 +283,22 @@ crate::declare_widget!(SizedBoxMut, SizedBox);
 impl SizedBox {
     /// Construct container with child, and both width and height not set.
     pub fn new(child: impl Widget) -> Self {
-        Self {
-            child: Some(WidgetPod::new(child).boxed()),
-            width: None,
-            height: None,
-            background: None,
-            border: None,
-            corner_radius: RoundedRectRadii::from_single_radius(0.0).into(),
-        }
+        Self::from_pod(WidgetPod::new(child).boxed())
     }
 
     /// Construct container with child, and both width and height not set.
     pub fn new_with_id(child: impl Widget, id: WidgetId) -> Self {
+        Self::from_pod(WidgetPod::new_with_id(child, id).boxed())
+    }
+
+    /// Construct container with an existing pod as its child.
+    ///
+    /// The pod keeps its id, so a subtree can be moved from another parent without
+    /// being rebuilt.
+    pub fn from_pod(child: WidgetPod<Box<dyn Widget>>) -> Self {
         Self {
-            child: Some(WidgetPod::new_with_id(child, id).boxed()),
-            width: None,
-            height: None,
-            background: None,
-            border: None,
-            corner_radius: RoundedRectRadii::from_single_radius(0.0).into(),
+            child: Some(child),
+            ..Self::empty()
         }
     }
 
@@ -92,23 +310,134 @@ impl SizedBox {
     pub fn empty() -> Self {
         Self {
             child: None,
+            responsive: None,
             width: None,
             height: None,
+            fill_width: false,
+            fill_height: false,
+            aspect_ratio: None,
+            aspect_cover: false,
+            loosen_child: false,
+            pin: None,
+            clip_child: false,
+            transform: None,
+            rotation: 0.0,
+            snap_child_to_pixels: false,
+            baseline_grid: None,
+            box_sizing: BoxSizing::BorderBox,
+            padding: Insets::ZERO,
+            hit_test_inset: Insets::ZERO,
+            measure_intrinsic_width: false,
+            child_intrinsic_width: 0.0,
+            child_origin: None,
+            shared_width: None,
+            z_index: 0,
+            constraints: None,
+            min_touch_target: None,
             background: None,
+            animation_painted: false,
+            animation_paused: false,
+            painted_content: false,
+            background_over_child: false,
+            hover_background: None,
+            hover_repaints_child: false,
+            focusable: false,
+            is_link: false,
+            cursor: None,
+            context_menu_handler: None,
+            wheel_handler: None,
+            click_handler: None,
+            env_scope: None,
+            scoped_env: None,
+            accessibility_label: None,
+            accessibility_role: None,
+            antialias: true,
+            transition_duration: None,
+            color_transition: None,
+            painted_color: None,
             border: None,
+            inner_shadow: None,
+            marching_ants: None,
+            dashed_border: None,
+            focus_ring: None,
+            cache_rendering: false,
+            render_cache: None,
+            image_cache: None,
+            opacity_cache: None,
+            collapse_when_empty: false,
+            collapsed: false,
+            visible_if: None,
+            warned_missing_visible_if: false,
+            hidden: false,
+            edge_fade: None,
+            opacity: 1.0,
+            style: None,
+            state_styles: None,
             corner_radius: RoundedRectRadii::from_single_radius(0.0).into(),
         }
     }
 
+    /// Construct container which shows `wide` or `narrow`, depending on the space available.
+    ///
+    /// During layout, `narrow` is shown if the maximum width allowed by the parent is less
+    /// than `breakpoint_width`, and `wide` is shown otherwise. Only the child shown is laid
+    /// out, painted and sent events. The other one is stashed: it stays in the widget tree
+    /// and keeps its state, eg the text of a text box, for when the box is resized again.
+    ///
+    /// Until the first layout, `wide` is shown. The other settings of the box apply to
+    /// whichever child is shown. [`SizedBoxMut::set_child`] replaces the wide child.
+    pub fn responsive(breakpoint_width: f64, wide: impl Widget, narrow: impl Widget) -> Self {
+        let mut narrow = WidgetPod::new(narrow).boxed();
+        narrow.state.is_stashed = true;
+        Self {
+            responsive: Some(Responsive {
+                breakpoint: breakpoint_width,
+                narrow,
+            }),
+            ..Self::new(wide)
+        }
+    }
+
+    /// Construct a square button around an icon, like the ones in toolbars.
+    ///
+    /// The box is `size` wide and high, with `icon` centered in it. It gets a background
+    /// of [`theme::ICON_BUTTON_HOVER_COLOR`] under the mouse, and corners rounded by
+    /// [`theme::ICON_BUTTON_RADIUS`]. If `size` is smaller than a touch target, the
+    /// area receiving mouse events is extended around the box to 44x44, like
+    /// [`min_touch_target`](Self::min_touch_target) but without changing the layout.
+    ///
+    /// Use [`on_click`](Self::on_click) to react to the button. The other builder
+    /// methods can still be used to change the preset.
+    pub fn icon_button(size: f64, icon: impl Widget) -> Self {
+        let margin = ((MIN_TOUCH_TARGET - size) / 2.0).max(0.0);
+        Self::new(icon)
+            .constrain(BoxConstraints::tight(Size::new(size, size)))
+            .loosen_child(true)
+            .hit_test_inset(Insets::uniform(-margin))
+            .hover_background(theme::ICON_BUTTON_HOVER_COLOR)
+            .rounded(theme::ICON_BUTTON_RADIUS)
+    }
+
+    /// Construct container with child, on the theme's surface color.
+    ///
+    /// The background is [`theme::SURFACE_COLOR`], read from the [`Env`] when painting,
+    /// so every surface follows the theme. Calling [`background`](Self::background)
+    /// afterwards replaces it.
+    pub fn surface(child: impl Widget) -> Self {
+        Self::new(child).background(BackgroundRole::Surface)
+    }
+
     /// Set container's width.
     pub fn width(mut self, width: f64) -> Self {
         self.width = Some(width);
+        self.fill_width = false;
         self
     }
 
     /// Set container's height.
     pub fn height(mut self, height: f64) -> Self {
         self.height = Some(height);
+        self.fill_height = false;
         self
     }
 
@@ -123,6 +452,8 @@ impl SizedBox {
     pub fn expand(mut self) -> Self {
         self.width = Some(INFINITY);
         self.height = Some(INFINITY);
+        self.fill_width = false;
+        self.fill_height = false;
         self
     }
 
@@ -131,6 +462,7 @@ impl SizedBox {
     /// This will force the child to have maximum width.
     pub fn expand_width(mut self) -> Self {
         self.width = Some(INFINITY);
+        self.fill_width = false;
         self
     }
 
@@ -139,389 +471,6323 @@ impl SizedBox {
     /// This will force the child to have maximum height.
     pub fn expand_height(mut self) -> Self {
         self.height = Some(INFINITY);
+        self.fill_height = false;
         self
     }
 
-    /// Builder-style method for setting the background for this widget.
+    /// Fill the space available in the parent.
     ///
-    /// This can be passed anything which can be represented by a [`BackgroundBrush`];
-    /// notably, it can be any [`Color`], a [`Key<Color>`](Key) resolvable in the [`Env`],
-    /// any gradient, or a fully custom painter `FnMut`.
-    pub fn background(mut self, brush: impl Into<BackgroundBrush>) -> Self {
-        self.background = Some(brush.into());
+    /// Unlike [`expand`](Self::expand), this never asks for an infinite size. On an axis
+    /// where the parent's constraints are bounded, the container takes the maximum
+    /// available size. On an unbounded axis, it falls back to the size of its child (or
+    /// to the minimum size allowed, if it has no child), as if no size had been set.
+    pub fn fill(mut self) -> Self {
+        self.width = None;
+        self.height = None;
+        self.fill_width = true;
+        self.fill_height = true;
         self
     }
 
-    /// Builder-style method for painting a border around the widget with a color and width.
+    /// Fill the space available in the parent on the x-axis.
     ///
-    /// Arguments can be either concrete values, or a [`Key`] of the respective
-    /// type.
-    pub fn border(
-        mut self,
-        color: impl Into<KeyOrValue<Color>>,
-        width: impl Into<KeyOrValue<f64>>,
-    ) -> Self {
-        self.border = Some(BorderStyle {
-            color: color.into(),
-            width: width.into(),
-        });
+    /// See [`fill`](Self::fill) for details. The height is left untouched.
+    pub fn fill_width(mut self) -> Self {
+        self.width = None;
+        self.fill_width = true;
         self
     }
 
-    /// Builder style method for rounding off corners of this container by setting a corner radius
-    pub fn rounded(mut self, radius: impl Into<KeyOrValue<RoundedRectRadii>>) -> Self {
-        self.corner_radius = radius.into();
+    /// Fill the space available in the parent on the y-axis.
+    ///
+    /// See [`fill`](Self::fill) for details. The width is left untouched.
+    pub fn fill_height(mut self) -> Self {
+        self.height = None;
+        self.fill_height = true;
         self
     }
 
-    // TODO - child()
-}
+    /// Keep the container's width divided by its height equal to `ratio`.
+    ///
+    /// The container takes the largest size with that ratio which fits the maximum
+    /// size it would otherwise have, eg its width if one is set. If only one axis is
+    /// bounded, the other one is computed from it; if neither is, the ratio is
+    /// ignored. The parent's minimum size still wins over the ratio.
+    ///
+    /// A `ratio` which isn't positive is ignored, with a warning.
+    pub fn aspect_ratio(mut self, ratio: f64) -> Self {
+        self.aspect_ratio = validate_aspect_ratio(ratio);
+        self.aspect_cover = false;
+        self
+    }
 
-impl<'a, 'b> SizedBoxMut<'a, 'b> {
-    pub fn set_child(&mut self, child: impl Widget) {
-        self.widget.child = Some(WidgetPod::new(child).boxed());
-        self.ctx.children_changed();
-        self.ctx.request_layout();
+    /// Keep the container's width divided by its height equal to `ratio`, covering
+    /// its maximum size.
+    ///
+    /// Where [`aspect_ratio`](Self::aspect_ratio) takes the largest size which fits,
+    /// this takes the smallest size which covers the maximum size on both axes, so the
+    /// container is larger than its parent allows on one of them. On an axis without a
+    /// maximum, the minimum size is covered instead.
+    ///
+    /// The container isn't clipped, and overflows over its neighbors. To trim it, put
+    /// it in a [`SizedBox`] with [`clip_child`](Self::clip_child): since that box's
+    /// child has no maximum size, the container covers the box's own size.
+    pub fn aspect_ratio_cover(mut self, ratio: f64) -> Self {
+        self.aspect_ratio = validate_aspect_ratio(ratio);
+        self.aspect_cover = true;
+        self
     }
 
-    pub fn remove_child(&mut self) {
-        self.widget.child = None;
-        self.ctx.children_changed();
-        self.ctx.request_layout();
+    /// Keep a 16:9 aspect ratio, eg for video frames.
+    ///
+    /// See [`aspect_ratio`](Self::aspect_ratio) for details.
+    pub fn widescreen(self) -> Self {
+        self.aspect_ratio(16.0 / 9.0)
     }
 
-    /// Set container's width.
-    pub fn set_width(&mut self, width: f64) {
-        self.widget.width = Some(width);
-        self.ctx.request_layout();
+    /// Keep a 4:3 aspect ratio.
+    ///
+    /// See [`aspect_ratio`](Self::aspect_ratio) for details.
+    pub fn standard(self) -> Self {
+        self.aspect_ratio(4.0 / 3.0)
     }
 
-    /// Set container's height.
-    pub fn set_height(&mut self, height: f64) {
-        self.widget.height = Some(height);
-        self.ctx.request_layout();
+    /// Keep the container square, as large as the smaller of its axes allows.
+    ///
+    /// See [`aspect_ratio`](Self::aspect_ratio) for details.
+    pub fn square(self) -> Self {
+        self.aspect_ratio(1.0)
     }
 
-    /// Set container's width.
-    pub fn unset_width(&mut self) {
-        self.widget.width = None;
-        self.ctx.request_layout();
+    /// Builder-style method for adding empty space between the border and the child.
+    ///
+    /// The padding is included in the box's size, like the border: a box with a
+    /// fixed width gives its child that width minus the padding.
+    pub fn padding(mut self, padding: impl Into<Insets>) -> Self {
+        self.padding = padding.into();
+        self
     }
 
-    /// Set container's height.
-    pub fn unset_height(&mut self) {
-        self.widget.height = None;
-        self.ctx.request_layout();
+    /// Builder-style method for changing the area which receives mouse events.
+    ///
+    /// Negative values extend the clickable area past the box's edges, eg
+    /// `Insets::uniform(-8.0)` for a small button; positive values shrink it. This
+    /// only affects hit testing, not layout or painting. See
+    /// [`LayoutCtx::set_hit_test_inset`] for details.
+    pub fn hit_test_inset(mut self, inset: impl Into<Insets>) -> Self {
+        self.hit_test_inset = inset.into();
+        self
     }
 
-    /// Set the background for this widget.
+    /// Builder-style method for choosing whether the width and height include the
+    /// border and padding.
     ///
-    /// This can be passed anything which can be represented by a [`BackgroundBrush`];
-    /// notably, it can be any [`Color`], a [`Key<Color>`](Key) resolvable in the [`Env`],
-    /// any gradient, or a fully custom painter `FnMut`.
-    pub fn set_background(&mut self, brush: impl Into<BackgroundBrush>) {
-        self.widget.background = Some(brush.into());
-        self.ctx.request_paint();
+    /// The default, [`BoxSizing::BorderBox`], includes them: a box with a width of
+    /// `100.0` and a border of `10.0` is 100 wide, and its child 80. With
+    /// [`BoxSizing::ContentBox`], the child is 100 wide and the box 120. This also
+    /// applies to [`expand`](Self::expand), which then lets the child fill the
+    /// available space and the box overflow it.
+    pub fn box_sizing(mut self, box_sizing: BoxSizing) -> Self {
+        self.box_sizing = box_sizing;
+        self
     }
 
-    /// Clears background.
-    pub fn clear_background(&mut self) {
-        self.widget.background = None;
-        self.ctx.request_paint();
+    /// Builder-style method for giving the child loosened constraints.
+    ///
+    /// When `true`, the child's minimum size is always zero, regardless of the
+    /// constraints this container receives. The container still takes the size it
+    /// would otherwise have, and centers the child if it's smaller.
+    pub fn loosen_child(mut self, loosen: bool) -> Self {
+        self.loosen_child = loosen;
+        self
     }
 
-    /// Paint a border around the widget with a color and width.
+    /// Builder-style method for placing the child in a corner of the box, eg a badge.
     ///
-    /// Arguments can be either concrete values, or a [`Key`] of the respective
-    /// type.
-    pub fn set_border(
-        &mut self,
-        color: impl Into<KeyOrValue<Color>>,
-        width: impl Into<KeyOrValue<f64>>,
-    ) {
-        self.widget.border = Some(BorderStyle {
-            color: color.into(),
-            width: width.into(),
-        });
-        self.ctx.request_layout();
+    /// The child gets loosened constraints, like with
+    /// [`loosen_child`](Self::loosen_child), and is placed in `corner`, `margin` away
+    /// from the edges inside the border and padding. The box still takes the size it
+    /// would otherwise have, which without a set size includes the margin.
+    pub fn pin(mut self, corner: Corner, margin: impl Into<Insets>) -> Self {
+        self.pin = Some((corner, margin.into()));
+        self
     }
 
-    /// Clears border.
-    pub fn clear_border(&mut self) {
-        self.widget.border = None;
-        self.ctx.request_layout();
+    /// Builder-style method for letting the child overflow this box, clipped.
+    ///
+    /// When `true`, the child is laid out without a maximum size, and whatever
+    /// extends beyond this box is clipped when painting. The amount of overflow is
+    /// reported with [`LayoutCtx::set_content_overflow`], so a scrolling parent can
+    /// size its scrollbars.
+    ///
+    /// The child must pick a finite size under unbounded constraints, so children which
+    /// fill the space they're given, eg with `expand()` or flex children, don't work
+    /// here. If the child returns an infinite size anyway, this panics in debug builds;
+    /// in release builds an error is logged and the child is laid out again to fit
+    /// inside the box.
+    pub fn clip_child(mut self, clip: bool) -> Self {
+        self.clip_child = clip;
+        self
     }
 
-    /// Round off corners of this container by setting a corner radius
-    pub fn set_rounded(&mut self, radius: impl Into<KeyOrValue<RoundedRectRadii>>) {
-        self.widget.corner_radius = radius.into();
-        self.ctx.request_paint();
+    /// Builder-style method for scaling, rotating or otherwise transforming the child.
+    ///
+    /// `transform` is applied to the child when painting, in this box's coordinate
+    /// space, and mouse events are transformed back before they reach the child, so the
+    /// child sees them in its own coordinates. Layout isn't affected: the box keeps the
+    /// size of the untransformed child, and only receives mouse events over that area.
+    ///
+    /// Transforms which can't be inverted, eg a scale of `0.0`, are ignored with a
+    /// warning.
+    pub fn transform(mut self, transform: Affine) -> Self {
+        self.transform = validate_transform(transform);
+        self
     }
 
-    // TODO - Doc
-    pub fn child_mut(&mut self) -> Option<WidgetMut<'_, 'b, Box<dyn Widget>>> {
-        let child = self.widget.child.as_mut()?;
-        Some(self.ctx.get_mut(child))
+    /// Builder-style method for rotating the child around the center of this box.
+    ///
+    /// `radians` is clockwise, since the y axis points down. The rotation is applied
+    /// after any [`transform`](Self::transform), and like it, it only affects painting
+    /// and mouse events: the box keeps its axis-aligned size, and its background and
+    /// border aren't rotated. The rotated child is painted outside the box if it
+    /// doesn't fit, unless [`clip_child`](Self::clip_child) is set.
+    ///
+    /// Infinite and NaN angles are ignored with a warning.
+    pub fn rotation(mut self, radians: f64) -> Self {
+        self.rotation = validate_rotation(radians);
+        self
     }
-}
 
-impl SizedBox {
-    fn child_constraints(&self, bc: &BoxConstraints) -> BoxConstraints {
-        // if we don't have a width/height, we don't change that axis.
-        // if we have a width/height, we clamp it on that axis.
-        let (min_width, max_width) = match self.width {
-            Some(width) => {
-                let w = width.max(bc.min().width).min(bc.max().width);
-                (w, w)
-            }
-            None => (bc.min().width, bc.max().width),
-        };
+    /// Builder-style method for caching the child's rendering in an offscreen bitmap.
+    ///
+    /// This is meant for children which are expensive to paint but rarely change.
+    /// The child is painted once into a bitmap the size of the box, and the bitmap is
+    /// drawn in its place until the child or one of its descendants requests a paint
+    /// or a layout, or the box's size or scale factor changes.
+    ///
+    /// Anything the child paints outside of the box is cut off.
+    pub fn cache_rendering(mut self, cache: bool) -> Self {
+        self.cache_rendering = cache;
+        self
+    }
 
-        let (min_height, max_height) = match self.height {
-            Some(height) => {
-                let h = height.max(bc.min().height).min(bc.max().height);
-                (h, h)
-            }
-            None => (bc.min().height, bc.max().height),
-        };
+    /// Builder-style method for hiding the box when its child is empty.
+    ///
+    /// When `true` and the child's size has no area, eg a label with no text, the box
+    /// takes the smallest size its parent allows, usually zero, instead of its own size
+    /// or the size of its border and padding. Nothing is painted until the child lays
+    /// out to a non-empty size again. A box without a child isn't affected.
+    pub fn collapse_when_empty(mut self, collapse: bool) -> Self {
+        self.collapse_when_empty = collapse;
+        self
+    }
 
-        BoxConstraints::new(
-            Size::new(min_width, min_height),
-            Size::new(max_width, max_height),
-        )
+    /// Builder-style method for only showing the box while an [`Env`] flag is set.
+    ///
+    /// `key` is read on each layout pass. While it's `false`, the box takes the
+    /// smallest size its parent allows and paints nothing, and its child is stashed:
+    /// it keeps its state, but isn't laid out, painted or sent events. This can be used
+    /// for feature flags, eg from [`TestHarness::set_env_value`] in tests.
+    ///
+    /// If `key` isn't in the [`Env`], the box is shown, and a warning is logged once.
+    ///
+    /// [`TestHarness::set_env_value`]: crate::testing::TestHarness::set_env_value
+    pub fn visible_if(mut self, key: Key<bool>) -> Self {
+        self.visible_if = Some(key);
+        self
     }
 
-    #[allow(dead_code)]
-    pub(crate) fn width_and_height(&self) -> (Option<f64>, Option<f64>) {
-        (self.width, self.height)
+    /// Builder-style method for fading the child out to transparent along some edges.
+    ///
+    /// Over `length` pixels from each edge in `sides`, the child goes from fully visible
+    /// to fully transparent, so eg a scrolling list seems to slide out of view. The
+    /// background and border aren't faded. Where two faded edges meet, their fades
+    /// multiply.
+    ///
+    /// The child is painted into an offscreen bitmap, which is then masked, as with
+    /// [`cache_rendering`](Self::cache_rendering). A `length` that isn't positive and
+    /// finite is ignored with a warning.
+    pub fn edge_fade(mut self, sides: Sides, length: f64) -> Self {
+        self.edge_fade = EdgeFade::new(sides, length);
+        self
     }
-}
 
-impl Widget for SizedBox {
-    fn on_event(&mut self, ctx: &mut EventCtx, event: &Event, env: &Env) {
-        if let Some(ref mut child) = self.child {
-            child.on_event(ctx, event, env);
-        }
+    /// Builder-style method for keeping the child on whole logical pixels.
+    ///
+    /// When `true`, the child's constraints are rounded to whole pixels, and its origin
+    /// to the nearest pixel, so images and icons aren't blurred by a half-pixel offset,
+    /// eg from centering or fractional padding. The origin is rounded relative to this
+    /// box, which should itself be placed on whole pixels.
+    ///
+    /// This works in logical pixels; see [`hairline_border`](Self::hairline_border)
+    /// for borders aligned to device pixels.
+    pub fn snap_child_to_pixels(mut self, snap: bool) -> Self {
+        self.snap_child_to_pixels = snap;
+        self
     }
 
-    fn on_status_change(&mut self, _ctx: &mut LifeCycleCtx, _event: &StatusChange, _env: &Env) {}
+    /// Builder-style method for rounding this box's height up to a multiple of `step`.
+    ///
+    /// This lines up text in boxes placed one below the other with a baseline grid,
+    /// eg of 4px. The extra height goes below the child. The height is still limited
+    /// by the box's constraints, so it may end up off the grid. A `step` which isn't
+    /// positive is ignored, with a warning.
+    pub fn baseline_grid(mut self, step: f64) -> Self {
+        self.baseline_grid = validate_grid_step(step);
+        self
+    }
 
-    fn lifecycle(&mut self, ctx: &mut LifeCycleCtx, event: &LifeCycle, env: &Env) {
-        if let Some(ref mut child) = self.child {
-            child.lifecycle(ctx, event, env)
-        }
+    /// Builder-style method for setting the hit-testing order of this box.
+    ///
+    /// When this box overlaps its siblings, eg because it's offset in a stack, the
+    /// sibling with the highest z-index receives mouse events first. The default is
+    /// `0`. See [`LayoutCtx::set_z_index`] for details.
+    pub fn z_index(mut self, z_index: i32) -> Self {
+        self.z_index = z_index;
+        self
     }
 
-    fn layout(&mut self, ctx: &mut LayoutCtx, bc: &BoxConstraints, env: &Env) -> Size {
-        // Shrink constraints by border offset
-        let border_width = match &self.border {
-            Some(border) => border.width.resolve(env),
-            None => 0.0,
-        };
+    /// Builder-style method for measuring the child's natural width on each layout.
+    ///
+    /// When `true`, the child's [max intrinsic width](Widget::max_intrinsic_width) is
+    /// measured before it's laid out. The result is available through
+    /// [`intrinsic_width`](Self::intrinsic_width).
+    ///
+    /// Most widgets are laid out once more to be measured, so it's off by default.
+    pub fn measure_intrinsic_width(mut self, measure: bool) -> Self {
+        self.measure_intrinsic_width = measure;
+        self
+    }
 
-        let child_bc = self.child_constraints(bc);
-        let child_bc = child_bc.shrink((2.0 * border_width, 2.0 * border_width));
-        let origin = Point::new(border_width, border_width);
+    /// Builder-style method for setting explicit minimum and maximum sizes.
+    ///
+    /// The stored constraints are intersected with the ones this box receives from its
+    /// parent; when the two don't overlap, the parent's constraints win. Width and
+    /// height set with other methods are then applied within the intersection.
+    ///
+    /// This is a lower-level alternative to the other sizing methods, for cases they
+    /// can't express. If the minimum is larger than the maximum on either axis, a
+    /// warning is logged and the minimum is lowered to the maximum.
+    pub fn constrain(mut self, constraints: BoxConstraints) -> Self {
+        self.constraints = Some(validate_constraints(constraints));
+        self
+    }
 
-        let mut size;
-        match self.child.as_mut() {
-            Some(child) => {
-                size = child.layout(ctx, &child_bc, env);
-                ctx.place_child(child, origin, env);
-                size = Size::new(
-                    size.width + 2.0 * border_width,
-                    size.height + 2.0 * border_width,
-                );
-            }
-            None => size = bc.constrain((self.width.unwrap_or(0.0), self.height.unwrap_or(0.0))),
-        };
+    /// Builder-style method for fading the whole box, including its child.
+    ///
+    /// `1.0` is fully opaque, and the default; `0.0` is invisible, and skips painting
+    /// altogether. Other values paint the box, and what it paints outside of its
+    /// bounds, into an offscreen bitmap which is then blended with what's below. The
+    /// bitmap is kept until something in the box is painted or laid out again. Values
+    /// outside of `0.0..=1.0` are clamped, and NaN is ignored with a warning.
+    pub fn opacity(mut self, opacity: f64) -> Self {
+        self.opacity = validate_opacity(opacity);
+        self
+    }
 
-        // TODO - figure out paint insets
-        // TODO - figure out baseline offset
+    /// Builder-style method for making this box at least 44x44, with its child centered.
+    ///
+    /// 44 logical pixels is the smallest size commonly recommended for controls which
+    /// are tapped with a finger. A smaller child, eg a 20x20 icon, is centered in the
+    /// box, and the whole box receives mouse and touch events. Use
+    /// [`min_touch_target_size`](Self::min_touch_target_size) for a different size.
+    pub fn min_touch_target(self) -> Self {
+        self.min_touch_target_size(Size::new(MIN_TOUCH_TARGET, MIN_TOUCH_TARGET))
+    }
 
-        trace!("Computed size: {}", size);
+    /// Builder-style method for making this box at least `size`, with its child centered.
+    ///
+    /// The minimum is combined with constraints set with [`constrain`](Self::constrain)
+    /// and with the width and height, which are raised to `size` if they're smaller, as
+    /// far as the parent's constraints allow. This also sets
+    /// [`loosen_child`](Self::loosen_child). See [`min_touch_target`](Self::min_touch_target).
+    pub fn min_touch_target_size(mut self, size: Size) -> Self {
+        self.min_touch_target = Some(size);
+        self.loosen_child(true)
+    }
 
-        if size.width.is_infinite() {
-            warn!("SizedBox is returning an infinite width.");
+    /// Builder-style method for setting the background for this widget.
+    ///
+    /// This can be passed anything which can be represented by a [`BackgroundBrush`];
+    /// notably, it can be any [`Color`], a [`Key<Color>`](Key) resolvable in the [`Env`],
+    /// any gradient, or a fully custom painter `FnMut`.
+    pub fn background(mut self, brush: impl Into<BackgroundBrush>) -> Self {
+        self.background = Some(brush.into());
+        self
+    }
+
+    /// Builder-style method for painting the background over the child.
+    ///
+    /// When `true`, the child is painted first, and the background is painted on top
+    /// of it, eg to cover the content with a translucent overlay. The border is still
+    /// painted last.
+    pub fn background_over_child(mut self, over: bool) -> Self {
+        self.background_over_child = over;
+        self
+    }
+
+    /// Builder-style method for setting a background painted while the mouse is over the box.
+    ///
+    /// This replaces the regular background while the box is hot. Changes between the
+    /// two aren't animated, even with [`transition_background`](Self::transition_background).
+    pub fn hover_background(mut self, brush: impl Into<BackgroundBrush>) -> Self {
+        self.hover_background = Some(brush.into());
+        self
+    }
+
+    /// Builder-style method for choosing whether hover changes repaint the child.
+    ///
+    /// By default, when the hover background appears or disappears, only the area
+    /// around the child is repainted, and the child isn't painted at all: the part of
+    /// the background under an opaque child is never visible. If the child lets the
+    /// background show through, set this to `true` to repaint the whole layout rect,
+    /// child included.
+    ///
+    /// This has no effect if the background is painted over the child.
+    pub fn hover_repaints_child(mut self, repaint: bool) -> Self {
+        self.hover_repaints_child = repaint;
+        self
+    }
+
+    /// Builder-style method for painting a shadow inside the box, along its edges.
+    ///
+    /// This makes the box look pressed in. The shadow is cast by the box's edges
+    /// moved by `offset`, so an offset of `(2.0, 2.0)` darkens the top and left sides.
+    /// `blur` is the blur radius, as in [`RenderContext::blurred_rect`].
+    ///
+    /// The shadow is painted over the background and under the border and child, and is
+    /// clipped to the box: it doesn't change its paint insets.
+    pub fn inner_shadow(
+        mut self,
+        offset: impl Into<Vec2>,
+        blur: f64,
+        color: impl Into<KeyOrValue<Color>>,
+    ) -> Self {
+        self.inner_shadow = Some(InnerShadow {
+            offset: offset.into(),
+            blur,
+            color: color.into(),
+        });
+        self
+    }
+
+    /// Builder-style method for painting an animated dashed outline, or "marching ants".
+    ///
+    /// The outline is `width` wide, inside the box and over its child, eg to show a
+    /// selection or a crop area. `dashes` alternates the lengths of dashes and gaps, and
+    /// the dashes move along the outline by `speed` pixels per second; a negative speed
+    /// moves them the other way.
+    ///
+    /// On a [`focusable`](Self::focusable) box, the outline is a focus ring: it's only
+    /// shown, and only moves, while the box has focus. Like animated backgrounds, it
+    /// follows [`theme::ANIMATION_SCALE`], and it can be stopped with
+    /// [`SizedBoxMut::set_animation_paused`].
+    ///
+    /// Invalid widths and dash patterns are ignored with a warning, as is the outline.
+    pub fn marching_ants(
+        mut self,
+        color: impl Into<KeyOrValue<Color>>,
+        width: f64,
+        dashes: &[f64],
+        speed: f64,
+    ) -> Self {
+        self.marching_ants = MarchingAnts::new(color.into(), width, dashes, speed);
+        self
+    }
+
+    /// Builder-style method for giving this box a dashed border.
+    ///
+    /// The border is `width` wide and, like [`border`](Self::border), insets the child
+    /// and is painted under it. `dashes` alternates the lengths of dashes and gaps. If
+    /// the box also has a solid border, the child is inset by the wider of the two and
+    /// the dashes are painted over the solid border.
+    ///
+    /// The dashes don't move; use [`animate_dash`](Self::animate_dash) for that. Invalid
+    /// widths and dash patterns are ignored with a warning, as is the border.
+    pub fn dashed_border(
+        mut self,
+        color: impl Into<KeyOrValue<Color>>,
+        width: f64,
+        dashes: &[f64],
+    ) -> Self {
+        self.dashed_border = MarchingAnts::new(color.into(), width, dashes, 0.0);
+        self
+    }
+
+    /// Builder-style method for moving the dashed border's dashes by `speed` pixels per second.
+    ///
+    /// The box then requests animation frames while it's painted, and advances the dash
+    /// offset on each of them. It stops once the box is removed from the tree, since it
+    /// no longer gets frames to request new ones from. A box without a
+    /// [`dashed_border`](Self::dashed_border) is left unchanged with a warning, and a
+    /// non-finite speed counts as zero.
+    pub fn animate_dash(mut self, speed: f64) -> Self {
+        match &mut self.dashed_border {
+            Some(dashes) => dashes.speed = if speed.is_finite() { speed } else { 0.0 },
+            None => warn!("SizedBox::animate_dash needs a dashed border to animate"),
+        }
+        self
+    }
+
+    /// Builder-style method for making this box a focus target.
+    ///
+    /// A focusable box is part of the focus chain, so it can be reached with the
+    /// keyboard. While it's focused, its border is painted in
+    /// [`theme::PRIMARY_LIGHT`]; a box without a border doesn't show focus, unless
+    /// it has a [`focus_ring`](Self::focus_ring).
+    pub fn focusable(mut self, focusable: bool) -> Self {
+        self.focusable = focusable;
+        self
+    }
+
+    /// Builder-style method for painting a ring inside the edges of a focused box.
+    ///
+    /// Unlike the focused border color, the ring is only painted when focus came from
+    /// the keyboard, eg by pressing Tab, and not when the box was focused by a click.
+    /// See [`EventCtx::is_focus_visible`]. It's painted over the child, and only on
+    /// a [`focusable`](Self::focusable) box.
+    ///
+    /// A negative or NaN `width` is handled like [`border`](Self::border) widths.
+    pub fn focus_ring(mut self, color: impl Into<KeyOrValue<Color>>, width: f64) -> Self {
+        self.focus_ring = Some(BorderStyle::new(color.into(), width.into()));
+        self
+    }
+
+    /// Builder-style method for handling requests for a context menu.
+    ///
+    /// `handler` is called when the right mouse button is pressed over the box, or on
+    /// macOS when the left button is pressed with Ctrl held. It's given the position of
+    /// the mouse in the box's coordinate space; use [`EventCtx::to_window`] to convert it
+    /// to window coordinates, eg to show a menu there.
+    ///
+    /// The handler is called after the child has seen the event, and only if the child
+    /// didn't handle it. The event is then marked as handled. Other clicks are left alone.
+    pub fn on_context_menu(mut self, handler: impl FnMut(&mut EventCtx, Point) + 'static) -> Self {
+        self.context_menu_handler = Some(Box::new(handler));
+        self
+    }
+
+    /// Builder-style method for handling clicks on the box.
+    ///
+    /// `handler` is called when the left mouse button is pressed and then released over
+    /// the box, unless the child handled the press. The box is
+    /// [active](EventCtx::is_active) while the button is held. Clicks are ignored while
+    /// the box is disabled.
+    pub fn on_click(mut self, handler: impl FnMut(&mut EventCtx) + 'static) -> Self {
+        self.click_handler = Some(Box::new(handler));
+        self
+    }
+
+    /// Builder-style method for showing `cursor` while the mouse is over the box.
+    ///
+    /// A child which sets its own cursor takes precedence, eg a text box inside the
+    /// box still shows an I-beam. This also replaces the pointer cursor of a
+    /// [link](Self::as_link).
+    pub fn cursor(mut self, cursor: Cursor) -> Self {
+        self.cursor = Some(cursor);
+        self
+    }
+
+    /// Builder-style method for making this box behave like a link.
+    ///
+    /// The box shows a pointer cursor and is underlined in [`theme::TEXT_COLOR`] while
+    /// hovered. `on_activate` is called when the box is clicked, like with
+    /// [`on_click`](Self::on_click), which it replaces, or when Enter is pressed while
+    /// the box has focus. The box is made [`focusable`](Self::focusable).
+    pub fn as_link(mut self, on_activate: impl FnMut(&mut EventCtx) + 'static) -> Self {
+        self.click_handler = Some(Box::new(on_activate));
+        self.focusable = true;
+        self.is_link = true;
+        self
+    }
+
+    /// Builder-style method for giving this box the same width as other boxes.
+    ///
+    /// Every box built with a clone of `handle` measures its child's intrinsic width,
+    /// like [`measure_intrinsic_width`](Self::measure_intrinsic_width), and then gives
+    /// the child the widest of those widths, within the box's own constraints. See
+    /// [`SharedSize`].
+    pub fn shared_width(mut self, handle: SharedSize) -> Self {
+        self.shared_width = Some(SharedWidth::new(handle));
+        self
+    }
+
+    /// Builder-style method for handling scroll wheel events.
+    ///
+    /// `handler` is given the wheel delta, and returns whether it used the event, eg
+    /// to scroll. If it returns `false`, the event is left unhandled and bubbles up to
+    /// the box's ancestors; a scrolling box at the end of its content can do this to
+    /// let an enclosing scroll area take over.
+    ///
+    /// As with [`on_context_menu`](Self::on_context_menu), the handler is called after
+    /// the child has seen the event, and only if the child didn't handle it.
+    pub fn on_wheel(mut self, handler: impl FnMut(&mut EventCtx, Vec2) -> bool + 'static) -> Self {
+        self.wheel_handler = Some(Box::new(handler));
+        self
+    }
+
+    /// Builder-style method for overriding [`Env`] values for this box and its subtree.
+    ///
+    /// `f` is given a copy of the environment passed to the box, and the result is what
+    /// the box and all its descendants see in every pass, eg to use a darker palette or a
+    /// larger font in one part of the window. The box's own background and border are
+    /// resolved against the overridden environment too.
+    ///
+    /// The result is kept until the environment passed to the box changes, so `f` should
+    /// always make the same changes.
+    pub fn env_scope(mut self, f: impl Fn(&mut Env) + 'static) -> Self {
+        self.env_scope = Some(Box::new(f));
+        self
+    }
+
+    /// Builder-style method for naming this box for assistive technologies.
+    ///
+    /// This lets a decorative container, eg a sidebar, be announced by screen readers.
+    /// Use it with [`accessibility_role`](Self::accessibility_role) to make the box a
+    /// named landmark, like "Navigation". See [`Widget::accessibility`].
+    pub fn accessibility_label(mut self, label: impl Into<String>) -> Self {
+        self.accessibility_label = Some(label.into());
+        self
+    }
+
+    /// Builder-style method for setting what this box is for, for assistive technologies.
+    ///
+    /// See [`Role`].
+    pub fn accessibility_role(mut self, role: Role) -> Self {
+        self.accessibility_role = Some(role);
+        self
+    }
+
+    /// Builder-style method for animating changes of the background color.
+    ///
+    /// When the color this box paints changes, eg because it was disabled or given a new
+    /// background with [`SizedBoxMut::set_background`], the old color fades into the new
+    /// one over `duration` instead of being swapped instantly.
+    ///
+    /// Only solid colors (including their opacity) are interpolated. Gradients and
+    /// other brushes are swapped without a transition.
+    ///
+    /// The duration is multiplied by [`theme::ANIMATION_SCALE`], so setting that key
+    /// to `0.0` makes color changes instant. They are also instant when
+    /// [`theme::REDUCED_MOTION`] is set.
+    pub fn transition_background(mut self, duration: Duration) -> Self {
+        self.transition_duration = Some(duration);
+        self
+    }
+
+    /// Builder-style method for painting a border around the widget with a color and width.
+    ///
+    /// Arguments can be either concrete values, or a [`Key`] of the respective
+    /// type.
+    ///
+    /// A negative width is treated as zero, and a NaN width as no border at all;
+    /// both log a warning. Use [`try_border`](Self::try_border) to get an error instead.
+    pub fn border(
+        mut self,
+        color: impl Into<KeyOrValue<Color>>,
+        width: impl Into<KeyOrValue<f64>>,
+    ) -> Self {
+        self.border = Some(BorderStyle::new(color.into(), width.into()));
+        self
+    }
+
+    /// Builder-style method for painting a border around the widget, which fails if
+    /// the width is invalid.
+    ///
+    /// Returns an error if `width` is a negative or NaN value. Widths given as a [`Key`]
+    /// can't be checked in advance; invalid values are handled as in
+    /// [`border`](Self::border) when resolved.
+    pub fn try_border(
+        mut self,
+        color: impl Into<KeyOrValue<Color>>,
+        width: impl Into<KeyOrValue<f64>>,
+    ) -> Result<Self, BorderError> {
+        let width = width.into();
+        if let KeyOrValue::Concrete(width) = width {
+            check_border_width(width)?;
+        }
+        self.border = Some(BorderStyle::new(color.into(), width));
+        Ok(self)
+    }
+
+    /// Builder-style method for painting a border exactly one device pixel wide.
+    ///
+    /// Border widths are in logical pixels, so at a scale factor of 1.5 a 1.0 wide
+    /// border covers one and a half device pixels and looks blurry. A hairline border
+    /// is painted `1.0 / scale` logical pixels wide instead, which stays sharp at any
+    /// scale factor.
+    ///
+    /// Since the scale factor isn't known during layout, the child is inset by one
+    /// logical pixel, which is enough room for the hairline at scale factors of 1 and
+    /// above.
+    pub fn hairline_border(mut self, color: impl Into<KeyOrValue<Color>>) -> Self {
+        self.border = Some(BorderStyle::hairline(color.into()));
+        self
+    }
+
+    /// Builder-style method for styling the background and border with a [`StyleBundle`].
+    ///
+    /// The bundle replaces the [`background`](Self::background),
+    /// [`border`](Self::border) and [`rounded`](Self::rounded) settings, though the
+    /// [`hover_background`](Self::hover_background) is still painted while the box is hot.
+    /// Background color transitions don't apply to it.
+    ///
+    /// The bundle is resolved atomically: it's read from the env once per paint, and
+    /// both the background and the border are taken from that single value. When passed
+    /// a key, updating the key's value is enough to restyle the box consistently.
+    pub fn styled(mut self, style: impl Into<KeyOrValue<Arc<StyleBundle>>>) -> Self {
+        self.style = Some(style.into());
+        self
+    }
+
+    /// Builder-style method for changing the background, border and corner radius
+    /// depending on the box's state.
+    ///
+    /// When painting, the box picks the overrides for the states it's in, eg hovered or
+    /// disabled, with the precedence described in [`StateStyles`]. They take precedence
+    /// over the box's own background, border and corner radius, including the
+    /// [hover background](Self::hover_background), the [style bundle](Self::styled) and
+    /// the usual disabled and focused colors. The corner radius only changes what's
+    /// painted, not how the child is clipped.
+    pub fn state_styles(mut self, styles: StateStyles) -> Self {
+        self.state_styles = Some(styles);
+        self
+    }
+
+    /// Builder style method for rounding off corners of this container by setting a corner radius
+    pub fn rounded(mut self, radius: impl Into<KeyOrValue<RoundedRectRadii>>) -> Self {
+        self.corner_radius = radius.into();
+        self
+    }
+
+    /// Builder-style method for rounding off each corner with its own radius.
+    ///
+    /// Radii are given clockwise from the top left, like CSS `border-radius`:
+    /// `[top_left, top_right, bottom_right, bottom_left]`. Negative or NaN radii are
+    /// replaced with zero, and a warning is logged.
+    pub fn rounded_each(mut self, radii: [f64; 4]) -> Self {
+        self.corner_radius = corner_radii(radii).into();
+        self
+    }
+
+    /// Builder-style method for turning off antialiasing of the box's edges.
+    ///
+    /// When `false`, the rounded corners of the background and border, and the clip of
+    /// the child, are drawn on whole pixels, with hard steps instead of smooth edges.
+    /// This suits pixel-art or crisp-edged designs, as long as the box itself sits on
+    /// whole pixels. The default is `true`.
+    ///
+    /// What the child and custom brushes paint isn't affected.
+    pub fn antialias(mut self, antialias: bool) -> Self {
+        self.antialias = antialias;
+        self
+    }
+
+    /// Check the box's settings, eg when they come from untrusted input.
+    ///
+    /// The builder methods accept any value: invalid ones are replaced with a valid
+    /// default and a warning is logged, or, for sizes, cause problems during layout. This
+    /// returns an error for the first invalid setting instead:
+    ///
+    /// - a negative or NaN width or height (an infinite one is valid, see
+    ///   [`expand`](Self::expand)),
+    /// - a negative or NaN border width,
+    /// - a negative or NaN corner radius.
+    ///
+    /// Only the current settings are checked, so an invalid value which was replaced by
+    /// a later builder call isn't reported. Values read from the [`Env`] through a
+    /// [`Key`] aren't checked, since they're only known during layout.
+    pub fn try_build(self) -> Result<Self, BoxConfigError> {
+        if let Some(width) = self.width.filter(|width| is_invalid_length(*width)) {
+            return Err(BoxConfigError::InvalidWidth(width));
+        }
+        if let Some(height) = self.height.filter(|height| is_invalid_length(*height)) {
+            return Err(BoxConfigError::InvalidHeight(height));
+        }
+        if let Some(KeyOrValue::Concrete(width)) = self.border.as_ref().map(|border| &border.width)
+        {
+            check_border_width(*width).map_err(BoxConfigError::Border)?;
+        }
+        if let KeyOrValue::Concrete(radii) = &self.corner_radius {
+            let radii = [
+                radii.top_left,
+                radii.top_right,
+                radii.bottom_right,
+                radii.bottom_left,
+            ];
+            if let Some(radius) = radii.into_iter().find(|radius| is_invalid_length(*radius)) {
+                return Err(BoxConfigError::InvalidCornerRadius(radius));
+            }
+        }
+        Ok(self)
+    }
+
+    // TODO - child()
+
+    /// The id of the child, or `None` if the box has no child.
+    ///
+    /// This is the id given to [`new_with_id`](Self::new_with_id), or the one generated
+    /// for the child otherwise, eg to send it commands or focus it. For a
+    /// [`responsive`](Self::responsive) box, it's the id of the wide child.
+    pub fn child_id(&self) -> Option<WidgetId> {
+        self.child.as_ref().map(|child| child.id())
+    }
+
+    /// The width this box would like to have, given unlimited horizontal space.
+    ///
+    /// This is the explicit width if one was set, and otherwise the natural width of
+    /// the child plus the border and padding. It can be used by a parent to give a column of boxes
+    /// the width of the widest one, eg in a table.
+    ///
+    /// The child's width is measured during layout, and only if
+    /// [`measure_intrinsic_width`](Self::measure_intrinsic_width) is enabled; otherwise
+    /// it counts as zero.
+    pub fn intrinsic_width(&self, env: &Env) -> f64 {
+        if let Some(width) = self.width {
+            return width;
+        }
+        let style = self.resolved_style(env);
+        let border_width = self.border_inset(style.as_deref(), env);
+        self.child_intrinsic_width + 2.0 * border_width + self.padding.x_value()
+    }
+
+    /// Where the child was placed by the last layout pass, relative to this box.
+    ///
+    /// This includes the border, the padding and the centering of a
+    /// [loosened](Self::loosen_child) child. It is `None` if the box has no child or
+    /// hasn't been laid out yet.
+    pub fn child_origin(&self) -> Option<Point> {
+        self.child_origin
+    }
+
+    /// Whether the last paint pass drew anything.
+    ///
+    /// This is `true` if the box painted a background, a border or its child, and
+    /// `false` before the box is first painted. A painted child counts even if it
+    /// didn't draw anything itself.
+    pub fn did_paint_content(&self) -> bool {
+        self.painted_content
+    }
+
+    /// Whether the background covers the whole box with opaque colors.
+    ///
+    /// A compositor can use this to skip painting what's behind the box. This looks at
+    /// the regular background, not the hover one, and is `false` if the box has rounded
+    /// corners, since the corners are transparent, or [state styles](Self::state_styles).
+    /// An [edge fade](Self::edge_fade) doesn't change it, since only the child is
+    /// faded, but an [opacity](Self::opacity) below `1.0` does. It's also `false`
+    /// while the box is [collapsed](Self::collapse_when_empty) or hidden by
+    /// [`visible_if`](Self::visible_if), since it paints nothing.
+    /// See [`BackgroundBrush::is_opaque`].
+    pub fn is_opaque(&self, env: &Env) -> bool {
+        if self.opacity < 1.0 || self.collapsed || self.hidden {
+            return false;
+        }
+        // The look depends on the state, which isn't known here.
+        if self.state_styles.is_some() {
+            return false;
+        }
+        let style = self.resolved_style(env);
+        let radii = self.resolved_corner_radius(style.as_deref(), env);
+        let is_rounded = [
+            radii.top_left,
+            radii.top_right,
+            radii.bottom_right,
+            radii.bottom_left,
+        ]
+        .iter()
+        .any(|radius| *radius > 0.0);
+        if is_rounded {
+            return false;
+        }
+        match (style, &self.background) {
+            (Some(style), _) => match &style.brush {
+                Some(brush) => brush.is_opaque(),
+                None => style.background.as_rgba8().3 == u8::MAX,
+            },
+            (None, Some(background)) => background.is_opaque(env),
+            (None, None) => false,
+        }
+    }
+
+    /// The [`Env`] keys this box is configured with that are missing from `env`.
+    ///
+    /// This covers the keys given to builder methods, eg for the background, border and
+    /// corner radius, and reports all of them at once; painting the box would panic on
+    /// the first one. It also covers the theme keys the box uses implicitly, eg
+    /// [`theme::PRIMARY_LIGHT`] for the border of a focused box or [`theme::TEXT_COLOR`]
+    /// for the underline of a link. See [`Env::validate_keys`].
+    pub fn missing_keys(&self, env: &Env) -> Vec<MissingKeyError> {
+        env.validate_keys(&self.env_keys())
+    }
+
+    /// The raw [`Env`] keys this box resolves, without duplicates.
+    ///
+    /// See [`missing_keys`](Self::missing_keys) for what this covers.
+    fn env_keys(&self) -> Vec<&'static str> {
+        let mut keys = Vec::new();
+        for brush in [&self.background, &self.hover_background]
+            .into_iter()
+            .flatten()
+        {
+            keys.extend(brush.raw_keys());
+        }
+        for border in [&self.border, &self.focus_ring].into_iter().flatten() {
+            keys.extend(border.width.raw_key());
+            keys.extend(border.color.raw_key());
+        }
+        keys.extend(
+            self.inner_shadow
+                .as_ref()
+                .and_then(|shadow| shadow.color.raw_key()),
+        );
+        for dashes in [&self.marching_ants, &self.dashed_border]
+            .into_iter()
+            .flatten()
+        {
+            keys.extend(dashes.color.raw_key());
+        }
+        keys.extend(self.style.as_ref().and_then(KeyOrValue::raw_key));
+        keys.extend(self.corner_radius.raw_key());
+        keys.extend(
+            self.visible_if
+                .clone()
+                .and_then(|key| KeyOrValue::from(key).raw_key()),
+        );
+        if let Some(styles) = &self.state_styles {
+            for style in styles.by_precedence() {
+                keys.extend(style.raw_keys());
+            }
+        }
+
+        // Keys painting reads without them being passed to a builder method.
+        let has_color_background = [&self.background, &self.hover_background]
+            .into_iter()
+            .any(|brush| matches!(brush, Some(BackgroundBrush::Color(_))));
+        if has_color_background || self.style.is_some() {
+            keys.extend(KeyOrValue::from(theme::DISABLED_BACKGROUND_COLOR).raw_key());
+        }
+        let has_border = self.border.is_some() || self.style.is_some();
+        if has_border || self.dashed_border.is_some() {
+            keys.extend(KeyOrValue::from(theme::DISABLED_BORDER_COLOR).raw_key());
+        }
+        if has_border && self.focusable {
+            keys.extend(KeyOrValue::from(theme::PRIMARY_LIGHT).raw_key());
+        }
+        if self.is_link {
+            keys.extend(KeyOrValue::from(theme::TEXT_COLOR).raw_key());
+        }
+
+        let mut unique_keys = Vec::new();
+        for key in keys {
+            if !unique_keys.contains(&key) {
+                unique_keys.push(key);
+            }
+        }
+        unique_keys
+    }
+
+    /// A hash of this box's style, with keys resolved in `env`.
+    ///
+    /// This covers the size settings, background, border, shadow and corner radius, and can be
+    /// used to cheaply check whether the box needs to be rebuilt, eg in a memoization
+    /// layer. Two boxes with the same style have the same hash.
+    ///
+    /// The child doesn't contribute to the hash. `PainterFn` and `Custom` backgrounds
+    /// can't be inspected, so all brushes of the same kind hash the same.
+    ///
+    /// The hash uses a fixed hash function, so it's the same from one run to the next and
+    /// can be stored in snapshots, but it may change between versions of Masonry.
+    pub fn config_hash(&self, env: &Env) -> u64 {
+        fn hash_f64(value: f64, state: &mut impl Hasher) {
+            value.to_bits().hash(state);
+        }
+
+        let mut state = FnvHasher::default();
+        self.width.map(f64::to_bits).hash(&mut state);
+        self.height.map(f64::to_bits).hash(&mut state);
+        self.fill_width.hash(&mut state);
+        self.fill_height.hash(&mut state);
+        self.aspect_ratio.map(f64::to_bits).hash(&mut state);
+        self.aspect_cover.hash(&mut state);
+        self.loosen_child.hash(&mut state);
+        self.clip_child.hash(&mut state);
+        self.snap_child_to_pixels.hash(&mut state);
+        self.baseline_grid.map(f64::to_bits).hash(&mut state);
+        self.box_sizing.hash(&mut state);
+        let padding = self.padding;
+        for value in [padding.x0, padding.y0, padding.x1, padding.y1] {
+            hash_f64(value, &mut state);
+        }
+        match &self.constraints {
+            Some(bc) => {
+                for value in [
+                    bc.min().width,
+                    bc.min().height,
+                    bc.max().width,
+                    bc.max().height,
+                ] {
+                    hash_f64(value, &mut state);
+                }
+            }
+            None => 0u8.hash(&mut state),
+        }
+        match self.min_touch_target {
+            Some(size) => {
+                hash_f64(size.width, &mut state);
+                hash_f64(size.height, &mut state);
+            }
+            None => 0u8.hash(&mut state),
+        }
+        let style = self.resolved_style(env);
+        match (&style, &self.background) {
+            (Some(style), _) => {
+                style.background.as_rgba_u32().hash(&mut state);
+                match &style.brush {
+                    Some(brush) => brush.hash(env, &mut state),
+                    None => 0u8.hash(&mut state),
+                }
+            }
+            (None, Some(background)) => background.hash_resolved(env, &mut state),
+            (None, None) => 0u8.hash(&mut state),
+        }
+        self.background_over_child.hash(&mut state);
+        self.antialias.hash(&mut state);
+        hash_f64(self.opacity, &mut state);
+        match self.resolved_border(style.as_deref(), env) {
+            Some((width, color)) => {
+                hash_f64(width, &mut state);
+                color.as_rgba_u32().hash(&mut state);
+                self.has_hairline_border().hash(&mut state);
+            }
+            None => 0u8.hash(&mut state),
+        }
+        match &self.dashed_border {
+            Some(dashes) => {
+                hash_f64(dashes.width, &mut state);
+                for &dash in dashes.dashes.iter() {
+                    hash_f64(dash, &mut state);
+                }
+                dashes.color.resolve(env).as_rgba_u32().hash(&mut state);
+            }
+            None => 0u8.hash(&mut state),
+        }
+        match &self.inner_shadow {
+            Some(shadow) => {
+                for value in [shadow.offset.x, shadow.offset.y, shadow.blur] {
+                    hash_f64(value, &mut state);
+                }
+                shadow.color.resolve(env).as_rgba_u32().hash(&mut state);
+            }
+            None => 0u8.hash(&mut state),
+        }
+        let radii = self.resolved_corner_radius(style.as_deref(), env);
+        for radius in [
+            radii.top_left,
+            radii.top_right,
+            radii.bottom_right,
+            radii.bottom_left,
+        ] {
+            hash_f64(radius, &mut state);
+        }
+        if let Some(styles) = &self.state_styles {
+            for style in styles.by_precedence() {
+                let background = style.background.as_ref().map(|color| color.resolve(env));
+                background.map(|color| color.as_rgba_u32()).hash(&mut state);
+                match &style.border {
+                    Some((color, width)) => {
+                        color.resolve(env).as_rgba_u32().hash(&mut state);
+                        hash_f64(width.resolve(env), &mut state);
+                    }
+                    None => 0u8.hash(&mut state),
+                }
+                match style
+                    .corner_radius
+                    .as_ref()
+                    .map(|radius| radius.resolve(env))
+                {
+                    Some(radii) => {
+                        for radius in [
+                            radii.top_left,
+                            radii.top_right,
+                            radii.bottom_right,
+                            radii.bottom_left,
+                        ] {
+                            hash_f64(radius, &mut state);
+                        }
+                    }
+                    None => 0u8.hash(&mut state),
+                }
+            }
+        }
+        state.finish()
+    }
+
+    /// Map a point from this box's coordinate space to its child's.
+    ///
+    /// This accounts for the border and for the offset of a centered child. Returns
+    /// `None` if there is no child, or if the point is outside of the child, eg on
+    /// the border.
+    ///
+    /// The result is only meaningful after the box has been laid out.
+    pub fn to_child_space(&self, point: Point) -> Option<Point> {
+        let child_rect = self.active_child()?.layout_rect();
+        if child_rect.contains(point) {
+            Some(point - child_rect.origin().to_vec2())
+        } else {
+            None
+        }
+    }
+}
+
+impl<'a, 'b> SizedBoxMut<'a, 'b> {
+    pub fn set_child(&mut self, child: impl Widget) {
+        self.widget.child = Some(WidgetPod::new(child).boxed());
+        self.ctx.children_changed();
+        self.ctx.request_layout();
+    }
+
+    /// Replace the child with an existing pod, which keeps its id.
+    ///
+    /// See [`SizedBox::from_pod`].
+    pub fn set_child_pod(&mut self, child: WidgetPod<Box<dyn Widget>>) {
+        self.widget.child = Some(child);
+        self.ctx.children_changed();
+        self.ctx.request_layout();
+    }
+
+    pub fn remove_child(&mut self) {
+        self.widget.child = None;
+        self.ctx.children_changed();
+        self.ctx.request_layout();
+    }
+
+    /// Set the cursor shown while the mouse is over the box.
+    ///
+    /// See [`SizedBox::cursor`] for details.
+    pub fn set_cursor(&mut self, cursor: Cursor) {
+        self.ctx.set_cursor(&cursor);
+        self.widget.cursor = Some(cursor);
+    }
+
+    /// Go back to the cursor of the parent, or the pointer cursor of a link.
+    pub fn clear_cursor(&mut self) {
+        self.widget.cursor = None;
+        match self.widget.hover_cursor() {
+            Some(cursor) => self.ctx.set_cursor(&cursor),
+            None => self.ctx.clear_cursor(),
+        }
+    }
+
+    /// The id of the child, or `None` if the box has no child.
+    ///
+    /// See [`SizedBox::child_id`].
+    pub fn child_id(&self) -> Option<WidgetId> {
+        self.widget.child_id()
+    }
+
+    /// Set container's width.
+    pub fn set_width(&mut self, width: f64) {
+        self.widget.width = Some(width);
+        self.widget.fill_width = false;
+        self.ctx.request_layout();
+    }
+
+    /// Set container's height.
+    pub fn set_height(&mut self, height: f64) {
+        self.widget.height = Some(height);
+        self.widget.fill_height = false;
+        self.ctx.request_layout();
+    }
+
+    /// Set container's width.
+    pub fn unset_width(&mut self) {
+        self.widget.width = None;
+        self.widget.fill_width = false;
+        self.ctx.request_layout();
+    }
+
+    /// Set container's height.
+    pub fn unset_height(&mut self) {
+        self.widget.height = None;
+        self.widget.fill_height = false;
+        self.ctx.request_layout();
+    }
+
+    /// Set the ratio of the container's width to its height.
+    ///
+    /// See [`SizedBox::aspect_ratio`] for details.
+    pub fn set_aspect_ratio(&mut self, ratio: f64) {
+        self.widget.aspect_ratio = validate_aspect_ratio(ratio);
+        self.widget.aspect_cover = false;
+        self.ctx.request_layout();
+    }
+
+    /// Set the ratio of the container's width to its height, covering its maximum size.
+    ///
+    /// See [`SizedBox::aspect_ratio_cover`] for details.
+    pub fn set_aspect_ratio_cover(&mut self, ratio: f64) {
+        self.widget.aspect_ratio = validate_aspect_ratio(ratio);
+        self.widget.aspect_cover = true;
+        self.ctx.request_layout();
+    }
+
+    /// Stop keeping the container's aspect ratio.
+    pub fn unset_aspect_ratio(&mut self) {
+        self.widget.aspect_ratio = None;
+        self.ctx.request_layout();
+    }
+
+    /// Set the empty space between the border and the child.
+    ///
+    /// See [`SizedBox::padding`] for details.
+    pub fn set_padding(&mut self, padding: impl Into<Insets>) {
+        self.widget.padding = padding.into();
+        self.ctx.request_layout();
+    }
+
+    /// Change the area which receives mouse events.
+    ///
+    /// See [`SizedBox::hit_test_inset`] for details.
+    pub fn set_hit_test_inset(&mut self, inset: impl Into<Insets>) {
+        self.widget.hit_test_inset = inset.into();
+        self.ctx.request_layout();
+    }
+
+    /// Set whether the width and height include the border and padding.
+    ///
+    /// See [`SizedBox::box_sizing`] for details.
+    pub fn set_box_sizing(&mut self, box_sizing: BoxSizing) {
+        self.widget.box_sizing = box_sizing;
+        self.ctx.request_layout();
+    }
+
+    /// Set whether the child is given loosened constraints.
+    ///
+    /// See [`SizedBox::loosen_child`] for details.
+    pub fn set_loosen_child(&mut self, loosen: bool) {
+        self.widget.loosen_child = loosen;
+        self.ctx.request_layout();
+    }
+
+    /// Place the child in a corner of the box.
+    ///
+    /// See [`SizedBox::pin`] for details.
+    pub fn set_pin(&mut self, corner: Corner, margin: impl Into<Insets>) {
+        self.widget.pin = Some((corner, margin.into()));
+        self.ctx.request_layout();
+    }
+
+    /// Stop placing the child in a corner.
+    pub fn clear_pin(&mut self) {
+        self.widget.pin = None;
+        self.ctx.request_layout();
+    }
+
+    /// Set the transform applied to the child, or `None` to remove it.
+    ///
+    /// See [`SizedBox::transform`] for details.
+    pub fn set_transform(&mut self, transform: Option<Affine>) {
+        self.widget.transform = transform.and_then(validate_transform);
+        self.ctx.request_layout();
+    }
+
+    /// Set the rotation of the child around the center of this box, in radians.
+    ///
+    /// See [`SizedBox::rotation`] for details.
+    pub fn set_rotation(&mut self, radians: f64) {
+        self.widget.rotation = validate_rotation(radians);
+        self.ctx.request_layout();
+    }
+
+    /// Set whether the child can overflow this box, clipped.
+    ///
+    /// See [`SizedBox::clip_child`] for details.
+    pub fn set_clip_child(&mut self, clip: bool) {
+        self.widget.clip_child = clip;
+        self.ctx.request_layout();
+    }
+
+    /// Set whether the child's rendering is cached in an offscreen bitmap.
+    ///
+    /// See [`SizedBox::cache_rendering`] for details.
+    pub fn set_cache_rendering(&mut self, cache: bool) {
+        self.widget.cache_rendering = cache;
+        self.widget.render_cache = None;
+        self.ctx.request_paint();
+    }
+
+    /// Set whether the box is hidden when its child is empty.
+    ///
+    /// See [`SizedBox::collapse_when_empty`] for details.
+    pub fn set_collapse_when_empty(&mut self, collapse: bool) {
+        self.widget.collapse_when_empty = collapse;
+        self.ctx.request_layout();
+    }
+
+    /// Only show the box while `key` is `true` in the [`Env`].
+    ///
+    /// See [`SizedBox::visible_if`] for details.
+    pub fn set_visible_if(&mut self, key: Key<bool>) {
+        self.widget.visible_if = Some(key);
+        self.widget.warned_missing_visible_if = false;
+        self.ctx.request_layout();
+    }
+
+    /// Always show the box, whatever the [`Env`] flag given to `visible_if`.
+    pub fn clear_visible_if(&mut self) {
+        self.widget.visible_if = None;
+        self.ctx.request_layout();
+    }
+
+    /// Set the edges along which the child fades out.
+    ///
+    /// See [`SizedBox::edge_fade`] for details.
+    pub fn set_edge_fade(&mut self, sides: Sides, length: f64) {
+        self.widget.edge_fade = EdgeFade::new(sides, length);
+        self.widget.render_cache = None;
+        self.ctx.request_paint();
+    }
+
+    /// Stop fading out the child.
+    pub fn clear_edge_fade(&mut self) {
+        self.widget.edge_fade = None;
+        self.widget.render_cache = None;
+        self.ctx.request_paint();
+    }
+
+    /// Set how opaque the whole box is, including its child.
+    ///
+    /// See [`SizedBox::opacity`] for details.
+    pub fn set_opacity(&mut self, opacity: f64) {
+        self.widget.opacity = validate_opacity(opacity);
+        self.widget.opacity_cache = None;
+        self.ctx.request_paint();
+    }
+
+    /// Throw away everything this box has cached, and lay it out and paint it again.
+    ///
+    /// This is for changes the box can't see, eg resources reloaded from disk which
+    /// a cached child painted from. At the moment, the caches are the child's bitmap
+    /// from [`SizedBox::cache_rendering`] and the faded box from [`SizedBox::opacity`].
+    pub fn invalidate(&mut self) {
+        self.widget.render_cache = None;
+        self.widget.opacity_cache = None;
+        self.ctx.request_layout();
+        self.ctx.request_paint();
+    }
+
+    /// Set whether the child is kept on whole logical pixels.
+    ///
+    /// See [`SizedBox::snap_child_to_pixels`] for details.
+    pub fn set_snap_child_to_pixels(&mut self, snap: bool) {
+        self.widget.snap_child_to_pixels = snap;
+        self.ctx.request_layout();
+    }
+
+    /// Set the hit-testing order of this box.
+    ///
+    /// See [`SizedBox::z_index`] for details.
+    pub fn set_z_index(&mut self, z_index: i32) {
+        self.widget.z_index = z_index;
+        self.ctx.request_layout();
+    }
+
+    /// Set whether the child's natural width is measured on each layout.
+    ///
+    /// See [`SizedBox::measure_intrinsic_width`].
+    pub fn set_measure_intrinsic_width(&mut self, measure: bool) {
+        self.widget.measure_intrinsic_width = measure;
+        self.ctx.request_layout();
+    }
+
+    /// Set explicit minimum and maximum sizes.
+    ///
+    /// See [`SizedBox::constrain`] for details.
+    pub fn set_constraints(&mut self, constraints: BoxConstraints) {
+        self.widget.constraints = Some(validate_constraints(constraints));
+        self.ctx.request_layout();
+    }
+
+    /// Remove the explicit minimum and maximum sizes.
+    pub fn unset_constraints(&mut self) {
+        self.widget.constraints = None;
+        self.ctx.request_layout();
+    }
+
+    /// Set the grid this box's height is rounded up to.
+    ///
+    /// See [`SizedBox::baseline_grid`] for details.
+    pub fn set_baseline_grid(&mut self, step: f64) {
+        self.widget.baseline_grid = validate_grid_step(step);
+        self.ctx.request_layout();
+    }
+
+    /// Stop rounding this box's height to a grid.
+    pub fn unset_baseline_grid(&mut self) {
+        self.widget.baseline_grid = None;
+        self.ctx.request_layout();
+    }
+
+    /// Set the background for this widget.
+    ///
+    /// This can be passed anything which can be represented by a [`BackgroundBrush`];
+    /// notably, it can be any [`Color`], a [`Key<Color>`](Key) resolvable in the [`Env`],
+    /// any gradient, or a fully custom painter `FnMut`.
+    ///
+    /// If the new brush is equal to the current one, this does nothing, so it's cheap to
+    /// call unconditionally. Painter functions and custom backgrounds are never equal to
+    /// anything, so they always trigger a repaint.
+    pub fn set_background(&mut self, brush: impl Into<BackgroundBrush>) {
+        let brush = brush.into();
+        if self.widget.background.as_ref() == Some(&brush) {
+            return;
+        }
+        if brush.is_animated() {
+            self.ctx.request_anim_frame();
+        }
+        let is_color = matches!(brush, BackgroundBrush::Color(_));
+        self.widget.background = Some(brush);
+        if is_color {
+            if self.widget.start_color_transition() {
+                self.ctx.request_anim_frame();
+            }
+        } else {
+            self.widget.color_transition = None;
+        }
+        self.ctx.request_paint();
+    }
+
+    /// Pause or resume the box's animations: an animated background, eg a [`Shimmer`],
+    /// and [marching ants](SizedBox::marching_ants).
+    ///
+    /// A paused box stays as it is and doesn't request animation frames. It picks up
+    /// where it was when resumed.
+    pub fn set_animation_paused(&mut self, paused: bool) {
+        if self.widget.animation_paused == paused {
+            return;
+        }
+        self.widget.animation_paused = paused;
+        if !paused && self.widget.has_animation(self.ctx.is_focused()) {
+            self.widget.animation_painted = true;
+            self.ctx.request_anim_frame();
+        }
+    }
+
+    /// Set whether the background is painted over the child.
+    ///
+    /// See [`SizedBox::background_over_child`] for details.
+    pub fn set_background_over_child(&mut self, over: bool) {
+        self.widget.background_over_child = over;
+        self.ctx.request_paint();
+    }
+
+    /// Set the background painted while the mouse is over the box.
+    ///
+    /// See [`SizedBox::hover_background`] for details.
+    pub fn set_hover_background(&mut self, brush: impl Into<BackgroundBrush>) {
+        self.widget.hover_background = Some(brush.into());
+        self.ctx.request_paint();
+    }
+
+    /// Clears the hover background.
+    pub fn clear_hover_background(&mut self) {
+        self.widget.hover_background = None;
+        self.ctx.request_paint();
+    }
+
+    /// Set whether hover changes repaint the child.
+    ///
+    /// See [`SizedBox::hover_repaints_child`] for details.
+    pub fn set_hover_repaints_child(&mut self, repaint: bool) {
+        self.widget.hover_repaints_child = repaint;
+    }
+
+    /// Set the shadow painted inside the box.
+    ///
+    /// See [`SizedBox::inner_shadow`] for details.
+    pub fn set_inner_shadow(
+        &mut self,
+        offset: impl Into<Vec2>,
+        blur: f64,
+        color: impl Into<KeyOrValue<Color>>,
+    ) {
+        self.widget.inner_shadow = Some(InnerShadow {
+            offset: offset.into(),
+            blur,
+            color: color.into(),
+        });
+        self.ctx.request_paint();
+    }
+
+    /// Remove the shadow painted inside the box.
+    pub fn clear_inner_shadow(&mut self) {
+        self.widget.inner_shadow = None;
+        self.ctx.request_paint();
+    }
+
+    /// Set the animated dashed outline painted over the box.
+    ///
+    /// See [`SizedBox::marching_ants`] for details.
+    pub fn set_marching_ants(
+        &mut self,
+        color: impl Into<KeyOrValue<Color>>,
+        width: f64,
+        dashes: &[f64],
+        speed: f64,
+    ) {
+        self.widget.marching_ants = MarchingAnts::new(color.into(), width, dashes, speed);
+        if self.widget.has_animation(self.ctx.is_focused()) && !self.widget.animation_paused {
+            self.widget.animation_painted = true;
+            self.ctx.request_anim_frame();
+        }
+        self.ctx.request_paint();
+    }
+
+    /// Remove the animated dashed outline.
+    pub fn clear_marching_ants(&mut self) {
+        self.widget.marching_ants = None;
+        self.ctx.request_paint();
+    }
+
+    /// Set the dashed border, whose dashes don't move.
+    ///
+    /// See [`SizedBox::dashed_border`] for details.
+    pub fn set_dashed_border(
+        &mut self,
+        color: impl Into<KeyOrValue<Color>>,
+        width: f64,
+        dashes: &[f64],
+    ) {
+        self.widget.dashed_border = MarchingAnts::new(color.into(), width, dashes, 0.0);
+        self.ctx.request_layout();
+    }
+
+    /// Remove the dashed border.
+    pub fn clear_dashed_border(&mut self) {
+        self.widget.dashed_border = None;
+        self.ctx.request_layout();
+    }
+
+    /// Set the ring painted when the box has keyboard focus.
+    ///
+    /// See [`SizedBox::focus_ring`] for details.
+    pub fn set_focus_ring(&mut self, color: impl Into<KeyOrValue<Color>>, width: f64) {
+        self.widget.focus_ring = Some(BorderStyle::new(color.into(), width.into()));
+        self.ctx.request_paint();
+    }
+
+    /// Remove the focus ring.
+    pub fn clear_focus_ring(&mut self) {
+        self.widget.focus_ring = None;
+        self.ctx.request_paint();
+    }
+
+    /// Set whether this box is a focus target.
+    ///
+    /// See [`SizedBox::focusable`] for details.
+    pub fn set_focusable(&mut self, focusable: bool) {
+        self.widget.focusable = focusable;
+        self.ctx.request_focus_chain_update();
+        self.ctx.request_paint();
+    }
+
+    /// Set the handler for context menu requests.
+    ///
+    /// See [`SizedBox::on_context_menu`] for details.
+    pub fn set_on_context_menu(&mut self, handler: impl FnMut(&mut EventCtx, Point) + 'static) {
+        self.widget.context_menu_handler = Some(Box::new(handler));
+    }
+
+    /// Remove the handler for context menu requests.
+    pub fn clear_on_context_menu(&mut self) {
+        self.widget.context_menu_handler = None;
+    }
+
+    /// Set the handler for scroll wheel events.
+    ///
+    /// See [`SizedBox::on_wheel`] for details.
+    pub fn set_on_wheel(&mut self, handler: impl FnMut(&mut EventCtx, Vec2) -> bool + 'static) {
+        self.widget.wheel_handler = Some(Box::new(handler));
+    }
+
+    /// Remove the handler for scroll wheel events.
+    pub fn clear_on_wheel(&mut self) {
+        self.widget.wheel_handler = None;
+    }
+
+    /// Set the handler for clicks.
+    ///
+    /// See [`SizedBox::on_click`] for details.
+    pub fn set_on_click(&mut self, handler: impl FnMut(&mut EventCtx) + 'static) {
+        self.widget.click_handler = Some(Box::new(handler));
+    }
+
+    /// Remove the handler for clicks.
+    pub fn clear_on_click(&mut self) {
+        self.widget.click_handler = None;
+    }
+
+    /// Set the name of this box for assistive technologies.
+    ///
+    /// See [`SizedBox::accessibility_label`] for details.
+    pub fn set_accessibility_label(&mut self, label: impl Into<String>) {
+        self.widget.accessibility_label = Some(label.into());
+    }
+
+    /// Remove the name of this box for assistive technologies.
+    pub fn clear_accessibility_label(&mut self) {
+        self.widget.accessibility_label = None;
+    }
+
+    /// Set what this box is for, for assistive technologies.
+    ///
+    /// See [`SizedBox::accessibility_role`] for details.
+    pub fn set_accessibility_role(&mut self, role: Role) {
+        self.widget.accessibility_role = Some(role);
+    }
+
+    /// Remove the role of this box for assistive technologies.
+    pub fn clear_accessibility_role(&mut self) {
+        self.widget.accessibility_role = None;
+    }
+
+    /// Set the [`Env`] overrides for this box and its subtree.
+    ///
+    /// See [`SizedBox::env_scope`] for details.
+    pub fn set_env_scope(&mut self, f: impl Fn(&mut Env) + 'static) {
+        self.widget.env_scope = Some(Box::new(f));
+        self.widget.scoped_env = None;
+        self.ctx.request_layout();
+    }
+
+    /// Remove the [`Env`] overrides for this box and its subtree.
+    pub fn clear_env_scope(&mut self) {
+        self.widget.env_scope = None;
+        self.widget.scoped_env = None;
+        self.ctx.request_layout();
+    }
+
+    /// Share this box's width with the other boxes using `handle`.
+    ///
+    /// See [`SizedBox::shared_width`] for details.
+    pub fn set_shared_width(&mut self, handle: SharedSize) {
+        self.widget.shared_width = Some(SharedWidth::new(handle));
+        self.ctx.request_layout();
+    }
+
+    /// Stop sharing this box's width, and take it out of the shared width.
+    pub fn clear_shared_width(&mut self) {
+        if self.widget.shared_width.take().is_some() {
+            self.ctx.request_layout();
+        }
+    }
+
+    /// Clears background.
+    pub fn clear_background(&mut self) {
+        self.widget.background = None;
+        self.widget.color_transition = None;
+        self.ctx.request_paint();
+    }
+
+    /// Set how long background color changes are animated for.
+    ///
+    /// `None` disables the transition. See [`SizedBox::transition_background`].
+    pub fn set_transition_background(&mut self, duration: Option<Duration>) {
+        self.widget.transition_duration = duration;
+        if duration.is_none() {
+            self.widget.color_transition = None;
+        }
+    }
+
+    /// Paint a border around the widget with a color and width.
+    ///
+    /// Arguments can be either concrete values, or a [`Key`] of the respective
+    /// type. Invalid widths are handled as in [`SizedBox::border`].
+    pub fn set_border(
+        &mut self,
+        color: impl Into<KeyOrValue<Color>>,
+        width: impl Into<KeyOrValue<f64>>,
+    ) {
+        self.widget.border = Some(BorderStyle::new(color.into(), width.into()));
+        self.ctx.request_layout();
+    }
+
+    /// Paint a border exactly one device pixel wide.
+    ///
+    /// See [`SizedBox::hairline_border`] for details.
+    pub fn set_hairline_border(&mut self, color: impl Into<KeyOrValue<Color>>) {
+        self.widget.border = Some(BorderStyle::hairline(color.into()));
+        self.ctx.request_layout();
+    }
+
+    /// Clears border.
+    pub fn clear_border(&mut self) {
+        self.widget.border = None;
+        self.ctx.request_layout();
+    }
+
+    /// Style the background and border with a [`StyleBundle`].
+    ///
+    /// See [`SizedBox::styled`] for details.
+    pub fn set_style(&mut self, style: impl Into<KeyOrValue<Arc<StyleBundle>>>) {
+        self.widget.style = Some(style.into());
+        self.ctx.request_layout();
+    }
+
+    /// Clears the style bundle.
+    pub fn clear_style(&mut self) {
+        self.widget.style = None;
+        self.ctx.request_layout();
+    }
+
+    /// Set the overrides used in each interaction state.
+    ///
+    /// See [`SizedBox::state_styles`] for details.
+    pub fn set_state_styles(&mut self, styles: StateStyles) {
+        self.widget.state_styles = Some(styles);
+        self.ctx.request_paint();
+    }
+
+    /// Remove the per-state overrides.
+    pub fn clear_state_styles(&mut self) {
+        self.widget.state_styles = None;
+        self.ctx.request_paint();
+    }
+
+    /// Round off corners of this container by setting a corner radius
+    pub fn set_rounded(&mut self, radius: impl Into<KeyOrValue<RoundedRectRadii>>) {
+        self.widget.corner_radius = radius.into();
+        self.ctx.request_paint();
+    }
+
+    /// Round off each corner of this container with its own radius.
+    ///
+    /// See [`SizedBox::rounded_each`] for details.
+    pub fn set_rounded_each(&mut self, radii: [f64; 4]) {
+        self.widget.corner_radius = corner_radii(radii).into();
+        self.ctx.request_paint();
+    }
+
+    /// Set whether the box's edges are antialiased.
+    ///
+    /// See [`SizedBox::antialias`] for details.
+    pub fn set_antialias(&mut self, antialias: bool) {
+        self.widget.antialias = antialias;
+        self.ctx.request_paint();
+    }
+
+    // TODO - Doc
+    pub fn child_mut(&mut self) -> Option<WidgetMut<'_, 'b, Box<dyn Widget>>> {
+        let child = self.widget.child.as_mut()?;
+        Some(self.ctx.get_mut(child))
+    }
+}
+
+impl SizedBox {
+    /// The width and height this box should have given the parent constraints.
+    ///
+    /// This is where `fill` is resolved: it becomes the max constraint on bounded axes,
+    /// and is ignored on unbounded ones.
+    ///
+    /// `insets` is the space between the box's edges and the child's, which the size
+    /// is grown by with [`BoxSizing::ContentBox`].
+    fn resolved_size(&self, bc: &BoxConstraints, insets: Insets) -> (Option<f64>, Option<f64>) {
+        let width = if self.fill_width {
+            Some(bc.max().width).filter(|_| bc.is_width_bounded())
+        } else {
+            self.width
+        };
+        let height = if self.fill_height {
+            Some(bc.max().height).filter(|_| bc.is_height_bounded())
+        } else {
+            self.height
+        };
+        match self.box_sizing {
+            BoxSizing::BorderBox => (width, height),
+            BoxSizing::ContentBox => (
+                width.map(|width| width + insets.x_value()),
+                height.map(|height| height + insets.y_value()),
+            ),
+        }
+    }
+
+    /// The space between the box's edges and the child's, for a border of `border_width`.
+    fn child_insets(&self, border_width: f64) -> Insets {
+        Insets::new(
+            border_width + self.padding.x0,
+            border_width + self.padding.y0,
+            border_width + self.padding.x1,
+            border_width + self.padding.y1,
+        )
+    }
+
+    /// The min or max intrinsic width of the box, given a maximum `height`.
+    ///
+    /// A fixed width is returned as is; otherwise the child is measured, and the border
+    /// and padding are added. A box which expands is as wide as its content.
+    fn measure_width(&mut self, ctx: &mut LayoutCtx, height: f64, env: &Env, max: bool) -> f64 {
+        let scoped_env = self.scoped_env(env);
+        let env = scoped_env.as_ref().unwrap_or(env);
+        let style = self.resolved_style(env);
+        let border_width = self.border_inset(style.as_deref(), env);
+        let insets = self.child_insets(border_width);
+        let bc = BoxConstraints::new(Size::ZERO, Size::new(INFINITY, height));
+        let box_bc = self.box_constraints(&bc, insets);
+        let min_width = box_bc.min().width;
+        let max_width = box_bc.max().width;
+        // An infinite width means the box expands, which only gives a width in a
+        // bounded parent.
+        if min_width.is_finite() && min_width == max_width {
+            return min_width;
+        }
+        let pin_margin = self.pin.map_or(Insets::ZERO, |(_, margin)| margin);
+        let insets = Insets::new(
+            insets.x0 + pin_margin.x0,
+            insets.y0 + pin_margin.y0,
+            insets.x1 + pin_margin.x1,
+            insets.y1 + pin_margin.y1,
+        );
+
+        let child_height = (box_bc.max().height - insets.y_value()).max(0.0);
+        let child_width = match Self::active_child_mut(&mut self.child, &mut self.responsive) {
+            Some(child) if max => child.max_intrinsic_width(ctx, child_height, env),
+            Some(child) => child.min_intrinsic_width(ctx, child_height, env),
+            None => 0.0,
+        };
+        let width = child_width + insets.x_value();
+        if min_width.is_infinite() {
+            width
+        } else {
+            width.clamp(min_width, max_width)
+        }
+    }
+
+    /// The constraints the container applies to its own size.
+    fn box_constraints(&self, bc: &BoxConstraints, insets: Insets) -> BoxConstraints {
+        let bc = &match self.constraints {
+            Some(constraints) => {
+                let min = constraints.min().clamp(bc.min(), bc.max());
+                let max = constraints.max().clamp(min, bc.max());
+                BoxConstraints::new(min, max)
+            }
+            None => *bc,
+        };
+        let bc = &match self.min_touch_target {
+            Some(target) => {
+                let min = Size::new(
+                    bc.min().width.max(target.width),
+                    bc.min().height.max(target.height),
+                );
+                BoxConstraints::new(min.clamp(bc.min(), bc.max()), bc.max())
+            }
+            None => *bc,
+        };
+        let (width, height) = self.resolved_size(bc, insets);
+
+        // if we don't have a width/height, we don't change that axis.
+        // if we have a width/height, we clamp it on that axis.
+        let (min_width, max_width) = match width {
+            Some(width) => {
+                let w = width.max(bc.min().width).min(bc.max().width);
+                (w, w)
+            }
+            None => (bc.min().width, bc.max().width),
+        };
+
+        let (min_height, max_height) = match height {
+            Some(height) => {
+                let h = height.max(bc.min().height).min(bc.max().height);
+                (h, h)
+            }
+            None => (bc.min().height, bc.max().height),
+        };
+
+        let box_bc = BoxConstraints::new(
+            Size::new(min_width, min_height),
+            Size::new(max_width, max_height),
+        );
+        match self.aspect_ratio {
+            Some(ratio) if self.aspect_cover => aspect_cover_constraints(&box_bc, ratio),
+            Some(ratio) => aspect_constraints(&box_bc, ratio),
+            None => box_bc,
+        }
+    }
+
+    fn child_constraints(&self, bc: &BoxConstraints, insets: Insets) -> BoxConstraints {
+        let box_bc = self.box_constraints(bc, insets);
+        let child_bc = if self.loosen_child || self.pin.is_some() {
+            box_bc.loosen()
+        } else {
+            box_bc
+        };
+        if self.clip_child {
+            BoxConstraints::new(child_bc.min(), Size::new(INFINITY, INFINITY))
+        } else {
+            child_bc
+        }
+    }
+
+    /// Start fading from the last painted color, if transitions are enabled.
+    ///
+    /// Returns `true` if a transition was started, in which case the caller must request
+    /// an animation frame.
+    fn start_color_transition(&mut self) -> bool {
+        if self.transition_duration.is_none() {
+            return false;
+        }
+        match self.painted_color {
+            Some(from) => {
+                self.color_transition = Some(ColorTransition {
+                    from,
+                    elapsed: Duration::ZERO,
+                });
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// The color the background should be painted with right now.
+    ///
+    /// Returns `None` if the background isn't a solid color.
+    fn background_color(&self, is_disabled: bool, env: &Env) -> Option<Color> {
+        let target = match self.background.as_ref()? {
+            BackgroundBrush::Color(_) if is_disabled => env.get(theme::DISABLED_BACKGROUND_COLOR),
+            BackgroundBrush::Color(color) => color.resolve(env),
+            _ => return None,
+        };
+        match (&self.color_transition, self.scaled_transition_duration(env)) {
+            (Some(transition), Some(duration)) if !duration.is_zero() => {
+                let t = transition.elapsed.as_secs_f64() / duration.as_secs_f64();
+                Some(lerp_color(transition.from, target, t.min(1.0)))
+            }
+            _ => Some(target),
+        }
+    }
+
+    /// The style bundle, resolved in `env`.
+    ///
+    /// Callers should resolve it once and read everything they need from the result.
+    fn resolved_style(&self, env: &Env) -> Option<Arc<StyleBundle>> {
+        self.style.as_ref().map(|style| style.resolve(env))
+    }
+
+    /// The width and color of the border, taken from `style` if the box has one.
+    fn resolved_border(&self, style: Option<&StyleBundle>, env: &Env) -> Option<(f64, Color)> {
+        match style {
+            // `max` also turns NaN into zero.
+            Some(style) => Some((style.border_width.max(0.0), style.border_color)),
+            None => self
+                .border
+                .as_ref()
+                .map(|border| (border.resolve_width(env), border.color.resolve(env))),
+        }
+    }
+
+    /// How far the borders reach into the box: the wider of the solid and dashed borders.
+    fn border_inset(&self, style: Option<&StyleBundle>, env: &Env) -> f64 {
+        let solid = self
+            .resolved_border(style, env)
+            .map_or(0.0, |(width, _)| width);
+        let dashed = self
+            .dashed_border
+            .as_ref()
+            .map_or(0.0, |dashes| dashes.width);
+        solid.max(dashed)
+    }
+
+    /// The corner radius, taken from `style` if the box has one, with invalid radii
+    /// replaced with zero.
+    fn resolved_corner_radius(&self, style: Option<&StyleBundle>, env: &Env) -> RoundedRectRadii {
+        match style {
+            Some(style) => valid_corner_radii(style.corner_radius),
+            None => valid_corner_radii(self.corner_radius.resolve(env)),
+        }
+    }
+
+    /// Whether the border is a hairline, and not overridden by the style bundle.
+    fn has_hairline_border(&self) -> bool {
+        self.style.is_none() && self.border.as_ref().map_or(false, |border| border.hairline)
+    }
+
+    /// The duration of color transitions, scaled by [`theme::ANIMATION_SCALE`].
+    fn scaled_transition_duration(&self, env: &Env) -> Option<Duration> {
+        let scale = theme::animation_scale(env);
+        self.transition_duration
+            .map(|duration| duration.mul_f64(scale))
+    }
+
+    /// The cursor to show while hovered, if any.
+    fn hover_cursor(&self) -> Option<Cursor> {
+        match &self.cursor {
+            Some(cursor) => Some(cursor.clone()),
+            None if self.is_link => Some(Cursor::Pointer),
+            None => None,
+        }
+    }
+
+    /// The child which is shown.
+    ///
+    /// This is the narrow child of a responsive box while it's not stashed, and the
+    /// regular child otherwise.
+    fn active_child(&self) -> Option<&WidgetPod<Box<dyn Widget>>> {
+        match &self.responsive {
+            Some(responsive) if !responsive.narrow.state.is_stashed => Some(&responsive.narrow),
+            // The child is also stashed while the box is hidden.
+            _ => self.child.as_ref().filter(|child| !child.state.is_stashed),
+        }
+    }
+
+    /// Same as [`active_child`](Self::active_child), but borrowing only the fields needed.
+    fn active_child_mut<'a>(
+        child: &'a mut Option<WidgetPod<Box<dyn Widget>>>,
+        responsive: &'a mut Option<Responsive>,
+    ) -> Option<&'a mut WidgetPod<Box<dyn Widget>>> {
+        match responsive {
+            Some(responsive) if !responsive.narrow.state.is_stashed => Some(&mut responsive.narrow),
+            _ => child.as_mut().filter(|child| !child.state.is_stashed),
+        }
+    }
+
+    fn paint_child(&mut self, ctx: &mut PaintCtx, env: &Env) {
+        let child = match Self::active_child_mut(&mut self.child, &mut self.responsive) {
+            Some(child) => child,
+            None => return,
+        };
+        self.painted_content = true;
+        let clip = self.clip_child.then(|| {
+            ctx.size()
+                .to_rounded_rect(valid_corner_radii(self.corner_radius.resolve(env)))
+        });
+        let antialias = self.antialias;
+        let transform = child_transform(self.transform, self.rotation, ctx.size());
+        let paint = |ctx: &mut PaintCtx, child: &mut WidgetPod<Box<dyn Widget>>| {
+            ctx.with_save(|ctx| {
+                if let Some(panel) = clip {
+                    clip_to(ctx, panel, antialias);
+                }
+                match transform {
+                    Some(transform) => {
+                        // The child checks what to paint against the visible region.
+                        let region = transform
+                            .inverse()
+                            .transform_rect_bbox(ctx.region().bounding_box());
+                        ctx.transform(transform);
+                        ctx.with_child_ctx(region, |ctx| child.paint(ctx, env));
+                    }
+                    None => child.paint(ctx, env),
+                }
+            });
+        };
+
+        if !self.cache_rendering && self.edge_fade.is_none() {
+            paint(ctx, child);
+            return;
+        }
+
+        let rect = ctx.size().to_rect();
+        let scale = ctx.scale();
+        let is_cached = matches!(
+            &self.render_cache,
+            Some(cache) if cache.rect == rect && cache.scale == scale
+        );
+        if is_cached && !child.state.needs_paint {
+            ctx.skip_child(child);
+        } else {
+            trace!("Painting child into the render cache");
+            let mut image = ctx.paint_offscreen(rect, |ctx| paint(ctx, child));
+            if let (Some(image), Some(fade)) = (&mut image, &self.edge_fade) {
+                *image = fade.apply(image, scale);
+            }
+            self.render_cache = image.map(|image| RenderCache {
+                image: image.to_image(ctx.render_ctx),
+                rect,
+                scale,
+            });
+        }
+
+        match &self.render_cache {
+            Some(cache) => {
+                ctx.draw_image(&cache.image, cache.rect, InterpolationMode::Bilinear);
+            }
+            // The bitmap couldn't be created, so paint the child directly.
+            None => paint(ctx, child),
+        }
+    }
+
+    /// Paint the background, border and child, ignoring the opacity.
+    fn paint_content(&mut self, ctx: &mut PaintCtx, env: &Env) {
+        let state_style = self
+            .state_styles
+            .as_ref()
+            .map(|styles| styles.resolve(ctx, env))
+            .unwrap_or_default();
+        // Resolve the bundle only once, so the background and border come from the same value.
+        let style = self.resolved_style(env);
+        let corner_radius = state_style
+            .corner_radius
+            .unwrap_or_else(|| self.resolved_corner_radius(style.as_deref(), env));
+        self.painted_content = false;
+
+        if self.background_over_child {
+            self.paint_child(ctx, env);
+        }
+
+        let is_hovered = ctx.is_hot() && self.hover_background.is_some();
+        // Solid colors are replaced with the theme's disabled color, other brushes are
+        // painted as-is.
+        let hover_is_color = matches!(self.hover_background, Some(BackgroundBrush::Color(_)));
+        self.painted_color = match &style {
+            _ if state_style.background.is_some() => state_style.background,
+            _ if is_hovered && hover_is_color && ctx.is_disabled() => {
+                Some(env.get(theme::DISABLED_BACKGROUND_COLOR))
+            }
+            _ if is_hovered => None,
+            Some(_) if ctx.is_disabled() => Some(env.get(theme::DISABLED_BACKGROUND_COLOR)),
+            Some(style) if style.brush.is_some() => None,
+            Some(style) => Some(style.background),
+            None => self.background_color(ctx.is_disabled(), env),
+        };
+        let background = if is_hovered {
+            self.hover_background.as_mut()
+        } else if style.is_some() {
+            None
+        } else {
+            self.background.as_mut()
+        };
+        let style_brush = match &style {
+            Some(style) if !is_hovered && self.painted_color.is_none() => style.brush.as_ref(),
+            _ => None,
+        };
+        let image_cache = &mut self.image_cache;
+        let antialias = self.antialias;
+        let painted_color = self.painted_color;
+        if background.is_some() || painted_color.is_some() || style_brush.is_some() {
+            let panel = ctx.size().to_rounded_rect(corner_radius);
+            let bounds = ctx.size().to_rect();
+
+            trace_span!("paint background").in_scope(|| {
+                ctx.with_save(|ctx| {
+                    clip_to(ctx, panel, antialias);
+                    match (painted_color, background, style_brush) {
+                        (Some(color), _, _) => ctx.fill(bounds, &color),
+                        (None, Some(background), _) => {
+                            background.paint_cached(ctx, env, image_cache)
+                        }
+                        (None, None, Some(brush)) => brush.paint(ctx, env),
+                        (None, None, None) => {}
+                    }
+                });
+            });
+            self.animation_painted = true;
+            self.painted_content = true;
+        }
+
+        if let Some(shadow) = &self.inner_shadow {
+            let panel = ctx.size().to_rounded_rect(corner_radius);
+            let color = shadow.color.resolve(env);
+            paint_inner_shadow(ctx.render_ctx, panel, shadow, color, antialias);
+            self.painted_content = true;
+        }
+
+        let border = match state_style.border {
+            Some((color, width)) => Some((width, color)),
+            None => self.resolved_border(style.as_deref(), env),
+        };
+        if let Some((border_width, border_color)) = border.filter(|(width, _)| *width > 0.0) {
+            let border_width = if self.has_hairline_border() && state_style.border.is_none() {
+                1.0 / ctx.scale()
+            } else {
+                border_width
+            };
+            let border_color = if state_style.border.is_some() {
+                border_color
+            } else if ctx.is_disabled() {
+                env.get(theme::DISABLED_BORDER_COLOR)
+            } else if self.focusable && ctx.is_focused() {
+                env.get(theme::PRIMARY_LIGHT)
+            } else {
+                border_color
+            };
+            let size = ctx.size();
+            paint_border(
+                ctx.render_ctx,
+                size,
+                corner_radius,
+                border_width,
+                border_color,
+                antialias,
+            );
+            self.painted_content = true;
+        };
+
+        if let Some(dashes) = &self.dashed_border {
+            let color = if ctx.is_disabled() {
+                env.get(theme::DISABLED_BORDER_COLOR)
+            } else {
+                dashes.color.resolve(env)
+            };
+            dashes.paint(ctx.render_ctx, ctx.size(), corner_radius, color);
+            self.animation_painted = true;
+            self.painted_content = true;
+        }
+
+        if !self.background_over_child {
+            self.paint_child(ctx, env);
+        }
+
+        if self.shows_marching_ants(ctx.is_focused()) {
+            let ants = self.marching_ants.as_ref().unwrap();
+            let color = ants.color.resolve(env);
+            ants.paint(ctx.render_ctx, ctx.size(), corner_radius, color);
+            self.animation_painted = true;
+            self.painted_content = true;
+        }
+
+        if self.is_link && ctx.is_hot() {
+            let size = ctx.size();
+            let underline = Rect::new(
+                0.0,
+                size.height - LINK_UNDERLINE_WIDTH,
+                size.width,
+                size.height,
+            );
+            ctx.fill(underline, &env.get(theme::TEXT_COLOR));
+            self.painted_content = true;
+        }
+
+        if let Some(ring) = self.focus_ring.as_ref().filter(|_| self.focusable) {
+            if ctx.is_focus_visible() {
+                let size = ctx.size();
+                let width = ring.resolve_width(env);
+                let color = ring.color.resolve(env);
+                paint_border(ctx.render_ctx, size, corner_radius, width, color, antialias);
+                self.painted_content = true;
+            }
+        }
+    }
+
+    /// The parts of `bounds` a hover change needs to repaint.
+    ///
+    /// This is the whole of `bounds`, unless the child is known to hide the
+    /// background under it.
+    fn hover_repaint_rects(&self, bounds: Rect) -> SmallVec<[Rect; 4]> {
+        let child_rect = match self.active_child() {
+            Some(child) if !self.hover_repaints_child && !self.background_over_child => {
+                child.layout_rect().intersect(bounds)
+            }
+            _ => return smallvec![bounds],
+        };
+        let rects = [
+            Rect::new(bounds.x0, bounds.y0, bounds.x1, child_rect.y0),
+            Rect::new(bounds.x0, child_rect.y1, bounds.x1, bounds.y1),
+            Rect::new(bounds.x0, child_rect.y0, child_rect.x0, child_rect.y1),
+            Rect::new(child_rect.x1, child_rect.y0, bounds.x1, child_rect.y1),
+        ];
+        rects.into_iter().filter(|rect| rect.area() > 0.0).collect()
+    }
+
+    fn has_animated_background(&self) -> bool {
+        self.background
+            .as_ref()
+            .map_or(false, BackgroundBrush::is_animated)
+    }
+
+    /// Whether anything shown in this box moves on animation frames, when not paused.
+    fn has_animation(&self, is_focused: bool) -> bool {
+        let moves = |dashes: &Option<MarchingAnts>| {
+            dashes.as_ref().map_or(false, |dashes| dashes.speed != 0.0)
+        };
+        self.has_animated_background()
+            || moves(&self.dashed_border)
+            || (moves(&self.marching_ants) && self.shows_marching_ants(is_focused))
+    }
+
+    /// Whether the marching ants are shown, which for a focus ring needs focus.
+    fn shows_marching_ants(&self, is_focused: bool) -> bool {
+        self.marching_ants.is_some() && (!self.focusable || is_focused)
+    }
+
+    #[allow(dead_code)]
+    pub(crate) fn width_and_height(&self) -> (Option<f64>, Option<f64>) {
+        (self.width, self.height)
+    }
+
+    /// The environment with [`env_scope`](Self::env_scope) applied, if there is one.
+    ///
+    /// The scoped env is only rebuilt when `env` or the scope changes.
+    fn scoped_env(&mut self, env: &Env) -> Option<Env> {
+        let f = self.env_scope.as_ref()?;
+        match &self.scoped_env {
+            Some((parent, scoped)) if parent.same(env) => Some(scoped.clone()),
+            _ => {
+                let mut scoped = env.clone();
+                f(&mut scoped);
+                self.scoped_env = Some((env.clone(), scoped.clone()));
+                Some(scoped)
+            }
+        }
+    }
+
+    /// The keys that changed in the environment seen by the box and its subtree.
+    ///
+    /// Without an [`env_scope`](Self::env_scope), these are the `changed` keys of the
+    /// new `env`. A scope can override a changed key, or derive other keys from it, so
+    /// the scoped environments before and after the change are compared instead.
+    fn scoped_changed_keys(&self, changed: &ChangedKeys, env: &Env) -> ChangedKeys {
+        match (&self.env_scope, &self.scoped_env) {
+            (Some(f), Some((_, old_scoped))) => {
+                let mut scoped = env.clone();
+                f(&mut scoped);
+                old_scoped.clone().merge(&scoped)
+            }
+            _ => changed.clone(),
+        }
+    }
+}
+
+impl Widget for SizedBox {
+    fn on_event(&mut self, ctx: &mut EventCtx, event: &Event, env: &Env) {
+        let scoped_env = self.scoped_env(env);
+        let env = scoped_env.as_ref().unwrap_or(env);
+        if let Event::Command(cmd) = event {
+            if cmd.is(SHARED_SIZE_CHANGED) {
+                ctx.request_layout();
+                ctx.set_handled();
+                return;
+            }
+        }
+        if let Some(child) = Self::active_child_mut(&mut self.child, &mut self.responsive) {
+            match child_transform(self.transform, self.rotation, ctx.size())
+                .and_then(|transform| untransform_mouse(event, transform))
+            {
+                Some(event) => child.on_event(ctx, &event, env),
+                None => child.on_event(ctx, event, env),
+            }
+        }
+
+        if let (Event::MouseDown(mouse), Some(handler)) = (event, &mut self.context_menu_handler) {
+            if is_context_menu_click(mouse) && !ctx.is_handled() {
+                handler(ctx, mouse.pos);
+                ctx.set_handled();
+            }
+        }
+
+        if let (Event::Wheel(mouse), Some(handler)) = (event, &mut self.wheel_handler) {
+            if !ctx.is_handled() && handler(ctx, mouse.wheel_delta) {
+                ctx.set_handled();
+            }
+        }
+
+        if let Event::MouseMove(_) = event {
+            if let Some(cursor) = self.hover_cursor() {
+                ctx.set_cursor(&cursor);
+            }
+        }
+
+        if self.is_link {
+            match event {
+                Event::KeyDown(key)
+                    if key.key == KbKey::Enter
+                        && ctx.is_focused()
+                        && !ctx.is_handled()
+                        && !ctx.is_disabled() =>
+                {
+                    if let Some(handler) = &mut self.click_handler {
+                        handler(ctx);
+                    }
+                    ctx.set_handled();
+                }
+                _ => {}
+            }
+        }
+
+        if let Some(handler) = &mut self.click_handler {
+            match event {
+                Event::MouseDown(mouse)
+                    if mouse.button.is_left()
+                        && !is_context_menu_click(mouse)
+                        && !ctx.is_handled()
+                        && !ctx.is_disabled() =>
+                {
+                    ctx.set_active(true);
+                    if self.state_styles.is_some() {
+                        ctx.request_paint();
+                    }
+                    ctx.set_handled();
+                }
+                Event::MouseUp(mouse) if mouse.button.is_left() && ctx.is_active() => {
+                    ctx.set_active(false);
+                    if self.state_styles.is_some() {
+                        ctx.request_paint();
+                    }
+                    if ctx.is_hot() && !ctx.is_disabled() {
+                        handler(ctx);
+                    }
+                    ctx.set_handled();
+                }
+                _ => {}
+            }
+        }
+
+        let transition_duration = self.scaled_transition_duration(env).unwrap_or_default();
+        if let (Event::AnimFrame(interval), Some(transition)) = (event, &mut self.color_transition)
+        {
+            transition.elapsed += Duration::from_nanos(*interval);
+            if transition.elapsed >= transition_duration {
+                self.color_transition = None;
+            } else {
+                ctx.request_anim_frame();
+            }
+            ctx.request_paint();
+        }
+
+        if self.has_animation(ctx.is_focused()) && !self.animation_paused {
+            if let Event::AnimFrame(interval) = event {
+                // If we weren't painted since the last frame, we're probably not visible,
+                // so we stop animating until the next event.
+                let scale = theme::animation_scale(env);
+                if self.animation_painted && scale > 0.0 {
+                    let interval = (*interval as f64 / scale) as u64;
+                    if let Some(background) = &mut self.background {
+                        background.advance(interval);
+                    }
+                    if self.shows_marching_ants(ctx.is_focused()) {
+                        self.marching_ants.as_mut().unwrap().advance(interval);
+                    }
+                    if let Some(dashes) = &mut self.dashed_border {
+                        dashes.advance(interval);
+                    }
+                    ctx.request_anim_frame();
+                    ctx.request_paint();
+                }
+                self.animation_painted = false;
+            } else if !self.animation_painted {
+                self.animation_painted = true;
+                ctx.request_anim_frame();
+            }
+        }
+    }
+
+    fn on_status_change(&mut self, ctx: &mut LifeCycleCtx, event: &StatusChange, _env: &Env) {
+        match event {
+            StatusChange::HotChanged(_) if self.state_styles.is_some() || self.is_link => {
+                ctx.request_paint()
+            }
+            StatusChange::HotChanged(_) if self.hover_background.is_some() => {
+                // Only the background changes, so we don't repaint our paint insets.
+                for rect in self.hover_repaint_rects(ctx.size().to_rect()) {
+                    ctx.request_paint_rect(rect);
+                }
+            }
+            StatusChange::FocusChanged(focused) if self.focusable => {
+                // A focus ring of marching ants starts moving when shown.
+                if *focused && self.has_animation(true) && !self.animation_paused {
+                    self.animation_painted = true;
+                    ctx.request_anim_frame();
+                }
+                ctx.request_paint();
+            }
+            _ => {}
+        }
+    }
+
+    fn lifecycle(&mut self, ctx: &mut LifeCycleCtx, event: &LifeCycle, env: &Env) {
+        // The subtree is told about the keys that changed in its own environment.
+        // This must be worked out before the scoped env is rebuilt.
+        let scoped_event = match event {
+            LifeCycle::EnvChanged(changed) if self.env_scope.is_some() => Some(
+                LifeCycle::EnvChanged(self.scoped_changed_keys(changed, env)),
+            ),
+            _ => None,
+        };
+        let event = scoped_event.as_ref().unwrap_or(event);
+        let scoped_env = self.scoped_env(env);
+        let env = scoped_env.as_ref().unwrap_or(env);
+        match event {
+            LifeCycle::WidgetAdded
+                if self.has_animation(ctx.is_focused()) && !self.animation_paused =>
+            {
+                self.animation_painted = true;
+                ctx.request_anim_frame();
+            }
+            LifeCycle::BuildFocusChain if self.focusable => ctx.register_for_focus(),
+            LifeCycle::DisabledChanged(_) => {
+                // Our colors depend on the disabled state.
+                if self.start_color_transition() {
+                    ctx.request_anim_frame();
+                }
+                ctx.request_paint();
+            }
+            _ => {}
+        }
+
+        if let Some(ref mut child) = self.child {
+            child.lifecycle(ctx, event, env)
+        }
+        if let Some(responsive) = &mut self.responsive {
+            responsive.narrow.lifecycle(ctx, event, env);
+        }
+    }
+
+    fn env_changed(&mut self, ctx: &mut LifeCycleCtx, changed: &ChangedKeys, env: &Env) {
+        let changed = self.scoped_changed_keys(changed, env);
+        let keys = self.env_keys();
+        if changed.iter().any(|key| keys.contains(&key)) {
+            ctx.request_layout();
+        } else if [&self.background, &self.hover_background]
+            .into_iter()
+            .flatten()
+            .any(|brush| {
+                matches!(
+                    brush,
+                    BackgroundBrush::PainterFn(_) | BackgroundBrush::Custom(_)
+                )
+            })
+        {
+            // These can read any key.
+            ctx.request_paint();
+        }
+    }
+
+    fn layout(&mut self, ctx: &mut LayoutCtx, bc: &BoxConstraints, env: &Env) -> Size {
+        let scoped_env = self.scoped_env(env);
+        let env = scoped_env.as_ref().unwrap_or(env);
+        trace!("Constraints: {}", bc);
+
+        // Shrink constraints by border offset
+        let style = self.resolved_style(env);
+        let border_width = self.border_inset(style.as_deref(), env);
+
+        ctx.set_z_index(self.z_index);
+        ctx.set_hit_test_inset(self.hit_test_inset);
+
+        self.hidden = match &self.visible_if {
+            Some(key) => match env.try_get(key) {
+                Ok(visible) => !visible,
+                Err(err) => {
+                    if !self.warned_missing_visible_if {
+                        warn!("SizedBox::visible_if: {}, showing the box", err);
+                        self.warned_missing_visible_if = true;
+                    }
+                    false
+                }
+            },
+            None => false,
+        };
+        if let Some(responsive) = &mut self.responsive {
+            let is_narrow = bc.max().width < responsive.breakpoint;
+            ctx.set_stashed(&mut responsive.narrow, self.hidden || !is_narrow);
+            if let Some(child) = &mut self.child {
+                ctx.set_stashed(child, self.hidden || is_narrow);
+            }
+        } else if let Some(child) = &mut self.child {
+            ctx.set_stashed(child, self.hidden);
+        }
+
+        // The space between our edges and the child's
+        let insets = self.child_insets(border_width);
+
+        let box_bc = self.box_constraints(bc, insets);
+        let child_bc = self.child_constraints(bc, insets);
+        let mut child_bc = child_bc.shrink(insets.size());
+        let pin_margin = self.pin.map_or(Insets::ZERO, |(_, margin)| margin);
+        child_bc = child_bc.shrink(pin_margin.size());
+        if self.snap_child_to_pixels {
+            let max = Size::new(child_bc.max().width.floor(), child_bc.max().height.floor());
+            let min = Size::new(child_bc.min().width.ceil(), child_bc.min().height.ceil());
+            child_bc = BoxConstraints::new(min.clamp(Size::ZERO, max), max);
+        }
+        trace!(
+            "Box constraints: {}, child constraints: {}",
+            box_bc,
+            child_bc
+        );
+        let mut origin = Point::new(insets.x0, insets.y0);
+
+        // Anything in the box may move, so the faded box is painted again.
+        self.opacity_cache = None;
+        let mut size;
+        match Self::active_child_mut(&mut self.child, &mut self.responsive) {
+            Some(child) => {
+                if child.state.needs_layout {
+                    self.render_cache = None;
+                }
+                if self.measure_intrinsic_width || self.shared_width.is_some() {
+                    self.child_intrinsic_width =
+                        child.max_intrinsic_width(ctx, child_bc.max().height, env);
+                }
+                if let Some(shared) = &mut self.shared_width {
+                    let width =
+                        shared.contribute(ctx, self.child_intrinsic_width + insets.x_value());
+                    let width = child_bc
+                        .constrain(Size::new(width - insets.x_value(), 0.0))
+                        .width;
+                    child_bc = BoxConstraints::new(
+                        Size::new(width, child_bc.min().height),
+                        Size::new(width, child_bc.max().height),
+                    );
+                }
+                ctx.set_child_corner_radii(
+                    child,
+                    inner_corner_radii(valid_corner_radii(self.corner_radius.resolve(env)), insets),
+                );
+                let mut child_size = child.layout(ctx, &child_bc, env);
+                if self.clip_child
+                    && !(child_size.width.is_finite() && child_size.height.is_finite())
+                {
+                    warn!(
+                        "SizedBox with clip_child: the child returned an infinite size {}, but it's laid out without a maximum size and must pick a finite one. Laying it out again within the box.",
+                        child_size,
+                    );
+                    let max = box_bc.shrink(insets.size() + pin_margin.size()).max();
+                    let fit_bc = BoxConstraints::new(child_bc.min().clamp(Size::ZERO, max), max);
+                    child_size = child.layout(ctx, &fit_bc, env);
+                }
+                if self.collapse_when_empty && child_size.is_empty() {
+                    // Nothing to show, so don't draw a border around nothing either.
+                    self.collapsed = true;
+                    size = bc.constrain(Size::ZERO);
+                    ctx.place_child(child, Point::ORIGIN, env);
+                    self.child_origin = Some(Point::ORIGIN);
+                } else {
+                    self.collapsed = false;
+                    size = child_size + insets.size() + pin_margin.size();
+                    if let Some((corner, margin)) = self.pin {
+                        let box_size = box_bc.constrain(size);
+                        let area = box_size.to_rect() - insets - margin;
+                        origin = corner.place(area, child_size);
+                        size = box_size;
+                    } else if self.loosen_child || self.clip_child {
+                        // The child may be smaller than us, in which case we center it,
+                        // or larger, in which case it overflows to the right and bottom.
+                        let box_size = box_bc.constrain(size);
+                        origin.x += ((box_size.width - size.width) / 2.0).max(0.0);
+                        origin.y += ((box_size.height - size.height) / 2.0).max(0.0);
+                        size = box_size;
+                    }
+                    size = snap_to_grid(size, self.baseline_grid, &box_bc);
+                    if self.snap_child_to_pixels {
+                        origin = origin.round();
+                    }
+                    ctx.place_child(child, origin, env);
+                    self.child_origin = Some(origin);
+
+                    // The child's baselines, moved down by whatever is below the child.
+                    let space_below = size.height - origin.y - child_size.height;
+                    ctx.set_baseline_offset(child.baseline_offset() + space_below);
+                    ctx.set_last_baseline_offset(child.last_baseline_offset() + space_below);
+
+                    if self.clip_child {
+                        let inner_rect = size.to_rect().inset(-border_width);
+                        let child_rect = Rect::from_origin_size(origin, child_size);
+                        ctx.set_content_overflow(child_rect.union(inner_rect) - inner_rect);
+                    } else if let Some(transform) =
+                        child_transform(self.transform, self.rotation, size)
+                    {
+                        let bounds = size.to_rect();
+                        let child_rect = transform.transform_rect_bbox(child.paint_rect());
+                        ctx.set_paint_insets(child_rect.union(bounds) - bounds);
+                    }
+                }
+            }
+            None if self.hidden => {
+                self.collapsed = false;
+                self.child_origin = None;
+                size = bc.constrain(Size::ZERO);
+            }
+            None => {
+                self.collapsed = false;
+                self.child_origin = None;
+                let (width, height) = self.resolved_size(bc, insets);
+                let mut width = width.unwrap_or(0.0);
+                if let Some(shared) = &mut self.shared_width {
+                    width = shared.contribute(ctx, width.max(insets.x_value()));
+                }
+                size = box_bc.constrain((width, height.unwrap_or(0.0)));
+                size = snap_to_grid(size, self.baseline_grid, &box_bc);
+            }
+        };
+        if let Some(child) = self.active_child() {
+            ctx.debug_check_child_placed(child);
+        }
+
+        // TODO - figure out paint insets
+
+        trace!("Computed size: {}", size);
+
+        if size.width.is_infinite() {
+            warn!("SizedBox is returning an infinite width.");
+        }
+        if size.height.is_infinite() {
+            warn!("SizedBox is returning an infinite height.");
+        }
+
+        size
+    }
+
+    fn min_intrinsic_width(&mut self, ctx: &mut LayoutCtx, height: f64, env: &Env) -> f64 {
+        self.measure_width(ctx, height, env, false)
+    }
+
+    fn max_intrinsic_width(&mut self, ctx: &mut LayoutCtx, height: f64, env: &Env) -> f64 {
+        self.measure_width(ctx, height, env, true)
+    }
+
+    fn paint(&mut self, ctx: &mut PaintCtx, env: &Env) {
+        let scoped_env = self.scoped_env(env);
+        let env = scoped_env.as_ref().unwrap_or(env);
+        if self.opacity <= 0.0 || self.collapsed || self.hidden {
+            self.painted_content = false;
+            if let Some(child) = Self::active_child_mut(&mut self.child, &mut self.responsive) {
+                ctx.skip_child(child);
+            }
+            return;
+        }
+        if self.opacity >= 1.0 {
+            self.paint_content(ctx, env);
+            return;
+        }
+
+        // piet has no layers, so paint into a bitmap and fade that. The bitmap covers
+        // the paint rect, so that what the box and its child paint outside of the
+        // box's bounds isn't cut off, and it's kept until something in the box changes.
+        let rect = ctx.widget_state.local_paint_rect;
+        let scale = ctx.scale();
+        let is_cached = matches!(
+            &self.opacity_cache,
+            Some(cache) if cache.rect == rect && cache.scale == scale
+        );
+        if is_cached && !ctx.widget_state.needs_paint {
+            if let Some(child) = Self::active_child_mut(&mut self.child, &mut self.responsive) {
+                ctx.skip_child(child);
+            }
+        } else {
+            trace!("Painting the faded box");
+            let image = ctx.paint_offscreen(rect, |ctx| self.paint_content(ctx, env));
+            self.opacity_cache = image.map(|image| RenderCache {
+                image: fade_image(&image, self.opacity).to_image(ctx.render_ctx),
+                rect,
+                scale,
+            });
+        }
+
+        match &self.opacity_cache {
+            Some(cache) => {
+                ctx.draw_image(&cache.image, cache.rect, InterpolationMode::Bilinear);
+            }
+            // The bitmap couldn't be created, so paint the box directly.
+            None => self.paint_content(ctx, env),
+        }
+    }
+
+    fn children(&self) -> SmallVec<[WidgetRef<'_, dyn Widget>; 16]> {
+        let mut children = SmallVec::new();
+        if let Some(child) = &self.child {
+            children.push(child.as_dyn());
+        }
+        if let Some(responsive) = &self.responsive {
+            children.push(responsive.narrow.as_dyn());
+        }
+        children
+    }
+
+    fn make_trace_span(&self) -> Span {
+        trace_span!("SizedBox")
+    }
+
+    fn accessibility(&self) -> Option<Accessibility> {
+        if self.accessibility_label.is_none() && self.accessibility_role.is_none() {
+            return None;
+        }
+        Some(Accessibility {
+            role: self.accessibility_role.unwrap_or_default(),
+            label: self.accessibility_label.clone(),
+        })
+    }
+}
+
+/// Whether `value` is negative or NaN, and so can't be used as a width or radius.
+fn is_invalid_length(value: f64) -> bool {
+    value.is_nan() || value < 0.0
+}
+
+/// Whether a mouse press asks for a context menu.
+fn is_context_menu_click(mouse: &MouseEvent) -> bool {
+    // On macOS, Ctrl-click is the usual alternative to a right click.
+    mouse.button.is_right()
+        || (cfg!(target_os = "macos") && mouse.button.is_left() && mouse.mods.ctrl())
+}
+
+fn validate_aspect_ratio(ratio: f64) -> Option<f64> {
+    if ratio > 0.0 && ratio.is_finite() {
+        Some(ratio)
+    } else {
+        warn!("SizedBox aspect ratio must be positive, got {}", ratio);
+        None
+    }
+}
+
+/// Tight constraints for the largest size of the given ratio within `bc`.
+///
+/// If neither axis of `bc` is bounded, it's returned unchanged.
+fn aspect_constraints(bc: &BoxConstraints, ratio: f64) -> BoxConstraints {
+    let max = bc.max();
+    let width = match (bc.is_width_bounded(), bc.is_height_bounded()) {
+        (true, true) => max.width.min(max.height * ratio),
+        (true, false) => max.width,
+        (false, true) => max.height * ratio,
+        (false, false) => return *bc,
+    };
+    BoxConstraints::tight(bc.constrain(Size::new(width, width / ratio)))
+}
+
+/// Tight constraints for the smallest size of the given ratio covering `bc`.
+///
+/// The area to cover is `bc`'s maximum size, or its minimum on unbounded axes. If that
+/// area is empty, `bc` is returned unchanged.
+fn aspect_cover_constraints(bc: &BoxConstraints, ratio: f64) -> BoxConstraints {
+    let area = Size::new(
+        if bc.is_width_bounded() {
+            bc.max().width
+        } else {
+            bc.min().width
+        },
+        if bc.is_height_bounded() {
+            bc.max().height
+        } else {
+            bc.min().height
+        },
+    );
+    if area.width == 0.0 && area.height == 0.0 {
+        return *bc;
+    }
+    let width = area.width.max(area.height * ratio);
+    // Only the minimum applies, since we deliberately overflow the maximum.
+    BoxConstraints::tight(
+        Size::new(width, width / ratio).clamp(bc.min(), Size::new(INFINITY, INFINITY)),
+    )
+}
+
+fn validate_grid_step(step: f64) -> Option<f64> {
+    if step > 0.0 && step.is_finite() {
+        Some(step)
+    } else {
+        warn!("SizedBox baseline grid step must be positive, got {}", step);
+        None
+    }
+}
+
+/// Round the height of `size` up to a multiple of `step`, within `bc`.
+fn snap_to_grid(size: Size, step: Option<f64>, bc: &BoxConstraints) -> Size {
+    match step {
+        Some(step) => bc.constrain(Size::new(size.width, (size.height / step).ceil() * step)),
+        None => size,
+    }
+}
+
+fn validate_constraints(constraints: BoxConstraints) -> BoxConstraints {
+    let (min, max) = (constraints.min(), constraints.max());
+    if min.width <= max.width && min.height <= max.height {
+        return constraints;
+    }
+    warn!(
+        "SizedBox constraints have a minimum larger than the maximum: {:?}",
+        constraints
+    );
+    BoxConstraints::new(
+        Size::new(min.width.min(max.width), min.height.min(max.height)),
+        max,
+    )
+}
+
+/// Paint a border `width` wide along the inside of a box of the given size.
+fn paint_border(
+    rc: &mut impl RenderContext,
+    size: Size,
+    corner_radius: RoundedRectRadii,
+    width: f64,
+    color: Color,
+    antialias: bool,
+) {
+    if antialias {
+        let border_rect = size
+            .to_rect()
+            .inset(width / -2.0)
+            .to_rounded_rect(corner_radius);
+        rc.stroke(border_rect, &color, width);
+    } else {
+        // Fill the ring between the outer and inner edges of the border.
+        let outer = size.to_rounded_rect(corner_radius);
+        let inner = size
+            .to_rect()
+            .inset(-width)
+            .to_rounded_rect(inner_corner_radii(corner_radius, Insets::uniform(width)));
+        let mut ring = pixel_snapped_path(outer);
+        ring.extend(pixel_snapped_path(inner));
+        rc.fill_even_odd(ring, &color);
+    }
+}
+
+/// Paint `shadow` inside `panel`, clipped to it like [`clip_to`] does.
+///
+/// The shadow is cast by four blurred rects surrounding `panel`, moved by the
+/// shadow's offset; only their blur reaches inside the clip. Rounded corners don't
+/// bend the shadow, which is hard to see at the usual blur radii.
+fn paint_inner_shadow<R: RenderContext>(
+    rc: &mut R,
+    panel: RoundedRect,
+    shadow: &InnerShadow,
+    color: Color,
+    antialias: bool,
+) {
+    let inner = panel.rect();
+    // Far enough that the outer edges of the rects don't show through the blur.
+    let margin = shadow.blur * 3.0 + shadow.offset.hypot() + 1.0;
+    let outer = inner.inflate(margin, margin);
+    let sides = [
+        Rect::new(outer.x0, outer.y0, outer.x1, inner.y0),
+        Rect::new(outer.x0, inner.y1, outer.x1, outer.y1),
+        Rect::new(outer.x0, inner.y0, inner.x0, inner.y1),
+        Rect::new(inner.x1, inner.y0, outer.x1, inner.y1),
+    ];
+
+    let _ = rc.with_save(|rc| {
+        if antialias {
+            rc.clip(panel);
+        } else {
+            rc.clip(pixel_snapped_path(panel));
+        }
+        for side in sides {
+            rc.blurred_rect(side + shadow.offset, shadow.blur, &color);
+        }
+        Ok(())
+    });
+}
+
+/// Clip to `shape`, snapped to whole pixels if `antialias` is false.
+fn clip_to(ctx: &mut PaintCtx, shape: RoundedRect, antialias: bool) {
+    if antialias {
+        ctx.clip(shape);
+    } else {
+        ctx.clip(pixel_snapped_path(shape));
+    }
+}
+
+/// Approximate `shape` with rectangles covering whole pixels.
+///
+/// Piet has no setting to turn off antialiasing, but a shape whose edges all lie on
+/// pixel boundaries doesn't partially cover any pixel, so it's drawn without blending.
+/// Each run of pixel rows with the same horizontal extent becomes one rectangle.
+fn pixel_snapped_path(shape: RoundedRect) -> BezPath {
+    /// How far in from the straight edge a corner of `radius` is, at `distance` from
+    /// the edge it meets.
+    fn corner_inset(radius: f64, distance: f64) -> f64 {
+        if distance >= radius {
+            return 0.0;
+        }
+        let dy = radius - distance;
+        radius - (radius * radius - dy * dy).max(0.0).sqrt()
+    }
+
+    fn add_band(path: &mut BezPath, (x0, x1, y0, y1): (f64, f64, f64, f64)) {
+        if x1 > x0 && y1 > y0 {
+            path.move_to((x0, y0));
+            path.line_to((x1, y0));
+            path.line_to((x1, y1));
+            path.line_to((x0, y1));
+            path.close_path();
+        }
+    }
+
+    let rect = shape.rect();
+    let radii = shape.radii();
+    let mut path = BezPath::new();
+    let mut band: Option<(f64, f64, f64, f64)> = None;
+
+    // Each row covers the pixels whose center is inside the shape.
+    let mut y = (rect.y0 - 0.5).ceil();
+    while y + 0.5 < rect.y1 {
+        let center = y + 0.5;
+        let (from_top, from_bottom) = (center - rect.y0, rect.y1 - center);
+        let left = rect.x0
+            + corner_inset(radii.top_left, from_top)
+                .max(corner_inset(radii.bottom_left, from_bottom));
+        let right = rect.x1
+            - corner_inset(radii.top_right, from_top)
+                .max(corner_inset(radii.bottom_right, from_bottom));
+        let (left, right) = (left.round(), right.round());
+
+        band = match band {
+            Some((x0, x1, y0, _)) if x0 == left && x1 == right => Some((x0, x1, y0, y + 1.0)),
+            Some(previous) => {
+                add_band(&mut path, previous);
+                Some((left, right, y, y + 1.0))
+            }
+            None => Some((left, right, y, y + 1.0)),
+        };
+        y += 1.0;
+    }
+    if let Some(last) = band {
+        add_band(&mut path, last);
+    }
+    path
+}
+
+/// The radii of the area `insets` inside a rounded rect with the given radii.
+///
+/// Each corner shrinks by the larger of the two insets next to it.
+fn inner_corner_radii(radii: RoundedRectRadii, insets: Insets) -> RoundedRectRadii {
+    RoundedRectRadii::new(
+        (radii.top_left - insets.x0.max(insets.y0)).max(0.0),
+        (radii.top_right - insets.x1.max(insets.y0)).max(0.0),
+        (radii.bottom_right - insets.x1.max(insets.y1)).max(0.0),
+        (radii.bottom_left - insets.x0.max(insets.y1)).max(0.0),
+    )
+}
+
+/// Build corner radii from `[top_left, top_right, bottom_right, bottom_left]`.
+///
+/// Invalid radii are kept, so that [`SizedBox::try_build`] can report them, and a
+/// warning is logged. They're replaced with zero by [`valid_corner_radii`].
+fn corner_radii(radii: [f64; 4]) -> RoundedRectRadii {
+    for radius in radii
+        .into_iter()
+        .filter(|radius| is_invalid_length(*radius))
+    {
+        warn!("Invalid corner radius {}, using 0 instead.", radius);
+    }
+    let [top_left, top_right, bottom_right, bottom_left] = radii;
+    RoundedRectRadii::new(top_left, top_right, bottom_right, bottom_left)
+}
+
+/// `radii` with negative and NaN radii replaced with zero.
+fn valid_corner_radii(radii: RoundedRectRadii) -> RoundedRectRadii {
+    let valid = |radius: f64| if radius >= 0.0 { radius } else { 0.0 };
+    RoundedRectRadii::new(
+        valid(radii.top_left),
+        valid(radii.top_right),
+        valid(radii.bottom_right),
+        valid(radii.bottom_left),
+    )
+}
+
+impl From<StyleBundle> for KeyOrValue<Arc<StyleBundle>> {
+    fn from(style: StyleBundle) -> Self {
+        KeyOrValue::Concrete(Arc::new(style))
+    }
+}
+
+// --- SharedSize ---
+
+impl SharedSize {
+    /// Create a handle which isn't shared with any box yet.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The current shared width: the widest of the boxes using this handle.
+    ///
+    /// This is `0.0` until one of them has been laid out.
+    pub fn width(&self) -> f64 {
+        Self::max_width(&self.widths.borrow())
+    }
+
+    fn max_width(widths: &HashMap<WidgetId, f64>) -> f64 {
+        widths.values().copied().fold(0.0, f64::max)
+    }
+
+    /// Record the width the current widget would like, and return the shared width.
+    ///
+    /// If this changes the shared width, the other boxes are told to lay out again.
+    fn contribute(&self, ctx: &mut LayoutCtx, width: f64) -> f64 {
+        let own_id = ctx.widget_id();
+        let mut widths = self.widths.borrow_mut();
+        let old_width = Self::max_width(&widths);
+        widths.insert(own_id, width);
+        let new_width = Self::max_width(&widths);
+        if new_width != old_width {
+            for id in widths.keys().filter(|id| **id != own_id) {
+                ctx.submit_command(SHARED_SIZE_CHANGED.to(*id));
+            }
+        }
+        new_width
+    }
+
+    fn remove(&self, id: WidgetId) {
+        self.widths.borrow_mut().remove(&id);
+    }
+}
+
+impl SharedWidth {
+    fn new(handle: SharedSize) -> Self {
+        Self { handle, id: None }
+    }
+
+    /// Record the width the current widget would like, and return the shared width.
+    fn contribute(&mut self, ctx: &mut LayoutCtx, width: f64) -> f64 {
+        self.id = Some(ctx.widget_id());
+        self.handle.contribute(ctx, width)
+    }
+}
+
+impl Drop for SharedWidth {
+    fn drop(&mut self) {
+        if let Some(id) = self.id {
+            self.handle.remove(id);
+        }
+    }
+}
+
+impl Data for StyleBundle {
+    fn same(&self, other: &Self) -> bool {
+        self.background.same(&other.background)
+            && self.brush == other.brush
+            && self.border_color.same(&other.border_color)
+            && self.border_width.same(&other.border_width)
+            && self.corner_radius.same(&other.corner_radius)
+    }
+}
+
+impl StyleBrush {
+    /// Whether this brush always covers the box with opaque colors.
+    ///
+    /// Only solid colors are known to be opaque.
+    fn is_opaque(&self) -> bool {
+        match self {
+            Self::Brush(PaintBrush::Color(color)) => color.as_rgba8().3 == u8::MAX,
+            _ => false,
+        }
+    }
+
+    fn paint(&self, ctx: &mut PaintCtx, env: &Env) {
+        match self {
+            Self::Brush(brush) => {
+                let bounds = ctx.size().to_rect();
+                ctx.fill(bounds, brush);
+            }
+            Self::Painter(painter) => painter(ctx, env),
+        }
+    }
+
+    /// Feed this brush's settings to `state`.
+    fn hash(&self, env: &Env, state: &mut impl Hasher) {
+        match self {
+            Self::Brush(brush) => BackgroundBrush::from(brush.clone()).hash_resolved(env, state),
+            Self::Painter(_) => u8::MAX.hash(state),
+        }
+    }
+}
+
+impl PartialEq for StyleBrush {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Self::Brush(a), Self::Brush(b)) => {
+                BackgroundBrush::from(a.clone()) == BackgroundBrush::from(b.clone())
+            }
+            _ => false,
+        }
+    }
+}
+
+impl std::fmt::Debug for StyleBrush {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Brush(brush) => f.debug_tuple("Brush").field(brush).finish(),
+            Self::Painter(_) => f.write_str("Painter"),
+        }
+    }
+}
+
+// --- BorderStyle ---
+
+pub(crate) fn check_border_width(width: f64) -> Result<f64, BorderError> {
+    if width.is_nan() {
+        Err(BorderError::NanWidth)
+    } else if width < 0.0 {
+        Err(BorderError::NegativeWidth(width))
+    } else {
+        Ok(width)
+    }
+}
+
+impl EdgeFade {
+    /// Build a fade, or `None` if there's nothing to fade or with a warning if `length` is invalid.
+    fn new(sides: Sides, length: f64) -> Option<Self> {
+        if !(length.is_finite() && length > 0.0) {
+            warn!(
+                "Invalid edge fade length {}, the child will not fade.",
+                length
+            );
+            return None;
+        }
+        (sides != Sides::NONE).then_some(EdgeFade { sides, length })
+    }
+
+    /// Mask `image`, a bitmap of the whole box with premultiplied alpha at `scale`.
+    fn apply(&self, image: &ImageBuf, scale: f64) -> ImageBuf {
+        let (width, height) = (image.width(), image.height());
+        // How visible the pixel at `index` is, given it's `count` pixels long on its axis.
+        let ramp = |index: usize, count: usize, from_start: bool, from_end: bool| {
+            let center = (index as f64 + 0.5) / scale;
+            let end = count as f64 / scale - center;
+            let mut alpha = 1.0;
+            if from_start {
+                alpha *= (center / self.length).min(1.0);
+            }
+            if from_end {
+                alpha *= (end / self.length).min(1.0);
+            }
+            alpha
+        };
+        let columns: Vec<f64> = (0..width)
+            .map(|x| ramp(x, width, self.sides.left, self.sides.right))
+            .collect();
+
+        let bytes_per_pixel = image.format().bytes_per_pixel();
+        let mut pixels = image.raw_pixels().to_vec();
+        for (y, row) in pixels.chunks_mut(width * bytes_per_pixel).enumerate() {
+            let row_alpha = ramp(y, height, self.sides.top, self.sides.bottom);
+            for (pixel, column_alpha) in row.chunks_mut(bytes_per_pixel).zip(&columns) {
+                let alpha = row_alpha * column_alpha;
+                if alpha < 1.0 {
+                    // With premultiplied alpha, scaling every channel scales the opacity.
+                    for channel in pixel {
+                        *channel = (*channel as f64 * alpha).round() as u8;
+                    }
+                }
+            }
+        }
+        ImageBuf::from_raw(pixels, image.format(), width, height)
+    }
+}
+
+impl BorderStyle {
+    /// Build a border, warning about an invalid concrete width.
+    ///
+    /// The width is kept as given, so that [`SizedBox::try_build`] can report it; it's
+    /// treated as zero when resolved.
+    fn new(color: KeyOrValue<Color>, width: KeyOrValue<f64>) -> Self {
+        if let KeyOrValue::Concrete(value) = width {
+            match check_border_width(value) {
+                Ok(_) => {}
+                Err(BorderError::NegativeWidth(value)) => {
+                    warn!("Negative border width {}, using 0 instead.", value);
+                }
+                Err(BorderError::NanWidth) => {
+                    warn!("NaN border width, the border will not be painted.");
+                }
+            }
+        }
+        BorderStyle {
+            width,
+            color,
+            hairline: false,
+            warned_invalid_width: Cell::new(false),
+        }
+    }
+
+    /// Build a border one device pixel wide.
+    fn hairline(color: KeyOrValue<Color>) -> Self {
+        BorderStyle {
+            width: 1.0.into(),
+            color,
+            hairline: true,
+            warned_invalid_width: Cell::new(false),
+        }
+    }
+
+    /// Resolve the width, treating invalid values as zero.
+    ///
+    /// An invalid width from the env is reported once; concrete widths were already
+    /// reported by `new`.
+    fn resolve_width(&self, env: &Env) -> f64 {
+        let width = self.width.resolve(env);
+        match check_border_width(width) {
+            Ok(width) => width,
+            Err(err) => {
+                if let KeyOrValue::Key(_) = self.width {
+                    if !self.warned_invalid_width.replace(true) {
+                        warn!("Invalid border width: {}", err);
+                    }
+                }
+                0.0
+            }
+        }
+    }
+}
+
+impl std::fmt::Display for BorderError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            BorderError::NegativeWidth(width) => write!(f, "Negative border width: {}", width),
+            BorderError::NanWidth => write!(f, "Border width is NaN"),
+        }
+    }
+}
+
+impl std::error::Error for BorderError {}
+
+impl std::fmt::Display for BoxConfigError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            BoxConfigError::InvalidWidth(width) => write!(f, "Invalid width: {}", width),
+            BoxConfigError::InvalidHeight(height) => write!(f, "Invalid height: {}", height),
+            BoxConfigError::Border(err) => write!(f, "{}", err),
+            BoxConfigError::InvalidCornerRadius(radius) => {
+                write!(f, "Invalid corner radius: {}", radius)
+            }
+        }
+    }
+}
+
+impl std::error::Error for BoxConfigError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            BoxConfigError::Border(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+/// Linearly interpolate between two colors, including their alpha.
+fn lerp_color(from: Color, to: Color, t: f64) -> Color {
+    let (r0, g0, b0, a0) = from.as_rgba();
+    let (r1, g1, b1, a1) = to.as_rgba();
+    let lerp = |a: f64, b: f64| a + (b - a) * t;
+    Color::rgba(lerp(r0, r1), lerp(g0, g1), lerp(b0, b1), lerp(a0, a1))
+}
+
+fn validate_transform(transform: Affine) -> Option<Affine> {
+    let determinant = transform.determinant();
+    if determinant == 0.0 || !determinant.is_finite() {
+        warn!("Transform {:?} can't be inverted, ignoring it.", transform);
+        None
+    } else {
+        Some(transform)
+    }
+}
+
+/// `event` with its position moved from a box's coordinates to those of its child
+/// transformed by `transform`, if it's a mouse event.
+fn untransform_mouse(event: &Event, transform: Affine) -> Option<Event> {
+    let untransform = |mouse: &MouseEvent| {
+        let mut mouse = mouse.clone();
+        mouse.pos = transform.inverse() * mouse.pos;
+        mouse
+    };
+    match event {
+        Event::MouseDown(mouse) => Some(Event::MouseDown(untransform(mouse))),
+        Event::MouseUp(mouse) => Some(Event::MouseUp(untransform(mouse))),
+        Event::MouseMove(mouse) => Some(Event::MouseMove(untransform(mouse))),
+        Event::Wheel(mouse) => Some(Event::Wheel(untransform(mouse))),
+        _ => None,
+    }
+}
+
+/// The transform applied to the child of a box of the given size, if any.
+///
+/// This is `transform` followed by a rotation by `radians` around the center of the box.
+fn child_transform(transform: Option<Affine>, radians: f64, size: Size) -> Option<Affine> {
+    if radians == 0.0 {
+        return transform;
+    }
+    let center = size.to_rect().center().to_vec2();
+    let rotation = Affine::translate(center) * Affine::rotate(radians) * Affine::translate(-center);
+    Some(rotation * transform.unwrap_or(Affine::IDENTITY))
+}
+
+fn validate_rotation(radians: f64) -> f64 {
+    if radians.is_finite() {
+        radians
+    } else {
+        warn!("Invalid rotation {}, ignoring it.", radians);
+        0.0
+    }
+}
+
+fn validate_opacity(opacity: f64) -> f64 {
+    if opacity.is_nan() {
+        warn!("Invalid opacity {}, using 1.0 instead.", opacity);
+        1.0
+    } else {
+        opacity.clamp(0.0, 1.0)
+    }
+}
+
+/// A copy of `image` with every pixel faded by `opacity`.
+///
+/// `image` must have premultiplied alpha, so every channel is faded alike.
+fn fade_image(image: &ImageBuf, opacity: f64) -> ImageBuf {
+    let pixels: Vec<u8> = image
+        .raw_pixels()
+        .iter()
+        .map(|channel| (*channel as f64 * opacity).round() as u8)
+        .collect();
+    ImageBuf::from_raw(pixels, image.format(), image.width(), image.height())
+}
+
+// --- Tests ---
+
+#[cfg(test)]
+mod tests {
+    use std::cell::{Cell, RefCell};
+    use std::rc::Rc;
+
+    use druid_shell::MouseButton;
+    use insta::assert_debug_snapshot;
+
+    use instant::Duration;
+
+    use super::*;
+    use crate::assert_render_snapshot;
+    use crate::piet::{ImageFormat, LinearGradient, UnitPoint};
+    use crate::shell::{KbKey, KeyEvent, RawMods, SysMods};
+    use crate::testing::{
+        pixel_at, widget_ids, ModularWidget, Record, Recording, TestHarness, TestWidgetExt as _,
+    };
+    use crate::text::{FontDescriptor, FontFamily};
+    use crate::widget::{
+        Button, ColorSpace, CursorChange, CustomBackground, Flex, Label, StateStyle,
+    };
+    use crate::{Action, InputModality};
+
+    const FOCUS_TABBER: Selector = Selector::new("masonry-test.focus-tabber");
+
+    /// A focusable widget which moves focus to the next widget on Tab, like a text box does.
+    fn tabber(size: Size) -> ModularWidget<()> {
+        ModularWidget::new(())
+            .lifecycle_fn(|_, ctx, event, _| {
+                if let LifeCycle::BuildFocusChain = event {
+                    ctx.register_for_focus();
+                }
+            })
+            .event_fn(|_, ctx, event, _| match event {
+                Event::Command(cmd) if cmd.is(FOCUS_TABBER) => ctx.request_focus(),
+                Event::KeyDown(key) if key.key == KbKey::Tab => ctx.focus_next(),
+                _ => (),
+            })
+            .layout_fn(move |_, _, bc, _| bc.constrain(size))
+    }
+
+    /// Send an animation frame lasting `duration` if one was requested, then move the
+    /// timers forward by the same amount.
+    fn advance_frame(harness: &mut TestHarness, duration: Duration) {
+        if harness.window().wants_animation_frame() {
+            harness.process_event(Event::AnimFrame(duration.as_nanos() as u64));
+        }
+        harness.move_timers_forward(duration);
+    }
+
+    /// Focuses the [`tabber`] without the keyboard, then presses Tab.
+    fn tab_from_tabber(harness: &mut TestHarness) {
+        harness.submit_command(FOCUS_TABBER);
+        let tab = KeyEvent::for_test(RawMods::None, KbKey::Tab);
+        harness.process_event(Event::KeyDown(tab));
+    }
+
+    #[test]
+    fn expand() {
+        let expand = SizedBox::new(Label::new("hello!")).expand();
+        let bc = BoxConstraints::tight(Size::new(400., 400.)).loosen();
+        let child_bc = expand.child_constraints(&bc, Insets::ZERO);
+        assert_eq!(child_bc.min(), Size::new(400., 400.,));
+    }
+
+    #[test]
+    fn no_width() {
+        let expand = SizedBox::new(Label::new("hello!")).height(200.);
+        let bc = BoxConstraints::tight(Size::new(400., 400.)).loosen();
+        let child_bc = expand.child_constraints(&bc, Insets::ZERO);
+        assert_eq!(child_bc.min(), Size::new(0., 200.,));
+        assert_eq!(child_bc.max(), Size::new(400., 200.,));
+    }
+
+    #[test]
+    fn loosen_child() {
+        let sized_box = SizedBox::new(Label::new("hello!")).loosen_child(true);
+        let bc = BoxConstraints::tight(Size::new(400., 400.));
+        let child_bc = sized_box.child_constraints(&bc, Insets::ZERO);
+        assert_eq!(child_bc.min(), Size::ZERO);
+        assert_eq!(child_bc.max(), Size::new(400., 400.));
+
+        let sized_box = sized_box.width(100.);
+        let child_bc = sized_box.child_constraints(&bc.loosen(), Insets::ZERO);
+        assert_eq!(child_bc.min(), Size::ZERO);
+        assert_eq!(child_bc.max(), Size::new(100., 400.));
+    }
+
+    #[test]
+    fn loosen_child_centers_child() {
+        let [label_id] = widget_ids();
+        let widget = SizedBox::new_with_id(Label::new("hello"), label_id).loosen_child(true);
+
+        let harness = TestHarness::create_with_size(widget, Size::new(400., 400.));
+
+        let box_rect = harness.root_widget().state().layout_rect();
+        assert_eq!(box_rect.size(), Size::new(400., 400.));
+
+        let label_rect = harness.get_widget(label_id).state().layout_rect();
+        assert!(label_rect.width() < 400.);
+        assert!(label_rect.height() < 400.);
+        assert!((label_rect.center() - box_rect.center()).hypot() < 1e-9);
+    }
+
+    #[test]
+    fn child_reads_corner_radii() {
+        let radii = Rc::new(Cell::new(RoundedRectRadii::from_single_radius(0.0)));
+        let child = ModularWidget::new(radii.clone())
+            .layout_fn(|radii, ctx, bc, _| {
+                radii.set(ctx.parent_corner_radii());
+                bc.max()
+            })
+            .paint_fn(|_, ctx, _| {
+                // The child clips itself to the same shape as its parent.
+                let clip = ctx.size().to_rounded_rect(ctx.parent_corner_radii());
+                ctx.clip(clip);
+                let bounds = ctx.size().to_rect();
+                ctx.fill(bounds, &Color::RED);
+            });
+        let widget = SizedBox::new(child)
+            .width(40.0)
+            .height(40.0)
+            .rounded_each([10.0, 4.0, 0.0, 1.0])
+            .border(Color::WHITE, 2.0);
+
+        let mut harness = TestHarness::create_with_size(widget, Size::new(60.0, 60.0));
+
+        assert_eq!(radii.get(), RoundedRectRadii::new(8.0, 2.0, 0.0, 0.0));
+
+        // The child's square corner is filled, its rounded ones are clipped.
+        let size = harness.root_widget().state().layout_rect().size();
+        let (w, h) = (size.width as usize, size.height as usize);
+        let image = harness.render();
+        let red = [255, 0, 0, 255];
+        assert_eq!(pixel_at(&image, 60, w / 2, h / 2), red);
+        assert_eq!(pixel_at(&image, 60, w - 3, h - 3), red);
+        assert_ne!(pixel_at(&image, 60, 3, 3), red);
+    }
+
+    #[test]
+    fn z_index_orders_hit_testing() {
+        fn clicked(z_index: i32) -> Vec<&'static str> {
+            let clicks = Rc::new(RefCell::new(Vec::new()));
+            let make_box = |name: &'static str, z_index: i32| {
+                let leaf =
+                    ModularWidget::new(clicks.clone()).event_fn(move |clicks, ctx, event, _| {
+                        if let Event::MouseDown(_) = event {
+                            clicks.borrow_mut().push(name);
+                            ctx.set_handled();
+                        }
+                    });
+                let sized_box = SizedBox::new(leaf).width(40.0).height(40.0);
+                WidgetPod::new(sized_box.z_index(z_index))
+            };
+            // Both boxes are placed at the origin, and overlap entirely.
+            let children = vec![make_box("first", 0), make_box("second", z_index)];
+            let stack = ModularWidget::new(children)
+                .event_fn(|children, ctx, event, env| {
+                    ctx.dispatch_by_z_index(children.iter_mut(), event, env);
+                })
+                .lifecycle_fn(|children, ctx, event, env| {
+                    for child in children {
+                        child.lifecycle(ctx, event, env);
+                    }
+                })
+                .layout_fn(|children, ctx, bc, env| {
+                    let mut size = Size::ZERO;
+                    for child in children {
+                        size = child.layout(ctx, bc, env);
+                        ctx.place_child(child, Point::ZERO, env);
+                    }
+                    size
+                })
+                .paint_fn(|children, ctx, env| {
+                    for child in children {
+                        child.paint(ctx, env);
+                    }
+                })
+                .children_fn(|children| children.iter().map(|child| child.as_dyn()).collect());
+
+            let mut harness = TestHarness::create_with_size(stack, Size::new(100.0, 100.0));
+            harness.mouse_move(Point::new(20.0, 20.0));
+            harness.mouse_button_press(MouseButton::Left);
+
+            let clicks = clicks.borrow().clone();
+            clicks
+        }
+
+        assert_eq!(clicked(0), vec!["first"]);
+        assert_eq!(clicked(1), vec!["second"]);
+    }
+
+    #[test]
+    fn config_hash() {
+        let env = Env::with_theme();
+        let make_box = || {
+            SizedBox::empty()
+                .width(40.0)
+                .background(Color::RED)
+                .border(Color::WHITE, 2.0)
+                .rounded(4.0)
+        };
+        let hash = make_box().config_hash(&env);
+
+        assert_eq!(make_box().config_hash(&env), hash);
+        // Settings that don't affect the style don't change the hash.
+        assert_eq!(make_box().z_index(3).config_hash(&env), hash);
+        assert_ne!(make_box().border(Color::WHITE, 3.0).config_hash(&env), hash);
+
+        // Gradients are hashed stop by stop.
+        let rotating = |end_color| {
+            make_box()
+                .background(BackgroundBrush::rotating_linear(
+                    (Color::RED, end_color),
+                    Duration::from_secs(1),
+                ))
+                .config_hash(&env)
+        };
+        assert_eq!(rotating(Color::BLUE), rotating(Color::BLUE));
+        assert_ne!(rotating(Color::BLUE), rotating(Color::GREEN));
+
+        // Painter functions are opaque.
+        let painter = |_: &mut PaintCtx, _: &Env| {};
+        let painted_1 = make_box().background(BackgroundBrush::PainterFn(Box::new(painter)));
+        let painted_2 = make_box().background(BackgroundBrush::PainterFn(Box::new(painter)));
+        assert_eq!(painted_1.config_hash(&env), painted_2.config_hash(&env));
+        assert_ne!(painted_1.config_hash(&env), hash);
+    }
+
+    #[test]
+    fn hover_repaint_skips_child() {
+        fn child_painted_on_hover(repaint_child: Option<bool>) -> bool {
+            let recording = Recording::default();
+            let child = SizedBox::empty()
+                .width(20.0)
+                .height(20.0)
+                .record(&recording);
+            let mut widget = SizedBox::new(child)
+                .width(60.0)
+                .height(60.0)
+                .loosen_child(true)
+                .background(Color::BLACK)
+                .hover_background(Color::RED);
+            if let Some(repaint_child) = repaint_child {
+                widget = widget.hover_repaints_child(repaint_child);
+            }
+
+            let mut harness = TestHarness::create_with_size(widget, Size::new(60.0, 60.0));
+            let _ = harness.render();
+            recording.clear();
+
+            // The pointer is over the box, but not over the child.
+            harness.mouse_move(Point::new(5.0, 5.0));
+            let _ = harness.render();
+            let records = recording.drain();
+            records.iter().any(|record| matches!(record, Record::Paint))
+        }
+
+        // By default, only the background around the child is repainted.
+        assert!(!child_painted_on_hover(None));
+        assert!(child_painted_on_hover(Some(true)));
+    }
+
+    #[test]
+    fn antialias() {
+        fn distinct_colors(antialias: bool) -> usize {
+            let widget = SizedBox::empty()
+                .width(40.0)
+                .height(40.0)
+                .background(Color::RED)
+                .border(Color::WHITE, 3.0)
+                .rounded(12.0)
+                .antialias(antialias);
+            let mut harness = TestHarness::create_with_size(widget, Size::new(40.0, 40.0));
+            let image = harness.render();
+            let mut colors: Vec<&[u8]> = image.chunks(4).collect();
+            colors.sort();
+            colors.dedup();
+            colors.len()
+        }
+
+        // Without antialiasing, there is no blending between the window background,
+        // the border and the background.
+        assert_eq!(distinct_colors(false), 3);
+        assert!(distinct_colors(true) > 3);
+
+        let widget = SizedBox::empty()
+            .width(40.0)
+            .height(40.0)
+            .background(Color::RED)
+            .rounded(12.0)
+            .antialias(false);
+        let mut harness = TestHarness::create_with_size(widget, Size::new(40.0, 40.0));
+        let image = harness.render();
+        let red = [255, 0, 0, 255];
+        let background = pixel_at(&image, 40, 0, 0);
+        assert_ne!(background, red);
+        assert_eq!(pixel_at(&image, 40, 20, 20), red);
+        // Every pixel is either fully inside the rounded rect, or fully outside.
+        assert!(image
+            .chunks(4)
+            .all(|pixel| pixel == red || pixel == background));
+    }
+
+    #[test]
+    fn padding() {
+        let [box_id, child_id] = widget_ids();
+        let child = SizedBox::empty().width(20.0).height(20.0);
+        let sized_box = SizedBox::new_with_id(child, child_id)
+            .padding(Insets::new(1.0, 2.0, 3.0, 4.0))
+            .border(Color::WHITE, 1.0);
+        let widget = Flex::column().with_child_id(sized_box, box_id);
+
+        let harness = TestHarness::create_with_size(widget, Size::new(100.0, 100.0));
+        let box_rect = harness.get_widget(box_id).state().layout_rect();
+        let child_rect = harness.get_widget(child_id).state().layout_rect();
+
+        assert_eq!(box_rect.size(), Size::new(26.0, 28.0));
+        assert_eq!(child_rect.origin(), Point::new(2.0, 3.0));
+    }
+
+    #[test]
+    fn clip_child_overflow() {
+        let [box_id, child_id] = widget_ids();
+        let child = SizedBox::empty().width(100.0).height(10.0);
+        let sized_box = SizedBox::new_with_id(child, child_id)
+            .width(20.0)
+            .height(20.0)
+            .clip_child(true);
+        let widget = Flex::column().with_child_id(sized_box, box_id);
+
+        let harness = TestHarness::create_with_size(widget, Size::new(200.0, 200.0));
+        let box_state = harness.get_widget(box_id).state();
+        let child_rect = harness.get_widget(child_id).state().layout_rect();
+
+        assert_eq!(box_state.layout_rect().size(), Size::new(20.0, 20.0));
+        assert_eq!(child_rect.size(), Size::new(100.0, 20.0));
+        assert_eq!(box_state.content_overflow, Insets::new(0.0, 0.0, 80.0, 0.0));
+    }
+
+    #[test]
+    fn clip_child_expanding_child() {
+        let [child_id] = widget_ids();
+        let child = ModularWidget::new(()).layout_fn(|_, _, bc, _| bc.max());
+        let widget = SizedBox::new(child.with_id(child_id))
+            .width(20.0)
+            .height(20.0)
+            .clip_child(true);
+
+        // The child is laid out again, within the box's size.
+        let harness = TestHarness::create_with_size(widget, Size::new(20.0, 20.0));
+        let child_size = harness.get_widget(child_id).state().layout_rect().size();
+        assert_eq!(child_size, Size::new(20.0, 20.0));
+    }
+
+    #[test]
+    fn intrinsic_width() {
+        let [short_box_id, long_box_id, short_label_id, long_label_id] = widget_ids();
+        let measured = |text| {
+            SizedBox::new(Label::new(text))
+                .fill_width()
+                .measure_intrinsic_width(true)
+        };
+        let widget = Flex::column()
+            .with_child_id(measured("Hi"), short_box_id)
+            .with_child_id(measured("Hello, world!"), long_box_id)
+            .with_child_id(Label::new("Hi"), short_label_id)
+            .with_child_id(Label::new("Hello, world!"), long_label_id);
+
+        let harness = TestHarness::create_with_size(widget, Size::new(200.0, 200.0));
+        let env = Env::with_theme();
+        let intrinsic_width = |id| {
+            let sized_box = harness.get_widget(id);
+            let sized_box = sized_box.downcast::<SizedBox>().unwrap();
+            sized_box.deref().intrinsic_width(&env)
+        };
+        let short_label_rect = harness.get_widget(short_label_id).state().layout_rect();
+        let long_label_rect = harness.get_widget(long_label_id).state().layout_rect();
+        let long_box_rect = harness.get_widget(long_box_id).state().layout_rect();
+
+        assert_eq!(intrinsic_width(short_box_id), short_label_rect.width());
+        assert_eq!(intrinsic_width(long_box_id), long_label_rect.width());
+        assert!(intrinsic_width(short_box_id) < intrinsic_width(long_box_id));
+        assert_eq!(long_box_rect.width(), 200.0);
+    }
+
+    #[test]
+    fn constrain_intersects() {
+        let sized_box = SizedBox::empty().constrain(BoxConstraints::new(
+            Size::new(50.0, 50.0),
+            Size::new(150.0, 150.0),
+        ));
+        let bc = BoxConstraints::new(Size::ZERO, Size::new(100.0, 100.0));
+        let child_bc = sized_box.child_constraints(&bc, Insets::ZERO);
+
+        assert_eq!(child_bc.min(), Size::new(50.0, 50.0));
+        assert_eq!(child_bc.max(), Size::new(100.0, 100.0));
+    }
+
+    #[test]
+    fn constrain_invalid() {
+        let sized_box = SizedBox::empty().constrain(BoxConstraints::new(
+            Size::new(80.0, 20.0),
+            Size::new(40.0, 60.0),
+        ));
+        let child_bc = sized_box.child_constraints(&BoxConstraints::UNBOUNDED, Insets::ZERO);
+
+        assert_eq!(child_bc.min(), Size::new(40.0, 20.0));
+        assert_eq!(child_bc.max(), Size::new(40.0, 60.0));
+    }
+
+    #[test]
+    fn constrain_layout() {
+        let [box_id] = widget_ids();
+        let sized_box = SizedBox::empty().constrain(BoxConstraints::new(
+            Size::new(50.0, 50.0),
+            Size::new(150.0, 150.0),
+        ));
+        let widget = Flex::column().with_child_id(sized_box, box_id);
+
+        let harness = TestHarness::create_with_size(widget, Size::new(100.0, 100.0));
+        let box_rect = harness.get_widget(box_id).state().layout_rect();
+
+        assert_eq!(box_rect.size(), Size::new(50.0, 50.0));
+    }
+
+    #[test]
+    fn fill_bounded() {
+        let fill = SizedBox::new(Label::new("hello!")).fill();
+        let bc = BoxConstraints::tight(Size::new(400., 400.)).loosen();
+        let child_bc = fill.child_constraints(&bc, Insets::ZERO);
+        assert_eq!(child_bc.min(), Size::new(400., 400.,));
+        assert_eq!(child_bc.max(), Size::new(400., 400.,));
+    }
+
+    #[test]
+    fn fill_unbounded() {
+        let fill = SizedBox::new(Label::new("hello!")).fill();
+        let bc = BoxConstraints::UNBOUNDED;
+        let child_bc = fill.child_constraints(&bc, Insets::ZERO);
+        assert_eq!(child_bc.min(), Size::ZERO);
+        assert!(!child_bc.is_width_bounded());
+        assert!(!child_bc.is_height_bounded());
+    }
+
+    #[test]
+    fn fill_width_fixed_height() {
+        let fill = SizedBox::new(Label::new("hello!")).fill_width().height(50.);
+        let bc = BoxConstraints::new(Size::ZERO, Size::new(400., 400.));
+        let child_bc = fill.child_constraints(&bc, Insets::ZERO);
+        assert_eq!(child_bc.min(), Size::new(400., 50.));
+        assert_eq!(child_bc.max(), Size::new(400., 50.));
+
+        let bc = BoxConstraints::new(Size::ZERO, Size::new(400., f64::INFINITY));
+        let child_bc = fill.child_constraints(&bc, Insets::ZERO);
+        assert_eq!(child_bc.min(), Size::new(400., 50.));
+        assert_eq!(child_bc.max(), Size::new(400., 50.));
+    }
+
+    #[test]
+    fn fill_height_keeps_intrinsic_width() {
+        let [label_box_id] = widget_ids();
+        // A row gives its children unbounded constraints on the horizontal axis.
+        let widget = Flex::row().with_child_id(
+            SizedBox::new(Label::new("hello")).fill_height(),
+            label_box_id,
+        );
+
+        let harness = TestHarness::create_with_size(widget, Size::new(400., 400.));
+
+        let label_box_rect = harness.get_widget(label_box_id).state().layout_rect();
+        assert_eq!(label_box_rect.height(), 400.);
+        assert!(label_box_rect.width().is_finite());
+        assert!(label_box_rect.width() > 0.);
+        assert!(label_box_rect.width() < 400.);
+    }
+
+    #[test]
+    fn fill_width_in_column() {
+        let [box_id] = widget_ids();
+        let widget =
+            Flex::column().with_child_id(SizedBox::empty().fill_width().height(50.), box_id);
+
+        let harness = TestHarness::create_with_size(widget, Size::new(400., 400.));
+
+        let box_size = harness.get_widget(box_id).state().layout_rect().size();
+        assert_eq!(box_size, Size::new(400., 50.));
+    }
+
+    #[test]
+    fn fill_stays_finite() {
+        let [label_box_id, empty_box_id] = widget_ids();
+        // A column gives its children unbounded constraints on the vertical axis.
+        let widget = Flex::column()
+            .with_child_id(SizedBox::new(Label::new("hello")).fill(), label_box_id)
+            .with_child_id(SizedBox::empty().fill(), empty_box_id);
+
+        let harness = TestHarness::create_with_size(widget, Size::new(400., 400.));
+
+        let label_box_rect = harness.get_widget(label_box_id).state().layout_rect();
+        assert_eq!(label_box_rect.width(), 400.);
+        assert!(label_box_rect.height().is_finite());
+        assert!(label_box_rect.height() > 0.);
+
+        let empty_box_rect = harness.get_widget(empty_box_id).state().layout_rect();
+        assert_eq!(empty_box_rect.size(), Size::new(400., 0.));
+    }
+
+    #[test]
+    fn empty_box() {
+        let widget = SizedBox::empty()
+            .width(40.0)
+            .height(40.0)
+            .border(Color::BLUE, 5.0)
+            .rounded(5.0);
+
+        let mut harness = TestHarness::create(widget);
+
+        assert_debug_snapshot!(harness.root_widget());
+        assert_render_snapshot!(harness, "empty_box");
+    }
+
+    #[test]
+    fn label_box_no_size() {
+        let widget = SizedBox::new(Label::new("hello"))
+            .border(Color::BLUE, 5.0)
+            .rounded(5.0);
+
+        let mut harness = TestHarness::create(widget);
+
+        assert_debug_snapshot!(harness.root_widget());
+        assert_render_snapshot!(harness, "label_box_no_size");
+    }
+
+    #[test]
+    fn label_box_with_size() {
+        let widget = SizedBox::new(Label::new("hello"))
+            .width(40.0)
+            .height(40.0)
+            .border(Color::BLUE, 5.0)
+            .rounded(5.0);
+
+        let mut harness = TestHarness::create(widget);
+
+        assert_debug_snapshot!(harness.root_widget());
+        assert_render_snapshot!(harness, "label_box_no_size");
+    }
+
+    #[test]
+    fn invalid_border() {
+        let no_border_image = {
+            let widget = SizedBox::empty().width(40.0).height(40.0);
+            let mut harness = TestHarness::create_with_size(widget, Size::new(40.0, 40.0));
+            harness.render()
+        };
+
+        let negative_border = SizedBox::empty()
+            .width(40.0)
+            .height(40.0)
+            .border(Color::RED, -3.0);
+        let mut harness = TestHarness::create_with_size(negative_border, Size::new(40.0, 40.0));
+        let negative_border_image = harness.render();
+
+        let nan_border = SizedBox::empty()
+            .width(40.0)
+            .height(40.0)
+            .border(Color::RED, f64::NAN);
+        let mut harness = TestHarness::create_with_size(nan_border, Size::new(40.0, 40.0));
+        let nan_border_image = harness.render();
+
+        // We don't use assert_eq because we don't want rich assert
+        assert!(negative_border_image == no_border_image);
+        assert!(nan_border_image == no_border_image);
+    }
+
+    #[test]
+    fn negative_border_layout() {
+        let [label_id] = widget_ids();
+        let widget = SizedBox::new_with_id(Label::new("hello"), label_id).border(Color::RED, -3.0);
+
+        let harness = TestHarness::create(widget);
+        let label_rect = harness.get_widget(label_id).state().layout_rect();
+        assert_eq!(label_rect.origin(), Point::ORIGIN);
+    }
+
+    #[test]
+    fn try_border() {
+        let sized_box = SizedBox::empty().try_border(Color::RED, 3.0);
+        assert!(sized_box.is_ok());
+
+        let sized_box = SizedBox::empty().try_border(Color::RED, -3.0);
+        assert_eq!(sized_box.err(), Some(BorderError::NegativeWidth(-3.0)));
+
+        let sized_box = SizedBox::empty().try_border(Color::RED, f64::NAN);
+        assert_eq!(sized_box.err(), Some(BorderError::NanWidth));
+    }
+
+    #[test]
+    fn label_box_layout_tree() {
+        let [label_id] = widget_ids();
+        let widget = SizedBox::new_with_id(Label::new("hello"), label_id)
+            .width(40.0)
+            .height(40.0)
+            .border(Color::BLUE, 5.0);
+
+        let harness = TestHarness::create(widget);
+        let layout_tree = harness.layout_tree();
+
+        assert_eq!(layout_tree.type_name, "SizedBox");
+        assert_eq!(layout_tree.layout_rect.size(), Size::new(40.0, 40.0));
+        assert_eq!(layout_tree.children.len(), 1);
+
+        let label_node = layout_tree.find(label_id).unwrap();
+        assert_eq!(label_node.type_name, "Label");
+        assert_eq!(label_node.layout_rect, Rect::new(5.0, 5.0, 35.0, 35.0));
+        assert!(label_node.children.is_empty());
+
+        let json = serde_json::to_value(&layout_tree).unwrap();
+        assert_eq!(json["type_name"], "SizedBox");
+        assert_eq!(json["children"][0]["type_name"], "Label");
+        assert_eq!(
+            json["children"][0]["layout_rect"],
+            serde_json::json!([5.0, 5.0, 35.0, 35.0])
+        );
+    }
+
+    #[test]
+    fn disabled_colors() {
+        let disabled_image = {
+            let widget = SizedBox::empty()
+                .width(40.0)
+                .height(40.0)
+                .background(theme::DISABLED_BACKGROUND_COLOR)
+                .border(theme::DISABLED_BORDER_COLOR, 5.0);
+            let mut harness = TestHarness::create_with_size(widget, Size::new(40.0, 40.0));
+            harness.render()
+        };
+
+        let widget = SizedBox::empty()
+            .width(40.0)
+            .height(40.0)
+            .background(Color::RED)
+            .border(Color::BLUE, 5.0);
+        let mut harness = TestHarness::create_with_size(widget, Size::new(40.0, 40.0));
+        let normal_image = harness.render();
+
+        harness.edit_root_widget(|mut sized_box, _| {
+            let mut sized_box = sized_box.downcast::<SizedBox>().unwrap();
+            sized_box.ctx.set_disabled(true);
+        });
+        // We don't use assert_eq because we don't want rich assert
+        assert!(harness.render() == disabled_image);
+
+        harness.edit_root_widget(|mut sized_box, _| {
+            let mut sized_box = sized_box.downcast::<SizedBox>().unwrap();
+            sized_box.ctx.set_disabled(false);
+        });
+        assert!(harness.render() == normal_image);
+    }
+
+    #[test]
+    fn brush_paint_ignores_disabled() {
+        // The disabled color is chosen by SizedBox, painting a brush directly doesn't
+        // depend on the widget's state.
+        let painter = ModularWidget::new(BackgroundBrush::from(Color::RED))
+            .layout_fn(|_, _, bc, _| bc.max())
+            .paint_fn(|brush, ctx, env| brush.paint(ctx, env));
+        let mut harness =
+            TestHarness::create_with_size(SizedBox::new(painter), Size::new(40.0, 40.0));
+        harness.edit_root_widget(|mut sized_box, _| {
+            let mut sized_box = sized_box.downcast::<SizedBox>().unwrap();
+            sized_box.ctx.set_disabled(true);
+        });
+        let image = harness.render();
+        assert_eq!(pixel_at(&image, 40, 20, 20), [255, 0, 0, 255]);
+    }
+
+    #[test]
+    fn disabled_cascades() {
+        fn set_root_disabled(harness: &mut TestHarness, disabled: bool) {
+            harness.edit_root_widget(|mut root, _| {
+                let mut root = root.downcast::<SizedBox>().unwrap();
+                root.ctx.set_disabled(disabled);
+            });
+        }
+
+        fn pressed(harness: &mut TestHarness, id: WidgetId) -> bool {
+            harness.mouse_click_on(id);
+            harness.pop_action() == Some((Action::ButtonPressed, id))
+        }
+
+        let [button_id, new_button_id] = widget_ids();
+        let inner = SizedBox::new_with_id(Button::new("Click"), button_id);
+        let widget = SizedBox::new(inner);
+        let mut harness = TestHarness::create_with_size(widget, Size::new(100.0, 100.0));
+        assert!(pressed(&mut harness, button_id));
+
+        set_root_disabled(&mut harness, true);
+        assert!(harness.get_widget(button_id).state().is_disabled());
+        assert!(!pressed(&mut harness, button_id));
+
+        set_root_disabled(&mut harness, false);
+        assert!(!harness.get_widget(button_id).state().is_disabled());
+        assert!(pressed(&mut harness, button_id));
+
+        // Re-enabling the root leaves descendants disabled by their own flag alone.
+        harness.edit_root_widget(|mut root, _| {
+            let mut root = root.downcast::<SizedBox>().unwrap();
+            let mut inner = root.child_mut().unwrap();
+            inner.downcast::<SizedBox>().unwrap().ctx.set_disabled(true);
+        });
+        set_root_disabled(&mut harness, true);
+        set_root_disabled(&mut harness, false);
+        assert!(harness.get_widget(button_id).state().is_disabled());
+        assert!(!pressed(&mut harness, button_id));
+
+        // Children added to a disabled box are disabled too.
+        set_root_disabled(&mut harness, true);
+        harness.edit_root_widget(|mut root, _| {
+            let mut root = root.downcast::<SizedBox>().unwrap();
+            root.set_child(SizedBox::new_with_id(Button::new("New"), new_button_id));
+        });
+        assert!(harness.get_widget(new_button_id).state().is_disabled());
+        assert!(!pressed(&mut harness, new_button_id));
+
+        set_root_disabled(&mut harness, false);
+        assert!(pressed(&mut harness, new_button_id));
+    }
+
+    #[test]
+    fn custom_background() {
+        struct FillColor(Color);
+
+        impl CustomBackground for FillColor {
+            fn paint(&mut self, ctx: &mut PaintCtx, bounds: Rect, _env: &Env) {
+                ctx.fill(bounds, &self.0);
+            }
+        }
+
+        let custom: Box<dyn CustomBackground> = Box::new(FillColor(Color::PURPLE));
+        let widget = SizedBox::empty()
+            .width(40.0)
+            .height(40.0)
+            .background(custom);
+
+        let mut harness = TestHarness::create_with_size(widget, Size::new(40.0, 40.0));
+        let image_1 = harness.render();
+        assert_eq!(pixel_at(&image_1, 40, 20, 20), [128, 0, 128, 255]);
+
+        let widget = SizedBox::empty()
+            .width(40.0)
+            .height(40.0)
+            .background(Color::PURPLE);
+
+        let mut harness = TestHarness::create_with_size(widget, Size::new(40.0, 40.0));
+        let image_2 = harness.render();
+
+        // We don't use assert_eq because we don't want rich assert
+        assert!(image_1 == image_2);
+    }
+
+    #[test]
+    fn shimmer_moves_over_time() {
+        fn shimmer_progress(harness: &TestHarness) -> f64 {
+            let sized_box = harness.root_widget().downcast::<SizedBox>().unwrap();
+            match sized_box.deref().background.as_ref().unwrap() {
+                BackgroundBrush::Shimmer(shimmer) => shimmer.progress,
+                _ => unreachable!(),
+            }
+        }
+
+        let widget = SizedBox::empty()
+            .width(100.0)
+            .height(20.0)
+            .background(BackgroundBrush::shimmer(Color::BLACK, Color::WHITE, 1.0));
+
+        let mut harness = TestHarness::create_with_size(widget, Size::new(100.0, 20.0));
+        let frame_0 = harness.render();
+        assert_eq!(shimmer_progress(&harness), 0.0);
+
+        advance_frame(&mut harness, Duration::from_millis(250));
+        let frame_1 = harness.render();
+        assert!((shimmer_progress(&harness) - 0.25).abs() < 1e-6);
+
+        advance_frame(&mut harness, Duration::from_millis(250));
+        let frame_2 = harness.render();
+        assert!((shimmer_progress(&harness) - 0.5).abs() < 1e-6);
+
+        // We don't use assert_eq because we don't want rich assert
+        assert!(frame_0 != frame_1);
+        assert!(frame_1 != frame_2);
+        // Halfway through, the highlight is in the middle of the box.
+        let [r, g, b, _] = pixel_at(&frame_2, 100, 50, 10);
+        assert!(r > 200 && g > 200 && b > 200);
+        assert_eq!(pixel_at(&frame_2, 100, 2, 10), [0, 0, 0, 255]);
+        assert_eq!(pixel_at(&frame_2, 100, 97, 10), [0, 0, 0, 255]);
+
+        // If the box isn't painted between frames, the animation stops.
+        advance_frame(&mut harness, Duration::from_millis(250));
+        advance_frame(&mut harness, Duration::from_millis(250));
+        assert!((shimmer_progress(&harness) - 0.75).abs() < 1e-6);
+        assert!(!harness.window().wants_animation_frame());
+    }
+
+    #[test]
+    fn rotating_gradient() {
+        fn gradient_angle(harness: &TestHarness) -> f64 {
+            let sized_box = harness.root_widget().downcast::<SizedBox>().unwrap();
+            match sized_box.deref().background.as_ref().unwrap() {
+                BackgroundBrush::Rotating(rotating) => rotating.degrees,
+                _ => unreachable!(),
+            }
+        }
+
+        let brush =
+            BackgroundBrush::rotating_linear((Color::BLACK, Color::WHITE), Duration::from_secs(1));
+        let widget = SizedBox::empty().width(40.0).height(40.0).background(brush);
+
+        let mut harness = TestHarness::create_with_size(widget, Size::new(40.0, 40.0));
+        let frame_0 = harness.render();
+        assert_eq!(gradient_angle(&harness), 90.0);
+
+        // A quarter turn later, the gradient goes from top to bottom.
+        advance_frame(&mut harness, Duration::from_millis(250));
+        let frame_1 = harness.render();
+        assert!((gradient_angle(&harness) - 180.0).abs() < 1e-6);
+        assert!(frame_0 != frame_1);
+        // Left is black before the turn, and top is black after it.
+        let pixel = |image: &[u8], x: usize, y: usize| image[(y * 40 + x) * 4];
+        assert!(pixel(&frame_0, 0, 20) < 20 && pixel(&frame_0, 20, 0) > 100);
+        assert!(pixel(&frame_1, 20, 0) < 20 && pixel(&frame_1, 0, 20) > 100);
+
+        harness.edit_root_widget(|mut sized_box, _| {
+            let mut sized_box = sized_box.downcast::<SizedBox>().unwrap();
+            sized_box.set_animation_paused(true);
+        });
+        advance_frame(&mut harness, Duration::from_millis(250));
+        harness.render();
+        assert!((gradient_angle(&harness) - 180.0).abs() < 1e-6);
+        assert!(!harness.window().wants_animation_frame());
+
+        harness.edit_root_widget(|mut sized_box, _| {
+            let mut sized_box = sized_box.downcast::<SizedBox>().unwrap();
+            sized_box.set_animation_paused(false);
+        });
+        advance_frame(&mut harness, Duration::from_millis(250));
+        assert!((gradient_angle(&harness) - 270.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn marching_ants() {
+        fn dash_offset(harness: &TestHarness) -> f64 {
+            let sized_box = harness.root_widget().downcast::<SizedBox>().unwrap();
+            sized_box.deref().marching_ants.as_ref().unwrap().offset
+        }
+
+        let widget = SizedBox::empty().width(40.0).height(40.0).marching_ants(
+            Color::BLACK,
+            2.0,
+            &[4.0, 4.0],
+            20.0,
+        );
+        let mut harness = TestHarness::create_with_size(widget, Size::new(40.0, 40.0));
+        let frame_0 = harness.render();
+        assert_eq!(dash_offset(&harness), 0.0);
+
+        advance_frame(&mut harness, Duration::from_millis(100));
+        let frame_1 = harness.render();
+        assert!((dash_offset(&harness) - 2.0).abs() < 1e-6);
+        assert!(frame_0 != frame_1);
+
+        // The offset wraps around the length of the dash pattern.
+        advance_frame(&mut harness, Duration::from_millis(400));
+        assert!((dash_offset(&harness) - 2.0).abs() < 1e-6);
+
+        harness.edit_root_widget(|mut sized_box, _| {
+            let mut sized_box = sized_box.downcast::<SizedBox>().unwrap();
+            sized_box.set_animation_paused(true);
+        });
+        advance_frame(&mut harness, Duration::from_millis(100));
+        harness.render();
+        assert!((dash_offset(&harness) - 2.0).abs() < 1e-6);
+        assert!(!harness.window().wants_animation_frame());
+    }
+
+    #[test]
+    fn animate_dash() {
+        fn dash_offset(harness: &TestHarness, id: WidgetId) -> f64 {
+            let sized_box = harness.get_widget(id).downcast::<SizedBox>().unwrap();
+            sized_box.deref().dashed_border.as_ref().unwrap().offset
+        }
+
+        let [box_id] = widget_ids();
+        let dashed_box = SizedBox::empty().width(40.0).height(40.0).dashed_border(
+            Color::BLACK,
+            2.0,
+            &[4.0, 4.0],
+        );
+
+        // Without animate_dash, the dashes stay put.
+        let mut harness = TestHarness::create_with_size(dashed_box, Size::new(40.0, 40.0));
+        advance_frame(&mut harness, Duration::from_millis(100));
+        assert!(!harness.window().wants_animation_frame());
+
+        let widget = Flex::column().with_child_id(
+            SizedBox::empty()
+                .width(40.0)
+                .height(40.0)
+                .dashed_border(Color::BLACK, 2.0, &[4.0, 4.0])
+                .animate_dash(10.0),
+            box_id,
+        );
+        let mut harness = TestHarness::create_with_size(widget, Size::new(40.0, 40.0));
+        let frame_0 = harness.render();
+        advance_frame(&mut harness, Duration::from_millis(100));
+        let frame_1 = harness.render();
+        assert!((dash_offset(&harness, box_id) - 1.0).abs() < 1e-6);
+        advance_frame(&mut harness, Duration::from_millis(100));
+        let frame_2 = harness.render();
+        assert!((dash_offset(&harness, box_id) - 2.0).abs() < 1e-6);
+        // We don't use assert_eq because we don't want rich assert
+        assert!(frame_0 != frame_1);
+        assert!(frame_1 != frame_2);
+
+        harness.edit_root_widget(|mut flex, _| {
+            let mut flex = flex.downcast::<Flex>().unwrap();
+            flex.remove_child(0);
+        });
+        advance_frame(&mut harness, Duration::from_millis(100));
+        assert!(!harness.window().wants_animation_frame());
+    }
+
+    #[test]
+    fn dashed_border_insets_child() {
+        let [child_id] = widget_ids();
+        let widget = SizedBox::new_with_id(SizedBox::empty().expand(), child_id)
+            .width(40.0)
+            .height(40.0)
+            .focusable(true)
+            .dashed_border(Color::BLACK, 3.0, &[4.0, 4.0]);
+        let mut harness = TestHarness::create_with_size(widget, Size::new(40.0, 40.0));
+
+        // The border is shown without focus, and the child sits inside it.
+        let image = harness.render();
+        assert_ne!(pixel_at(&image, 40, 1, 1), pixel_at(&image, 40, 20, 20));
+        let child = harness.get_widget(child_id);
+        assert_eq!(child.state().layout_rect(), Rect::new(3.0, 3.0, 37.0, 37.0));
+    }
+
+    #[test]
+    fn marching_ants_focus_ring() {
+        fn dash_offset(harness: &TestHarness) -> f64 {
+            let sized_box = harness.root_widget().downcast::<SizedBox>().unwrap();
+            sized_box.deref().marching_ants.as_ref().unwrap().offset
         }
-        if size.height.is_infinite() {
-            warn!("SizedBox is returning an infinite height.");
+
+        let widget = SizedBox::empty()
+            .width(40.0)
+            .height(40.0)
+            .focusable(true)
+            .marching_ants(Color::BLACK, 2.0, &[4.0, 4.0], 20.0)
+            .on_context_menu(|ctx, _| ctx.request_focus());
+        let mut harness = TestHarness::create_with_size(widget, Size::new(40.0, 40.0));
+        let unfocused = harness.render();
+
+        // Without focus, the ring is hidden and doesn't move.
+        advance_frame(&mut harness, Duration::from_millis(100));
+        assert_eq!(dash_offset(&harness), 0.0);
+        assert!(!harness.window().wants_animation_frame());
+
+        harness.mouse_move(Point::new(20.0, 20.0));
+        harness.mouse_button_press(MouseButton::Right);
+        assert!(harness.window().wants_animation_frame());
+        assert!(harness.render() != unfocused);
+        advance_frame(&mut harness, Duration::from_millis(100));
+        assert!((dash_offset(&harness) - 2.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn focus_ring() {
+        let make_widget = || {
+            SizedBox::new(tabber(Size::ZERO))
+                .width(40.0)
+                .height(40.0)
+                .focusable(true)
+                .focus_ring(Color::BLUE, 3.0)
+                .on_context_menu(|ctx, _| ctx.request_focus())
+        };
+
+        // Clicking focuses the box without showing the ring.
+        let mut harness = TestHarness::create_with_size(make_widget(), Size::new(40.0, 40.0));
+        let box_id = harness.root_widget().id();
+        let unfocused = harness.render();
+        harness.mouse_move(Point::new(20.0, 20.0));
+        harness.mouse_button_press(MouseButton::Right);
+        assert_eq!(harness.window().focus, Some(box_id));
+        assert_eq!(harness.window().input_modality, InputModality::Pointer);
+        assert!(harness.render() == unfocused);
+
+        // Moving focus to it with the keyboard does.
+        let mut harness = TestHarness::create_with_size(make_widget(), Size::new(40.0, 40.0));
+        let box_id = harness.root_widget().id();
+        tab_from_tabber(&mut harness);
+        assert_eq!(harness.window().focus, Some(box_id));
+        assert_eq!(harness.window().input_modality, InputModality::Keyboard);
+        let focused = harness.render();
+        assert!(focused != unfocused);
+
+        // A later click doesn't hide the ring of a box that keeps focus.
+        harness.mouse_move(Point::new(20.0, 20.0));
+        harness.mouse_button_press(MouseButton::Right);
+        assert!(harness.render() == focused);
+    }
+
+    #[test]
+    fn background_transition() {
+        fn painted_color(harness: &TestHarness) -> Color {
+            let sized_box = harness.root_widget().downcast::<SizedBox>().unwrap();
+            sized_box.deref().painted_color.unwrap()
+        }
+
+        let widget = SizedBox::empty()
+            .width(20.0)
+            .height(20.0)
+            .background(Color::rgb8(0, 0, 0))
+            .transition_background(Duration::from_millis(400));
+
+        let mut harness = TestHarness::create_with_size(widget, Size::new(20.0, 20.0));
+        harness.render();
+        assert_eq!(painted_color(&harness).as_rgba8(), (0, 0, 0, 255));
+
+        harness.edit_root_widget(|mut sized_box, _| {
+            let mut sized_box = sized_box.downcast::<SizedBox>().unwrap();
+            sized_box.set_background(Color::rgba8(200, 100, 0, 55));
+        });
+        harness.render();
+        assert_eq!(painted_color(&harness).as_rgba8(), (0, 0, 0, 255));
+
+        advance_frame(&mut harness, Duration::from_millis(100));
+        harness.render();
+        assert_eq!(painted_color(&harness).as_rgba8(), (50, 25, 0, 205));
+
+        advance_frame(&mut harness, Duration::from_millis(200));
+        harness.render();
+        assert_eq!(painted_color(&harness).as_rgba8(), (150, 75, 0, 105));
+
+        advance_frame(&mut harness, Duration::from_millis(100));
+        harness.render();
+        assert_eq!(painted_color(&harness).as_rgba8(), (200, 100, 0, 55));
+        assert!(!harness.window().wants_animation_frame());
+    }
+
+    #[test]
+    fn background_transition_animation_scale() {
+        fn painted_color(harness: &TestHarness) -> Color {
+            let sized_box = harness.root_widget().downcast::<SizedBox>().unwrap();
+            sized_box.deref().painted_color.unwrap()
+        }
+
+        let widget = SizedBox::empty()
+            .width(20.0)
+            .height(20.0)
+            .background(Color::BLACK)
+            .transition_background(Duration::from_millis(400));
+
+        let mut harness = TestHarness::create_with_size(widget, Size::new(20.0, 20.0));
+        harness.set_env_value(theme::ANIMATION_SCALE, 0.0);
+        harness.render();
+
+        harness.edit_root_widget(|mut sized_box, _| {
+            let mut sized_box = sized_box.downcast::<SizedBox>().unwrap();
+            sized_box.set_background(Color::WHITE);
+        });
+        harness.render();
+        assert_eq!(painted_color(&harness), Color::WHITE);
+
+        advance_frame(&mut harness, Duration::from_millis(16));
+        let sized_box = harness.root_widget().downcast::<SizedBox>().unwrap();
+        assert!(sized_box.deref().color_transition.is_none());
+        assert!(!harness.window().wants_animation_frame());
+    }
+
+    #[test]
+    fn reduced_motion() {
+        let widget = SizedBox::empty()
+            .width(20.0)
+            .height(20.0)
+            .background(Color::BLACK)
+            .transition_background(Duration::from_millis(400));
+
+        let mut harness = TestHarness::create_with_size(widget, Size::new(20.0, 20.0));
+        harness.set_env_value(theme::REDUCED_MOTION, true);
+        harness.render();
+
+        harness.edit_root_widget(|mut sized_box, _| {
+            let mut sized_box = sized_box.downcast::<SizedBox>().unwrap();
+            sized_box.set_background(Color::WHITE);
+        });
+        harness.render();
+        let sized_box = harness.root_widget().downcast::<SizedBox>().unwrap();
+        assert_eq!(sized_box.deref().painted_color, Some(Color::WHITE));
+
+        // A single frame is enough to finish the transition.
+        advance_frame(&mut harness, Duration::from_millis(16));
+        let sized_box = harness.root_widget().downcast::<SizedBox>().unwrap();
+        assert!(sized_box.deref().color_transition.is_none());
+        assert!(!harness.window().wants_animation_frame());
+
+        // Animated brushes stand still.
+        harness.edit_root_widget(|mut sized_box, _| {
+            let mut sized_box = sized_box.downcast::<SizedBox>().unwrap();
+            sized_box.set_background(BackgroundBrush::shimmer(Color::BLACK, Color::WHITE, 1.0));
+        });
+        harness.render();
+        advance_frame(&mut harness, Duration::from_millis(250));
+        let sized_box = harness.root_widget().downcast::<SizedBox>().unwrap();
+        match sized_box.deref().background.as_ref().unwrap() {
+            BackgroundBrush::Shimmer(shimmer) => assert_eq!(shimmer.progress, 0.0),
+            _ => unreachable!(),
+        }
+        assert!(!harness.window().wants_animation_frame());
+    }
+
+    #[test]
+    fn background_transition_gradient_snaps() {
+        let widget = SizedBox::empty()
+            .width(20.0)
+            .height(20.0)
+            .background(Color::BLACK)
+            .transition_background(Duration::from_millis(400));
+
+        let mut harness = TestHarness::create_with_size(widget, Size::new(20.0, 20.0));
+        harness.render();
+
+        harness.edit_root_widget(|mut sized_box, _| {
+            let mut sized_box = sized_box.downcast::<SizedBox>().unwrap();
+            sized_box.set_background(LinearGradient::new(
+                UnitPoint::LEFT,
+                UnitPoint::RIGHT,
+                (Color::RED, Color::BLUE),
+            ));
+        });
+        harness.render();
+
+        let sized_box = harness.root_widget().downcast::<SizedBox>().unwrap();
+        assert!(sized_box.deref().painted_color.is_none());
+        assert!(sized_box.deref().color_transition.is_none());
+    }
+
+    #[test]
+    fn background_role() {
+        fn painted_color(harness: &TestHarness) -> Color {
+            let sized_box = harness.root_widget().downcast::<SizedBox>().unwrap();
+            sized_box.deref().painted_color.unwrap()
+        }
+
+        let widget = SizedBox::empty()
+            .width(20.0)
+            .height(20.0)
+            .background(BackgroundBrush::role(BackgroundRole::Error));
+
+        let mut harness = TestHarness::create_with_size(widget, Size::new(20.0, 20.0));
+        harness.render();
+        let default_color = painted_color(&harness);
+        harness.edit_root_widget(|_, env| {
+            assert_eq!(default_color, env.get(theme::ERROR_COLOR));
+        });
+
+        let new_color = Color::rgb8(0x12, 0x34, 0x56);
+        harness.set_env_value(theme::ERROR_COLOR, new_color);
+        harness.render();
+        assert_eq!(painted_color(&harness), new_color);
+    }
+
+    #[test]
+    fn surface() {
+        let middle_pixel = |harness: &mut TestHarness| {
+            let image = harness.render();
+            let offset = (10 * 20 + 10) * 4;
+            Color::rgba8(
+                image[offset],
+                image[offset + 1],
+                image[offset + 2],
+                image[offset + 3],
+            )
+        };
+
+        let widget = SizedBox::surface(SizedBox::empty().width(20.0).height(20.0));
+        let mut harness = TestHarness::create_with_size(widget, Size::new(20.0, 20.0));
+        let surface_color = middle_pixel(&mut harness);
+        harness.edit_root_widget(|_, env| {
+            assert_eq!(surface_color, env.get(theme::SURFACE_COLOR));
+        });
+
+        let new_color = Color::rgb8(0x12, 0x34, 0x56);
+        harness.set_env_value(theme::SURFACE_COLOR, new_color);
+        assert_eq!(middle_pixel(&mut harness), new_color);
+
+        // An explicit background wins.
+        let widget = SizedBox::surface(SizedBox::empty().width(20.0).height(20.0))
+            .background(Color::rgb8(0xff, 0, 0));
+        let mut harness = TestHarness::create_with_size(widget, Size::new(20.0, 20.0));
+        assert_eq!(middle_pixel(&mut harness), Color::rgb8(0xff, 0, 0));
+    }
+
+    #[test]
+    fn try_build() {
+        let error = SizedBox::empty().width(f64::NAN).try_build().err();
+        assert!(matches!(error, Some(BoxConfigError::InvalidWidth(width)) if width.is_nan()));
+
+        let error = SizedBox::empty()
+            .border(Color::WHITE, -1.0)
+            .try_build()
+            .err();
+        assert_eq!(
+            error,
+            Some(BoxConfigError::Border(BorderError::NegativeWidth(-1.0)))
+        );
+
+        let error = SizedBox::empty()
+            .rounded_each([1.0, -2.0, 3.0, 4.0])
+            .try_build()
+            .err();
+        assert_eq!(error, Some(BoxConfigError::InvalidCornerRadius(-2.0)));
+
+        let sized_box = SizedBox::empty()
+            .width(10.0)
+            .expand_height()
+            .border(Color::WHITE, 1.0)
+            .rounded(2.0)
+            .try_build();
+        assert!(sized_box.is_ok());
+
+        // Only the current settings are checked, not the ones they replaced.
+        let sized_box = SizedBox::empty()
+            .border(Color::WHITE, -1.0)
+            .border(Color::WHITE, 2.0)
+            .rounded_each([1.0, -2.0, 3.0, 4.0])
+            .rounded(2.0)
+            .try_build();
+        assert!(sized_box.is_ok());
+        let error = SizedBox::empty()
+            .border(Color::WHITE, f64::NAN)
+            .try_build()
+            .err();
+        assert_eq!(error, Some(BoxConfigError::Border(BorderError::NanWidth)));
+    }
+
+    #[test]
+    fn responsive() {
+        fn shown_child(window_width: f64) -> (Vec<Record>, Vec<Record>) {
+            let wide_recording = Recording::default();
+            let narrow_recording = Recording::default();
+            let widget = SizedBox::responsive(
+                400.0,
+                Label::new("wide").record(&wide_recording),
+                Label::new("narrow").record(&narrow_recording),
+            );
+
+            let mut harness = TestHarness::create_with_size(widget, Size::new(window_width, 100.0));
+            let _ = harness.render();
+            (wide_recording.drain(), narrow_recording.drain())
+        }
+
+        let is_layout_and_paint = |records: &[Record]| {
+            records
+                .iter()
+                .any(|record| matches!(record, Record::Layout(_)))
+                && records.iter().any(|record| matches!(record, Record::Paint))
+        };
+
+        let (wide, narrow) = shown_child(500.0);
+        assert!(is_layout_and_paint(&wide));
+        assert!(!is_layout_and_paint(&narrow));
+
+        let (wide, narrow) = shown_child(300.0);
+        assert!(!is_layout_and_paint(&wide));
+        assert!(is_layout_and_paint(&narrow));
+    }
+
+    #[test]
+    fn first_and_last_baselines() {
+        let [one_line_id, two_lines_id] = widget_ids();
+        let widget = Flex::row()
+            .with_child_id(SizedBox::new(Label::new("one")), one_line_id)
+            .with_child_id(
+                SizedBox::new(Label::new("one\ntwo"))
+                    .border(Color::WHITE, 2.0)
+                    .padding(Insets::new(0.0, 3.0, 0.0, 5.0)),
+                two_lines_id,
+            );
+
+        let harness = TestHarness::create_with_size(widget, Size::new(200.0, 200.0));
+        let line_height = harness
+            .get_widget(one_line_id)
+            .state()
+            .layout_rect()
+            .height();
+        let state = harness.get_widget(two_lines_id).state();
+
+        let difference = state.baseline_offset - state.last_baseline_offset;
+        assert!((difference - line_height).abs() < 1e-6);
+        // The last line sits above the bottom border and padding.
+        assert!(state.last_baseline_offset > 2.0 + 5.0);
+    }
+
+    #[test]
+    fn baseline_grid() {
+        let [label_id] = widget_ids();
+        let widget = Flex::column().with_child_id(SizedBox::new(Label::new("hello")), label_id);
+        let harness = TestHarness::create_with_size(widget, Size::new(100.0, 100.0));
+        let natural_height = harness.get_widget(label_id).state().layout_rect().height();
+        let expected_height = (natural_height / 4.0).ceil() * 4.0;
+        // Otherwise the test doesn't check anything.
+        assert!(expected_height > natural_height);
+
+        let widget = Flex::column().with_child_id(
+            SizedBox::new(Label::new("hello")).baseline_grid(4.0),
+            label_id,
+        );
+        let harness = TestHarness::create_with_size(widget, Size::new(100.0, 100.0));
+        let state = harness.get_widget(label_id).state();
+        assert_eq!(state.layout_rect().height(), expected_height);
+    }
+
+    #[test]
+    fn did_paint_content() {
+        fn paints_content(widget: SizedBox) -> bool {
+            let mut harness = TestHarness::create_with_size(widget, Size::new(20.0, 20.0));
+            let _ = harness.render();
+            let sized_box = harness.root_widget().downcast::<SizedBox>().unwrap();
+            sized_box.deref().did_paint_content()
+        }
+
+        assert!(!paints_content(SizedBox::empty()));
+        assert!(paints_content(SizedBox::empty().border(Color::WHITE, 1.0)));
+        assert!(paints_content(SizedBox::empty().background(Color::BLACK)));
+        assert!(paints_content(SizedBox::new(Label::new("hello"))));
+        assert!(!paints_content(
+            SizedBox::empty().background(Color::BLACK).opacity(0.0)
+        ));
+    }
+
+    #[test]
+    fn gradient_color_space() {
+        let red_to_blue = |space| {
+            let brush = BackgroundBrush::linear_stops_in(
+                90.0,
+                vec![(0.0, Color::RED), (1.0, Color::BLUE)],
+                space,
+            )
+            .unwrap();
+            SizedBox::empty()
+                .width(100.0)
+                .height(10.0)
+                .background(brush)
+        };
+        let middle_pixel = |harness: &mut TestHarness| {
+            let image = harness.render();
+            let offset = (5 * 100 + 50) * 4;
+            [image[offset], image[offset + 1], image[offset + 2]]
+        };
+        let assert_close = |actual: [u8; 3], expected: [u8; 3]| {
+            for (actual, expected) in actual.iter().zip(expected) {
+                assert!(
+                    actual.abs_diff(expected) <= 6,
+                    "{:?} != {:?}",
+                    actual,
+                    expected
+                );
+            }
+        };
+
+        let mut harness =
+            TestHarness::create_with_size(red_to_blue(ColorSpace::Srgb), Size::new(100.0, 10.0));
+        assert_close(middle_pixel(&mut harness), [127, 0, 127]);
+
+        let mut harness =
+            TestHarness::create_with_size(red_to_blue(ColorSpace::Oklab), Size::new(100.0, 10.0));
+        assert_close(middle_pixel(&mut harness), [140, 83, 162]);
+    }
+
+    #[test]
+    fn opacity() {
+        let faded_box = SizedBox::empty()
+            .width(20.0)
+            .height(20.0)
+            .background(Color::BLACK)
+            .opacity(0.5);
+        let widget = SizedBox::new(faded_box).background(Color::WHITE).expand();
+
+        let mut harness = TestHarness::create_with_size(widget, Size::new(20.0, 20.0));
+        let image = harness.render();
+        for channel in &image[..3] {
+            assert!(channel.abs_diff(128) <= 1, "{}", channel);
+        }
+
+        harness.edit_root_widget(|mut sized_box, _| {
+            let mut sized_box = sized_box.downcast::<SizedBox>().unwrap();
+            let mut faded_box = sized_box.child_mut().unwrap();
+            let mut faded_box = faded_box.downcast::<SizedBox>().unwrap();
+            faded_box.set_opacity(0.0);
+        });
+        let image = harness.render();
+        assert_eq!(image[..4], [255, 255, 255, 255]);
+    }
+
+    #[test]
+    fn opacity_bordered_label() {
+        let widget = SizedBox::new(Label::new("Faded"))
+            .border(Color::WHITE, 2.0)
+            .rounded(4.0)
+            .padding(4.0)
+            .opacity(0.5);
+
+        let mut harness = TestHarness::create_with_size(widget, Size::new(80.0, 40.0));
+        let faded = harness.render();
+        let mut render_with_opacity = |opacity| {
+            harness.edit_root_widget(|mut sized_box, _| {
+                let mut sized_box = sized_box.downcast::<SizedBox>().unwrap();
+                sized_box.set_opacity(opacity);
+            });
+            harness.render()
+        };
+        let opaque = render_with_opacity(1.0);
+        let hidden = render_with_opacity(0.0);
+        // We don't use assert_eq because we don't want rich assert
+        assert!(opaque != hidden);
+
+        // The border and the label are faded together, so every pixel is halfway
+        // between the opaque box and the window behind it.
+        for ((faded, opaque), hidden) in faded.iter().zip(opaque.iter()).zip(hidden.iter()) {
+            let expected = (*opaque as u16 + *hidden as u16) / 2;
+            assert!(
+                (*faded as u16).abs_diff(expected) <= 2,
+                "{} is not halfway between {} and {}",
+                faded,
+                opaque,
+                hidden
+            );
+        }
+    }
+
+    #[test]
+    fn opacity_paint_insets_and_cache() {
+        let [faded_id] = widget_ids();
+        let paint_count = Rc::new(Cell::new(0));
+        // A child which paints 5 pixels around its bounds.
+        let child = ModularWidget::new(paint_count.clone())
+            .layout_fn(|_, ctx, bc, _| {
+                ctx.set_paint_insets(5.0);
+                bc.constrain(Size::new(10.0, 10.0))
+            })
+            .paint_fn(|paint_count, ctx, _| {
+                paint_count.set(paint_count.get() + 1);
+                ctx.fill(Rect::new(-5.0, -5.0, 15.0, 15.0), &Color::BLACK);
+            });
+        let faded_box = SizedBox::new(child).opacity(0.5);
+        let widget = SizedBox::new(Flex::row().with_child_id(faded_box, faded_id))
+            .padding(10.0)
+            .background(Color::WHITE)
+            .expand();
+
+        let mut harness = TestHarness::create_with_size(widget, Size::new(40.0, 40.0));
+        let image = harness.render();
+        let origin = harness.get_widget(faded_id).state().window_origin();
+        let (x, y) = (origin.x as usize, origin.y as usize);
+        // Inside the box and outside of it, where the child's paint insets are, the
+        // child is faded the same way.
+        for pixel in [
+            pixel_at(&image, 40, x + 5, y + 5),
+            pixel_at(&image, 40, x - 3, y - 3),
+        ] {
+            assert!(pixel[0].abs_diff(128) <= 1, "{:?}", pixel);
+        }
+        assert_eq!(paint_count.get(), 1);
+
+        // Nothing in the box changed, so it isn't painted again.
+        harness.render();
+        assert_eq!(paint_count.get(), 1);
+
+        harness.edit_root_widget(|mut sized_box, _| {
+            let mut sized_box = sized_box.downcast::<SizedBox>().unwrap();
+            let mut flex = sized_box.child_mut().unwrap();
+            let mut flex = flex.downcast::<Flex>().unwrap();
+            let mut faded_box = flex.child_mut(0).unwrap();
+            let mut faded_box = faded_box.downcast::<SizedBox>().unwrap();
+            faded_box.set_opacity(0.25);
+        });
+        harness.render();
+        assert_eq!(paint_count.get(), 2);
+    }
+
+    #[test]
+    fn focusable() {
+        let [focusable_id, plain_id] = widget_ids();
+        let widget = SizedBox::new(
+            Flex::column()
+                .with_child_id(SizedBox::empty().width(10.0).focusable(true), focusable_id)
+                .with_child_id(SizedBox::empty().width(10.0), plain_id),
+        );
+
+        let mut harness = TestHarness::create(widget);
+        assert_eq!(harness.window().focus_chain(), &[focusable_id]);
+
+        let root_id = harness.root_widget().id();
+        harness.edit_root_widget(|mut sized_box, _| {
+            let mut sized_box = sized_box.downcast::<SizedBox>().unwrap();
+            sized_box.set_focusable(true);
+        });
+        assert_eq!(harness.window().focus_chain(), &[root_id, focusable_id]);
+    }
+
+    #[test]
+    fn from_pod() {
+        let [label_id, other_id] = widget_ids();
+        let pod = WidgetPod::new_with_id(Label::new("hello"), label_id).boxed();
+        let harness = TestHarness::create(SizedBox::from_pod(pod));
+        assert_eq!(harness.get_widget(label_id).id(), label_id);
+
+        let mut harness = TestHarness::create(SizedBox::empty());
+        harness.edit_root_widget(|mut sized_box, _| {
+            let mut sized_box = sized_box.downcast::<SizedBox>().unwrap();
+            let pod = WidgetPod::new_with_id(Label::new("hello"), other_id).boxed();
+            sized_box.set_child_pod(pod);
+        });
+        let child = harness.root_widget().children()[0];
+        assert_eq!(child.id(), other_id);
+        assert!(child.downcast::<Label>().is_some());
+    }
+
+    #[test]
+    fn box_sizing() {
+        fn box_and_child_width(box_sizing: BoxSizing) -> (f64, f64) {
+            let [box_id, child_id] = widget_ids();
+            let sized_box = SizedBox::new_with_id(SizedBox::empty().expand(), child_id)
+                .width(100.0)
+                .height(20.0)
+                .border(Color::WHITE, 10.0)
+                .box_sizing(box_sizing);
+            let widget = Flex::column().with_child_id(sized_box, box_id);
+
+            let harness = TestHarness::create_with_size(widget, Size::new(400.0, 400.0));
+            let box_width = harness.get_widget(box_id).state().layout_rect().width();
+            let child_width = harness.get_widget(child_id).state().layout_rect().width();
+            (box_width, child_width)
+        }
+
+        assert_eq!(box_and_child_width(BoxSizing::BorderBox), (100.0, 80.0));
+        assert_eq!(box_and_child_width(BoxSizing::ContentBox), (120.0, 100.0));
+
+        let sized_box = SizedBox::empty()
+            .width(100.0)
+            .box_sizing(BoxSizing::ContentBox);
+        let bc = BoxConstraints::new(Size::ZERO, Size::new(400.0, 400.0));
+        let insets = Insets::uniform(10.0);
+        let child_bc = sized_box
+            .child_constraints(&bc, insets)
+            .shrink(insets.size());
+        assert_eq!(child_bc.min().width, 100.0);
+        assert_eq!(child_bc.max().width, 100.0);
+    }
+
+    #[test]
+    fn linear_angle() {
+        fn pixel(image: &[u8], x: usize, y: usize) -> &[u8] {
+            let offset = (y * 40 + x) * 4;
+            &image[offset..offset + 4]
+        }
+
+        let stops = (Color::RED, Color::BLUE);
+        let gradient_box = |degrees| {
+            SizedBox::empty()
+                .width(40.0)
+                .height(40.0)
+                .background(BackgroundBrush::linear_angle(degrees, stops))
+        };
+
+        // 0 degrees goes up: red at the bottom, blue at the top.
+        let mut harness = TestHarness::create_with_size(gradient_box(0.0), Size::new(40.0, 40.0));
+        let image = harness.render();
+        assert!(pixel(&image, 20, 38)[0] > pixel(&image, 20, 1)[0]);
+        assert!(pixel(&image, 20, 38)[2] < pixel(&image, 20, 1)[2]);
+
+        // 90 degrees goes right.
+        let mut harness = TestHarness::create_with_size(gradient_box(90.0), Size::new(40.0, 40.0));
+        let image = harness.render();
+        assert!(pixel(&image, 1, 20)[0] > pixel(&image, 38, 20)[0]);
+
+        // 45 degrees goes from the bottom left corner to the top right one.
+        let mut harness = TestHarness::create_with_size(gradient_box(45.0), Size::new(40.0, 40.0));
+        let image = harness.render();
+        assert!(pixel(&image, 1, 38)[0] > pixel(&image, 38, 1)[0]);
+        assert!(pixel(&image, 1, 38)[2] < pixel(&image, 38, 1)[2]);
+        let (top_left, bottom_right) = (pixel(&image, 1, 1), pixel(&image, 38, 38));
+        assert!(top_left[0].abs_diff(bottom_right[0]) <= 2);
+    }
+
+    #[test]
+    fn aspect_ratio_presets() {
+        fn assert_size(sized_box: SizedBox, width: f64, height: f64) {
+            let bc = BoxConstraints::new(Size::ZERO, Size::new(200.0, 100.0));
+            let size = sized_box.box_constraints(&bc, Insets::ZERO).max();
+            assert!((size.width - width).abs() < 1e-9, "{:?}", size);
+            assert!((size.height - height).abs() < 1e-9, "{:?}", size);
+        }
+
+        // The height is the smaller axis.
+        assert_size(SizedBox::empty().square(), 100.0, 100.0);
+        assert_size(SizedBox::empty().standard(), 400.0 / 3.0, 100.0);
+        assert_size(SizedBox::empty().widescreen(), 1600.0 / 9.0, 100.0);
+
+        // With an unbounded width, the size comes from the height.
+        let [square_id] = widget_ids();
+        let widget = Flex::row().with_child_id(SizedBox::empty().square(), square_id);
+        let harness = TestHarness::create_with_size(widget, Size::new(300.0, 50.0));
+        let size = harness.get_widget(square_id).state().layout_rect().size();
+        assert_eq!(size, Size::new(50.0, 50.0));
+    }
+
+    #[test]
+    fn inner_shadow() {
+        let widget = SizedBox::empty()
+            .width(40.0)
+            .height(40.0)
+            .background(Color::WHITE)
+            .inner_shadow((2.0, 2.0), 3.0, Color::BLACK);
+
+        let mut harness = TestHarness::create_with_size(widget, Size::new(40.0, 40.0));
+        let image = harness.render();
+        let red_at = |x: usize, y: usize| image[(y * 40 + x) * 4];
+        // The top left edge is in the shadow, the center and bottom right aren't.
+        assert!(red_at(1, 20) < red_at(20, 20));
+        assert!(red_at(20, 1) < red_at(20, 20));
+        assert!(red_at(38, 20) > red_at(1, 20));
+        assert_eq!(red_at(20, 20), 255);
+    }
+
+    #[test]
+    fn wheel_bubbles_at_scroll_limit() {
+        /// A box which scrolls down to `limit`, and passes on wheel events past it.
+        fn scroll_box(child: impl Widget, offset: Rc<Cell<f64>>, limit: f64) -> SizedBox {
+            SizedBox::new(child).on_wheel(move |_, delta| {
+                let new_offset = (offset.get() + delta.y).clamp(0.0, limit);
+                let scrolled = new_offset != offset.get();
+                offset.set(new_offset);
+                scrolled
+            })
+        }
+
+        let inner_offset = Rc::new(Cell::new(0.0));
+        let outer_offset = Rc::new(Cell::new(0.0));
+        let inner = scroll_box(
+            SizedBox::empty().width(50.0).height(50.0),
+            inner_offset.clone(),
+            15.0,
+        );
+        let outer = scroll_box(inner, outer_offset.clone(), 100.0);
+
+        let mut harness = TestHarness::create_with_size(outer, Size::new(50.0, 50.0));
+        harness.mouse_move((25.0, 25.0));
+
+        harness.mouse_wheel(Vec2::new(0.0, 10.0));
+        assert_eq!((inner_offset.get(), outer_offset.get()), (10.0, 0.0));
+
+        // The inner box reaches its limit, and still uses the event.
+        harness.mouse_wheel(Vec2::new(0.0, 10.0));
+        assert_eq!((inner_offset.get(), outer_offset.get()), (15.0, 0.0));
+
+        // The inner box can't scroll further, so the outer one does.
+        harness.mouse_wheel(Vec2::new(0.0, 10.0));
+        assert_eq!((inner_offset.get(), outer_offset.get()), (15.0, 10.0));
+    }
+
+    #[test]
+    fn snap_child_to_pixels() {
+        fn child_origin(snap: bool) -> Point {
+            let [child_id] = widget_ids();
+            let widget = SizedBox::new_with_id(SizedBox::empty().width(20.0), child_id)
+                .padding(Insets::new(10.4, 0.0, 0.0, 0.0))
+                .snap_child_to_pixels(snap);
+            let widget = Flex::row().with_child(widget);
+
+            let harness = TestHarness::create_with_size(widget, Size::new(100.0, 100.0));
+            harness.get_widget(child_id).state().layout_rect().origin()
+        }
+
+        assert_eq!(child_origin(false).x, 10.4);
+        assert_eq!(child_origin(true).x, 10.0);
+    }
+
+    #[test]
+    fn is_opaque() {
+        let env = Env::with_theme();
+        assert!(BackgroundBrush::from(Color::BLACK).is_opaque(&env));
+        assert!(!BackgroundBrush::from(Color::BLACK.with_alpha(0.5)).is_opaque(&env));
+        assert!(BackgroundBrush::from(theme::BACKGROUND_LIGHT).is_opaque(&env));
+
+        assert!(SizedBox::empty().background(Color::BLACK).is_opaque(&env));
+        assert!(!SizedBox::empty().is_opaque(&env));
+        assert!(!SizedBox::empty()
+            .background(Color::BLACK)
+            .rounded(4.0)
+            .is_opaque(&env));
+        // The edge fade only applies to the child, the background stays opaque.
+        assert!(SizedBox::new(Label::new("Faded"))
+            .background(Color::BLACK)
+            .edge_fade(Sides::ALL, 4.0)
+            .is_opaque(&env));
+        assert!(!SizedBox::empty()
+            .background(Color::BLACK)
+            .opacity(0.5)
+            .is_opaque(&env));
+        assert!(!SizedBox::empty()
+            .background(Color::BLACK)
+            .opacity(0.0)
+            .is_opaque(&env));
+
+        let collapsed = SizedBox::new(Label::new(""))
+            .background(Color::BLACK)
+            .collapse_when_empty(true);
+        let harness =
+            TestHarness::create_with_size(Flex::row().with_child(collapsed), Size::new(40.0, 40.0));
+        let collapsed = harness.root_widget().children()[0];
+        assert!(!collapsed.downcast::<SizedBox>().unwrap().is_opaque(&env));
+
+        const SHOW_BOX: Key<bool> = Key::new("org.linebender.test.show_box");
+        let hidden = SizedBox::empty()
+            .width(10.0)
+            .height(10.0)
+            .background(Color::BLACK)
+            .visible_if(SHOW_BOX);
+        let mut harness =
+            TestHarness::create_with_size(Flex::row().with_child(hidden), Size::new(40.0, 40.0));
+        let is_opaque = |harness: &TestHarness| {
+            let sized_box = harness.root_widget().children()[0];
+            sized_box.downcast::<SizedBox>().unwrap().is_opaque(&env)
+        };
+        harness.set_env_value(SHOW_BOX, true);
+        assert!(is_opaque(&harness));
+        harness.set_env_value(SHOW_BOX, false);
+        assert!(!is_opaque(&harness));
+    }
+
+    #[test]
+    fn style_bundle_same() {
+        let style = StyleBundle {
+            background: Color::WHITE,
+            brush: None,
+            border_color: Color::BLACK,
+            border_width: 1.0,
+            corner_radius: RoundedRectRadii::from(0.0),
+        };
+        assert!(style.same(&style.clone()));
+        assert!(!style.same(&StyleBundle {
+            border_width: 2.0,
+            ..style.clone()
+        }));
+        assert!(!style.same(&StyleBundle {
+            background: Color::BLACK,
+            ..style.clone()
+        }));
+    }
+
+    #[test]
+    fn style_bundle_same_corner_radius() {
+        let style = StyleBundle {
+            background: Color::WHITE,
+            brush: None,
+            border_color: Color::BLACK,
+            border_width: 1.0,
+            corner_radius: RoundedRectRadii::from(4.0),
+        };
+        assert!(!style.same(&StyleBundle {
+            corner_radius: RoundedRectRadii::from(8.0),
+            ..style.clone()
+        }));
+
+        // Brushes are compared too, and painters are always different.
+        let gradient = || {
+            let gradient =
+                LinearGradient::new(UnitPoint::TOP, UnitPoint::BOTTOM, (Color::RED, Color::BLUE));
+            Some(StyleBrush::Brush(PaintBrush::Linear(gradient)))
+        };
+        let with_gradient = StyleBundle {
+            brush: gradient(),
+            ..style.clone()
+        };
+        assert!(with_gradient.same(&StyleBundle {
+            brush: gradient(),
+            ..style.clone()
+        }));
+        assert!(!with_gradient.same(&style));
+        let with_painter = StyleBundle {
+            brush: Some(StyleBrush::Painter(Arc::new(|_, _| {}))),
+            ..style
+        };
+        assert!(!with_painter.same(&with_painter.clone()));
+    }
+
+    #[test]
+    fn shared_width() {
+        let [narrow_id, wide_id, empty_id] = widget_ids();
+        let shared = SharedSize::new();
+        let cell = |width: f64, id: WidgetId| {
+            SizedBox::new(SizedBox::empty().width(width).height(10.0))
+                .with_id(id)
+                .shared_width(shared.clone())
+        };
+        let widget = Flex::column()
+            .with_child(cell(30.0, narrow_id))
+            .with_child(cell(60.0, wide_id))
+            // Boxes without a child take the shared width too.
+            .with_child(
+                SizedBox::empty()
+                    .height(10.0)
+                    .with_id(empty_id)
+                    .shared_width(shared.clone()),
+            );
+
+        let mut harness = TestHarness::create_with_size(widget, Size::new(200.0, 200.0));
+
+        assert_eq!(shared.width(), 60.0);
+        for id in [narrow_id, wide_id, empty_id] {
+            assert_eq!(harness.get_widget(id).state().layout_rect().width(), 60.0);
+        }
+
+        // Removing the widest box from the tree takes it out of the shared width.
+        harness.edit_root_widget(|mut flex, _| {
+            let mut flex = flex.downcast::<Flex>().unwrap();
+            flex.remove_child(1);
+        });
+
+        assert_eq!(shared.width(), 30.0);
+        for id in [narrow_id, empty_id] {
+            assert_eq!(harness.get_widget(id).state().layout_rect().width(), 30.0);
+        }
+    }
+
+    #[test]
+    fn cache_rendering() {
+        let paints = Rc::new(Cell::new(0));
+        let child = ModularWidget::new(paints.clone())
+            .layout_fn(|_, _, bc, _| bc.max())
+            .paint_fn(|paints, ctx, _| {
+                paints.set(paints.get() + 1);
+                let bounds = ctx.size().to_rect();
+                ctx.fill(bounds, &Color::RED);
+            });
+        let widget = SizedBox::new(child)
+            .width(20.0)
+            .height(20.0)
+            .cache_rendering(true);
+
+        let mut harness = TestHarness::create_with_size(widget, Size::new(20.0, 20.0));
+        let first_image = harness.render();
+        assert_eq!(paints.get(), 1);
+
+        // Repainting the box alone draws the cached child.
+        for color in [Color::BLACK, Color::WHITE, Color::BLUE] {
+            harness.edit_root_widget(|mut sized_box, _| {
+                let mut sized_box = sized_box.downcast::<SizedBox>().unwrap();
+                sized_box.set_background(color);
+            });
+            let image = harness.render();
+            assert!(image == first_image);
         }
+        assert_eq!(paints.get(), 1);
+
+        // A new scale factor needs a new bitmap.
+        harness.set_scale(2.0);
+        harness.render();
+        assert_eq!(paints.get(), 2);
+
+        harness.render();
+        assert_eq!(paints.get(), 2);
+        harness.edit_root_widget(|mut sized_box, _| {
+            let mut sized_box = sized_box.downcast::<SizedBox>().unwrap();
+            sized_box.invalidate();
+        });
+        harness.render();
+        assert_eq!(paints.get(), 3);
+    }
+
+    #[test]
+    fn hit_test_inset() {
+        let [box_id] = widget_ids();
+        let menu_pos = Rc::new(Cell::new(None));
+        let sized_box = SizedBox::empty()
+            .width(20.0)
+            .height(20.0)
+            .hit_test_inset(Insets::uniform(-10.0))
+            .on_context_menu({
+                let menu_pos = menu_pos.clone();
+                move |_, pos| menu_pos.set(Some(pos))
+            });
+        let widget = Flex::column()
+            .with_spacer(30.0)
+            .with_child_id(sized_box, box_id);
+
+        let mut harness = TestHarness::create_with_size(widget, Size::new(100.0, 100.0));
+        let box_origin = harness.get_widget(box_id).state().window_origin();
+
+        // Outside of the expanded area.
+        harness.mouse_move(box_origin + (-12.0, 5.0));
+        harness.mouse_button_press(MouseButton::Right);
+        harness.mouse_button_release(MouseButton::Right);
+        assert_eq!(menu_pos.get(), None);
+
+        // Outside of the box, but inside the expanded area.
+        harness.mouse_move(box_origin + (-8.0, 25.0));
+        harness.mouse_button_press(MouseButton::Right);
+        assert_eq!(menu_pos.get(), Some(Point::new(-8.0, 25.0)));
+    }
+
+    #[test]
+    fn min_touch_target() {
+        let [box_id, icon_id] = widget_ids();
+        let menu_pos = Rc::new(Cell::new(None));
+        let icon = SizedBox::empty().width(20.0).height(20.0);
+        let sized_box = SizedBox::new_with_id(icon, icon_id)
+            .min_touch_target()
+            .on_context_menu({
+                let menu_pos = menu_pos.clone();
+                move |_, pos| menu_pos.set(Some(pos))
+            });
+        let widget = Flex::column().with_child_id(sized_box, box_id);
+
+        let mut harness = TestHarness::create_with_size(widget, Size::new(100.0, 100.0));
+        let box_rect = harness.get_widget(box_id).state().layout_rect();
+        let icon_rect = harness.get_widget(icon_id).state().layout_rect();
+        assert_eq!(box_rect.size(), Size::new(44.0, 44.0));
+        assert_eq!(icon_rect, Rect::new(12.0, 12.0, 32.0, 32.0));
+
+        // The corners of the box are outside of the icon, but still hit.
+        let box_origin = harness.get_widget(box_id).state().window_origin();
+        harness.mouse_move(box_origin + (1.0, 1.0));
+        harness.mouse_button_press(MouseButton::Right);
+        assert_eq!(menu_pos.get(), Some(Point::new(1.0, 1.0)));
+        harness.mouse_button_release(MouseButton::Right);
+
+        harness.mouse_move(box_origin + (43.0, 43.0));
+        harness.mouse_button_press(MouseButton::Right);
+        assert_eq!(menu_pos.get(), Some(Point::new(43.0, 43.0)));
+    }
 
-        size
+    #[test]
+    fn min_touch_target_keeps_constraints() {
+        let [box_id] = widget_ids();
+        // The maximum width from `constrain` still applies, whichever order the
+        // builder methods are called in.
+        let wide_child = SizedBox::empty().width(200.0).height(20.0);
+        let sized_box = SizedBox::new(wide_child)
+            .constrain(BoxConstraints::new(Size::ZERO, Size::new(80.0, 80.0)))
+            .min_touch_target();
+        let widget = Flex::column().with_child_id(sized_box, box_id);
+
+        let harness = TestHarness::create_with_size(widget, Size::new(300.0, 300.0));
+        let box_rect = harness.get_widget(box_id).state().layout_rect();
+        assert_eq!(box_rect.size(), Size::new(80.0, 44.0));
     }
 
-    fn paint(&mut self, ctx: &mut PaintCtx, env: &Env) {
-        let corner_radius = self.corner_radius.resolve(env);
+    #[test]
+    fn transform() {
+        let [box_id] = widget_ids();
+        let click_pos = Rc::new(Cell::new(None));
+        let child = SizedBox::empty().width(50.0).height(50.0).on_context_menu({
+            let click_pos = click_pos.clone();
+            move |_, pos| click_pos.set(Some(pos))
+        });
+        let sized_box = SizedBox::new(child).transform(Affine::scale(2.0));
+        let widget = Flex::column().with_child_id(sized_box, box_id);
 
-        if let Some(background) = self.background.as_mut() {
-            let panel = ctx.size().to_rounded_rect(corner_radius);
+        let mut harness = TestHarness::create_with_size(widget, Size::new(100.0, 100.0));
+        let box_origin = harness.get_widget(box_id).state().window_origin();
 
-            trace_span!("paint background").in_scope(|| {
-                ctx.with_save(|ctx| {
-                    ctx.clip(panel);
-                    background.paint(ctx, env);
-                });
-            });
-        }
+        harness.mouse_move(box_origin + (20.0, 30.0));
+        harness.mouse_button_press(MouseButton::Right);
+        assert_eq!(click_pos.get(), Some(Point::new(10.0, 15.0)));
+
+        assert_eq!(
+            harness.get_paint_insets(box_id),
+            Insets::new(0.0, 0.0, 50.0, 50.0)
+        );
+    }
+
+    #[test]
+    fn rotation() {
+        use std::f64::consts::FRAC_PI_2;
 
-        if let Some(border) = &self.border {
-            let border_width = border.width.resolve(env);
-            let border_rect = ctx
-                .size()
-                .to_rect()
-                .inset(border_width / -2.0)
-                .to_rounded_rect(corner_radius);
-            ctx.stroke(border_rect, &border.color.resolve(env), border_width);
+        let [box_id] = widget_ids();
+        let click_pos = Rc::new(Cell::new(None));
+        let child = SizedBox::empty().on_context_menu({
+            let click_pos = click_pos.clone();
+            move |_, pos| click_pos.set(Some(pos))
+        });
+        let sized_box = SizedBox::new(child)
+            .width(100.0)
+            .height(100.0)
+            .rotation(FRAC_PI_2);
+        let widget = Flex::column().with_child_id(sized_box, box_id);
+
+        let mut harness = TestHarness::create_with_size(widget, Size::new(100.0, 100.0));
+        let box_origin = harness.get_widget(box_id).state().window_origin();
+
+        // The top right corner of the box shows the child's top left corner.
+        harness.mouse_move(box_origin + (80.0, 10.0));
+        harness.mouse_button_press(MouseButton::Right);
+        let pos = click_pos.get().unwrap();
+        assert!((pos - Point::new(10.0, 20.0)).hypot() < 1e-9);
+    }
+
+    #[test]
+    fn rotation_paint() {
+        use std::f64::consts::FRAC_PI_2;
+
+        // A strip along the top of the child, rotated a quarter turn, is along the right.
+        let strip = |padding: Insets| {
+            SizedBox::new(SizedBox::empty().background(Color::BLUE))
+                .padding(padding)
+                .width(40.0)
+                .height(40.0)
         };
+        let rotated = SizedBox::new(strip(Insets::new(0.0, 0.0, 0.0, 20.0))).rotation(FRAC_PI_2);
+        let mut harness = TestHarness::create_with_size(rotated, Size::new(40.0, 40.0));
+        let rotated_image = harness.render();
 
-        if let Some(ref mut child) = self.child {
-            child.paint(ctx, env);
-        }
+        let expected = strip(Insets::new(20.0, 0.0, 0.0, 0.0));
+        let mut harness = TestHarness::create_with_size(expected, Size::new(40.0, 40.0));
+        // We don't use assert_eq because we don't want rich assert
+        assert!(harness.render() == rotated_image);
     }
 
-    fn children(&self) -> SmallVec<[WidgetRef<'_, dyn Widget>; 16]> {
-        if let Some(child) = &self.child {
-            smallvec![child.as_dyn()]
-        } else {
-            smallvec![]
-        }
+    #[test]
+    fn env_changed() {
+        let recording = Recording::default();
+        let widget = SizedBox::empty()
+            .width(40.0)
+            .height(40.0)
+            .background(theme::BACKGROUND_LIGHT)
+            .record(&recording);
+        let mut harness = TestHarness::create_with_size(widget, Size::new(40.0, 40.0));
+        let _ = harness.render();
+        recording.clear();
+
+        // A key the box doesn't use leaves it alone.
+        let changed = harness.merge_env(&Env::empty().adding(theme::PRIMARY_DARK, Color::RED));
+        assert!(changed.contains(theme::PRIMARY_DARK));
+        let _ = harness.render();
+        let records = recording.drain();
+        assert!(!records
+            .iter()
+            .any(|record| matches!(record, Record::Layout(_) | Record::Paint)));
+
+        // Its background key relayouts and repaints it.
+        harness.merge_env(&Env::empty().adding(theme::BACKGROUND_LIGHT, Color::RED));
+        let image = harness.render();
+        let records = recording.drain();
+        assert!(records
+            .iter()
+            .any(|record| matches!(record, Record::Layout(_))));
+        assert_eq!(pixel_at(&image, 40, 20, 20), [255, 0, 0, 255]);
     }
 
-    fn make_trace_span(&self) -> Span {
-        trace_span!("SizedBox")
+    #[test]
+    fn env_changed_in_scope() {
+        let recording = Recording::default();
+        let inner = SizedBox::empty()
+            .width(40.0)
+            .height(40.0)
+            .background(theme::BACKGROUND_LIGHT)
+            .record(&recording);
+        let widget = SizedBox::new(inner).env_scope(|env| {
+            let color = env.get(theme::PRIMARY_DARK);
+            env.set(theme::BACKGROUND_LIGHT, color);
+        });
+        let mut harness = TestHarness::create_with_size(widget, Size::new(40.0, 40.0));
+        let _ = harness.render();
+        recording.clear();
+
+        // The scope overrides this key, so the subtree doesn't see it change.
+        harness.merge_env(&Env::empty().adding(theme::BACKGROUND_LIGHT, Color::BLUE));
+        let _ = harness.render();
+        let records = recording.drain();
+        assert!(!records
+            .iter()
+            .any(|record| matches!(record, Record::Layout(_) | Record::Paint)));
+
+        // The scope derives the child's background from this key.
+        harness.merge_env(&Env::empty().adding(theme::PRIMARY_DARK, Color::RED));
+        let image = harness.render();
+        let records = recording.drain();
+        assert!(records
+            .iter()
+            .any(|record| matches!(record, Record::Layout(_))));
+        assert_eq!(pixel_at(&image, 40, 20, 20), [255, 0, 0, 255]);
     }
-}
 
-// --- BackgroundBrush ---
+    #[test]
+    fn missing_keys() {
+        const BACKGROUND: Key<Color> = Key::new("org.linebender.test.background");
+        const BORDER: Key<Color> = Key::new("org.linebender.test.border");
+        const BORDER_WIDTH: Key<f64> = Key::new("org.linebender.test.border-width");
 
-impl BackgroundBrush {
-    /// Draw this brush into a provided [`PaintCtx`].
-    pub fn paint(&mut self, ctx: &mut PaintCtx, env: &Env) {
-        let bounds = ctx.size().to_rect();
-        match self {
-            Self::Color(color) => ctx.fill(bounds, &color.resolve(env)),
-            Self::Linear(grad) => ctx.fill(bounds, grad),
-            Self::Radial(grad) => ctx.fill(bounds, grad),
-            Self::Fixed(grad) => ctx.fill(bounds, grad),
-            Self::PainterFn(painter) => painter(ctx, env),
+        let sized_box = SizedBox::empty()
+            .background(BACKGROUND)
+            .hover_background(BACKGROUND)
+            .border(BORDER, BORDER_WIDTH);
+        let env = Env::empty().adding(BORDER_WIDTH, 2.0);
+        let missing: Vec<_> = sized_box
+            .missing_keys(&env)
+            .iter()
+            .map(|err| err.raw_key().to_string())
+            .collect();
+        assert_eq!(
+            missing,
+            [
+                "org.linebender.test.background",
+                "org.linebender.test.border",
+                "org.masonry.theme.disabled_background_color",
+                "org.masonry.theme.disabled_border_color",
+            ]
+        );
+
+        let env = env
+            .adding(BACKGROUND, Color::RED)
+            .adding(BORDER, Color::BLUE)
+            .adding(theme::DISABLED_BACKGROUND_COLOR, Color::GRAY)
+            .adding(theme::DISABLED_BORDER_COLOR, Color::GRAY);
+        assert!(sized_box.missing_keys(&env).is_empty());
+
+        // Focused borders and link underlines use theme colors.
+        let sized_box = sized_box.focusable(true).as_link(|_| {});
+        let missing: Vec<_> = sized_box
+            .missing_keys(&env)
+            .iter()
+            .map(|err| err.raw_key().to_string())
+            .collect();
+        assert_eq!(
+            missing,
+            [
+                "org.masonry.theme.primary_light",
+                "org.masonry.theme.label_color",
+            ]
+        );
+    }
+
+    #[test]
+    fn icon_button() {
+        let [button_id] = widget_ids();
+        let clicks = Rc::new(Cell::new(0));
+        let icon = SizedBox::empty()
+            .width(16.0)
+            .height(16.0)
+            .background(Color::WHITE);
+        let button = SizedBox::icon_button(24.0, icon).on_click({
+            let clicks = clicks.clone();
+            move |_| clicks.set(clicks.get() + 1)
+        });
+        let widget = Flex::column().with_child_id(button, button_id);
+
+        let mut harness = TestHarness::create_with_size(widget, Size::new(100.0, 100.0));
+        let button_rect = harness.get_widget(button_id).state().window_layout_rect();
+        assert_eq!(button_rect.size(), Size::new(24.0, 24.0));
+        let normal_image = harness.render();
+
+        // Hovering paints the hover background.
+        harness.mouse_move_to(button_id);
+        let hovered_image = harness.render();
+        assert!(hovered_image != normal_image);
+
+        harness.mouse_button_press(MouseButton::Left);
+        harness.mouse_button_release(MouseButton::Left);
+        assert_eq!(clicks.get(), 1);
+
+        // The touch target extends past the box, without painting there.
+        harness.mouse_move(button_rect.center() + Vec2::new(20.0, 0.0));
+        harness.mouse_button_press(MouseButton::Left);
+        harness.mouse_button_release(MouseButton::Left);
+        assert_eq!(clicks.get(), 2);
+        assert!(harness.render() == hovered_image);
+
+        // Releasing the mouse elsewhere cancels the click.
+        harness.mouse_button_press(MouseButton::Left);
+        harness.mouse_move(Point::new(90.0, 90.0));
+        harness.mouse_button_release(MouseButton::Left);
+        assert_eq!(clicks.get(), 2);
+        assert!(harness.render() == normal_image);
+    }
+
+    #[test]
+    fn state_styles() {
+        fn render_plain(background: Color, border: Option<Color>) -> Arc<[u8]> {
+            let mut widget = SizedBox::empty()
+                .width(40.0)
+                .height(40.0)
+                .background(background);
+            if let Some(border) = border {
+                widget = widget.border(border, 4.0);
+            }
+            TestHarness::create_with_size(widget, Size::new(40.0, 40.0)).render()
         }
+
+        let styles = StateStyles::new()
+            .normal(StateStyle::new().background(Color::RED))
+            .focused(StateStyle::new().border(Color::WHITE, 4.0))
+            .hover(StateStyle::new().background(Color::GREEN))
+            .active(StateStyle::new().background(Color::BLUE))
+            .disabled(
+                StateStyle::new()
+                    .background(Color::grey8(0x80))
+                    .border(Color::BLACK, 4.0),
+            );
+        let widget = SizedBox::new(tabber(Size::ZERO))
+            .width(40.0)
+            .height(40.0)
+            .background(Color::AQUA)
+            .focusable(true)
+            .on_click(|_| {})
+            .state_styles(styles);
+        let mut harness = TestHarness::create_with_size(widget, Size::new(40.0, 40.0));
+        // We don't use assert_eq because we don't want rich assert
+        assert!(harness.render() == render_plain(Color::RED, None));
+
+        tab_from_tabber(&mut harness);
+        assert!(harness.render() == render_plain(Color::RED, Some(Color::WHITE)));
+
+        // Hover takes precedence over focus, but only for the background.
+        harness.mouse_move(Point::new(20.0, 20.0));
+        assert!(harness.render() == render_plain(Color::GREEN, Some(Color::WHITE)));
+
+        harness.mouse_button_press(MouseButton::Left);
+        assert!(harness.render() == render_plain(Color::BLUE, Some(Color::WHITE)));
+
+        harness.edit_root_widget(|mut sized_box, _| {
+            let mut sized_box = sized_box.downcast::<SizedBox>().unwrap();
+            sized_box.ctx.set_disabled(true);
+        });
+        assert!(harness.render() == render_plain(Color::grey8(0x80), Some(Color::BLACK)));
+
+        // Without state styles, the box's own background is back.
+        harness.edit_root_widget(|mut sized_box, _| {
+            let mut sized_box = sized_box.downcast::<SizedBox>().unwrap();
+            sized_box.ctx.set_disabled(false);
+            sized_box.clear_state_styles();
+        });
+        harness.mouse_button_release(MouseButton::Left);
+        harness.mouse_move(Point::new(100.0, 100.0));
+        assert!(harness.render() == render_plain(Color::AQUA, None));
     }
-}
 
-impl From<Color> for BackgroundBrush {
-    fn from(src: Color) -> BackgroundBrush {
-        BackgroundBrush::Color(src.into())
+    #[test]
+    fn harness_set_active() {
+        let [box_id] = widget_ids();
+        let clickable_box = || {
+            let styles = StateStyles::new()
+                .normal(StateStyle::new().background(Color::RED))
+                .active(StateStyle::new().background(Color::BLUE));
+            SizedBox::empty()
+                .width(20.0)
+                .height(20.0)
+                .state_styles(styles)
+                .on_click(|_| {})
+                .with_id(box_id)
+        };
+
+        let pressed_image = {
+            let mut harness = TestHarness::create_with_size(clickable_box(), Size::new(20.0, 20.0));
+            harness.mouse_move_to(box_id);
+            harness.mouse_button_press(MouseButton::Left);
+            harness.render()
+        };
+        assert_eq!(pixel_at(&pressed_image, 20, 10, 10), [0, 0, 255, 255]);
+
+        let mut harness = TestHarness::create_with_size(clickable_box(), Size::new(20.0, 20.0));
+        let normal_image = harness.render();
+        assert_eq!(pixel_at(&normal_image, 20, 10, 10), [255, 0, 0, 255]);
+
+        // The box looks pressed without a mouse press.
+        harness.set_active(true);
+        assert!(harness.get_widget(box_id).state().is_active);
+        // We don't use assert_eq because we don't want rich assert
+        assert!(harness.render() == pressed_image);
+
+        harness.set_active(false);
+        assert!(!harness.get_widget(box_id).state().is_active);
+        assert!(harness.render() == normal_image);
+        assert_eq!(harness.pop_action(), None);
     }
-}
 
-impl From<Key<Color>> for BackgroundBrush {
-    fn from(src: Key<Color>) -> BackgroundBrush {
-        BackgroundBrush::Color(src.into())
+    #[test]
+    fn child_origin() {
+        let widget = SizedBox::new(SizedBox::empty().width(40.0).height(40.0))
+            .width(100.0)
+            .height(100.0)
+            .border(Color::BLUE, 10.0)
+            .loosen_child(true);
+
+        let harness = TestHarness::create(widget);
+        let sized_box = harness.root_widget().downcast::<SizedBox>().unwrap();
+        // 10px of border, then half of the 40px left over on each axis.
+        assert_eq!(
+            sized_box.deref().child_origin(),
+            Some(Point::new(30.0, 30.0))
+        );
+
+        let harness = TestHarness::create(SizedBox::empty().width(100.0));
+        let sized_box = harness.root_widget().downcast::<SizedBox>().unwrap();
+        assert_eq!(sized_box.deref().child_origin(), None);
     }
-}
 
-impl From<LinearGradient> for BackgroundBrush {
-    fn from(src: LinearGradient) -> BackgroundBrush {
-        BackgroundBrush::Linear(src)
+    #[test]
+    fn as_link() {
+        let activations = Rc::new(Cell::new(0));
+        let widget = SizedBox::new(tabber(Size::new(40.0, 20.0)))
+            .background(Color::BLACK)
+            .as_link({
+                let activations = activations.clone();
+                move |_| activations.set(activations.get() + 1)
+            });
+
+        let mut harness = TestHarness::create_with_size(widget, Size::new(40.0, 20.0));
+        let not_hovered = harness.render();
+
+        harness.mouse_move(Point::new(20.0, 10.0));
+        let hovered = harness.render();
+        assert!(hovered != not_hovered);
+        assert!(matches!(
+            harness.root_widget().state().cursor_change,
+            CursorChange::Set(Cursor::Pointer)
+        ));
+
+        harness.mouse_button_press(MouseButton::Left);
+        harness.mouse_button_release(MouseButton::Left);
+        assert_eq!(activations.get(), 1);
+
+        // Enter only activates the link while it has focus.
+        let enter = || Event::KeyDown(KeyEvent::for_test(RawMods::None, KbKey::Enter));
+        harness.process_event(enter());
+        assert_eq!(activations.get(), 1);
+        tab_from_tabber(&mut harness);
+        harness.process_event(enter());
+        assert_eq!(activations.get(), 2);
+
+        harness.mouse_move(Point::new(100.0, 100.0));
+        assert!(harness.render() == not_hovered);
     }
-}
 
-impl From<RadialGradient> for BackgroundBrush {
-    fn from(src: RadialGradient) -> BackgroundBrush {
-        BackgroundBrush::Radial(src)
+    #[test]
+    fn child_id() {
+        let [label_id] = widget_ids();
+        let harness = TestHarness::create(SizedBox::new_with_id(Label::new("hello"), label_id));
+        let sized_box = harness.root_widget().downcast::<SizedBox>().unwrap();
+        assert_eq!(sized_box.deref().child_id(), Some(label_id));
+
+        let mut harness = TestHarness::create(SizedBox::empty());
+        harness.edit_root_widget(|mut sized_box, _| {
+            let mut sized_box = sized_box.downcast::<SizedBox>().unwrap();
+            assert_eq!(sized_box.child_id(), None);
+            sized_box.set_child(Label::new("hello"));
+        });
+        let sized_box = harness.root_widget().downcast::<SizedBox>().unwrap();
+        let generated_id = sized_box.deref().child_id().unwrap();
+        assert!(harness
+            .get_widget(generated_id)
+            .downcast::<Label>()
+            .is_some());
     }
-}
 
-impl From<FixedGradient> for BackgroundBrush {
-    fn from(src: FixedGradient) -> BackgroundBrush {
-        BackgroundBrush::Fixed(src)
+    #[test]
+    fn cursor() {
+        let widget = SizedBox::empty()
+            .width(40.0)
+            .height(40.0)
+            .cursor(Cursor::ResizeLeftRight);
+
+        let mut harness = TestHarness::create_with_size(widget, Size::new(40.0, 40.0));
+        assert!(matches!(harness.window().cursor, Cursor::Arrow));
+        harness.mouse_move(Point::new(20.0, 20.0));
+        assert!(matches!(harness.window().cursor, Cursor::ResizeLeftRight));
+        harness.mouse_move(Point::new(100.0, 100.0));
+        assert!(matches!(harness.window().cursor, Cursor::Arrow));
+
+        harness.edit_root_widget(|mut sized_box, _| {
+            let mut sized_box = sized_box.downcast::<SizedBox>().unwrap();
+            sized_box.set_cursor(Cursor::Pointer);
+        });
+        harness.mouse_move(Point::new(20.0, 20.0));
+        assert!(matches!(harness.window().cursor, Cursor::Pointer));
+
+        harness.edit_root_widget(|mut sized_box, _| {
+            let mut sized_box = sized_box.downcast::<SizedBox>().unwrap();
+            sized_box.clear_cursor();
+        });
+        harness.mouse_move(Point::new(20.0, 21.0));
+        assert!(matches!(harness.window().cursor, Cursor::Arrow));
     }
-}
 
-impl<Painter: FnMut(&mut PaintCtx, &Env) + 'static> From<Painter> for BackgroundBrush {
-    fn from(src: Painter) -> BackgroundBrush {
-        BackgroundBrush::PainterFn(Box::new(src))
+    #[test]
+    fn edge_fade() {
+        let widget = SizedBox::new(
+            SizedBox::empty()
+                .width(40.0)
+                .height(40.0)
+                .background(Color::WHITE),
+        )
+        .background(Color::BLACK)
+        .edge_fade(Sides::TOP, 10.0);
+
+        let mut harness = TestHarness::create_with_size(widget, Size::new(40.0, 40.0));
+        let image = harness.render();
+        let red = |y: usize| image[(y * 40 + 20) * 4];
+        // The top 10 pixels go from black to white, and the rest is white.
+        assert!(red(0) < 30);
+        assert!(red(2) < red(5) && red(5) < red(8));
+        assert!(red(5) > 100 && red(5) < 160);
+        assert_eq!(red(10), 255);
+        assert_eq!(red(39), 255);
+
+        harness.edit_root_widget(|mut sized_box, _| {
+            let mut sized_box = sized_box.downcast::<SizedBox>().unwrap();
+            sized_box.set_edge_fade(Sides::BOTTOM | Sides::LEFT, 10.0);
+        });
+        let image = harness.render();
+        let red = |x: usize, y: usize| image[(y * 40 + x) * 4];
+        assert_eq!(red(20, 0), 255);
+        assert!(red(20, 39) < 30);
+        assert!(red(0, 20) < 30);
+        assert_eq!(red(39, 20), 255);
+
+        harness.edit_root_widget(|mut sized_box, _| {
+            let mut sized_box = sized_box.downcast::<SizedBox>().unwrap();
+            sized_box.clear_edge_fade();
+        });
+        let image = harness.render();
+        assert!(image.iter().all(|&byte| byte == 255));
+    }
+
+    #[test]
+    fn collapse_when_empty() {
+        let [label_id] = widget_ids();
+        let widget = SizedBox::new_with_id(Label::new(""), label_id)
+            .border(Color::BLUE, 5.0)
+            .padding(2.0)
+            .collapse_when_empty(true);
+        let widget = Flex::row().with_child(widget);
+
+        let mut harness = TestHarness::create_with_size(widget, Size::new(40.0, 40.0));
+        let collapsed = harness.render();
+        let sized_box = harness.root_widget().children()[0];
+        assert_eq!(sized_box.state().layout_rect().size(), Size::ZERO);
+        assert!(!sized_box
+            .downcast::<SizedBox>()
+            .unwrap()
+            .did_paint_content());
+
+        harness.edit_root_widget(|mut flex, _| {
+            let mut flex = flex.downcast::<Flex>().unwrap();
+            let mut sized_box = flex.child_mut(0).unwrap();
+            let mut sized_box = sized_box.downcast::<SizedBox>().unwrap();
+            sized_box.set_collapse_when_empty(false);
+        });
+        let sized_box = harness.root_widget().children()[0];
+        assert_eq!(
+            sized_box.state().layout_rect().width(),
+            2.0 * 5.0 + 2.0 * 2.0
+        );
+        assert!(harness.render() != collapsed);
+    }
+
+    #[test]
+    fn pin() {
+        let [badge_id] = widget_ids();
+        let badge = SizedBox::empty().width(16.0).height(16.0);
+        let widget = SizedBox::new_with_id(badge, badge_id)
+            .width(100.0)
+            .height(100.0)
+            .border(Color::BLUE, 2.0)
+            .pin(Corner::TopRight, 8.0);
+
+        let mut harness = TestHarness::create_with_size(widget, Size::new(100.0, 100.0));
+        // 100 - 2 (border) - 8 (margin) - 16 (badge) = 74 from the left.
+        let badge_rect = harness.get_widget(badge_id).state().layout_rect();
+        assert_eq!(badge_rect, Rect::new(74.0, 10.0, 90.0, 26.0));
+
+        harness.edit_root_widget(|mut sized_box, _| {
+            let mut sized_box = sized_box.downcast::<SizedBox>().unwrap();
+            sized_box.set_pin(Corner::BottomLeft, Insets::new(4.0, 0.0, 0.0, 6.0));
+        });
+        let badge_rect = harness.get_widget(badge_id).state().layout_rect();
+        assert_eq!(
+            badge_rect.origin(),
+            Point::new(6.0, 100.0 - 2.0 - 6.0 - 16.0)
+        );
+
+        // Without a set size, the box wraps the badge and its margin.
+        let badge = SizedBox::empty().width(16.0).height(16.0);
+        let widget = Flex::row().with_child(SizedBox::new(badge).pin(Corner::BottomRight, 8.0));
+        let harness = TestHarness::create_with_size(widget, Size::new(100.0, 100.0));
+        let sized_box = harness.root_widget().children()[0];
+        assert_eq!(
+            sized_box.state().layout_rect().size(),
+            Size::new(32.0, 32.0)
+        );
+        let sized_box = sized_box.downcast::<SizedBox>().unwrap();
+        assert_eq!(sized_box.child_origin(), Some(Point::new(8.0, 8.0)));
     }
-}
 
-impl From<PaintBrush> for BackgroundBrush {
-    fn from(src: PaintBrush) -> BackgroundBrush {
-        match src {
-            PaintBrush::Linear(grad) => BackgroundBrush::Linear(grad),
-            PaintBrush::Radial(grad) => BackgroundBrush::Radial(grad),
-            PaintBrush::Fixed(grad) => BackgroundBrush::Fixed(grad),
-            PaintBrush::Color(color) => BackgroundBrush::Color(color.into()),
+    #[test]
+    fn visible_if() {
+        const SHOW_BADGE: Key<bool> = Key::new("org.linebender.test.show_badge");
+
+        let [box_id, label_id] = widget_ids();
+        let widget = Flex::row().with_child_id(
+            SizedBox::new_with_id(Label::new("new").with_selectable(true), label_id)
+                .border(Color::BLUE, 2.0)
+                .visible_if(SHOW_BADGE),
+            box_id,
+        );
+
+        // The key isn't in the env yet, so the box is shown.
+        let mut harness = TestHarness::create_with_size(widget, Size::new(100.0, 40.0));
+        assert!(!harness.get_widget(box_id).state().layout_rect().is_empty());
+
+        harness.set_env_value(SHOW_BADGE, true);
+        let shown = harness.render();
+        assert!(!harness.get_widget(box_id).state().layout_rect().is_empty());
+
+        // Select the label's text, to check that it keeps its state while hidden.
+        harness.mouse_click_on(label_id);
+        let select_all = KeyEvent::for_test(RawMods::from(SysMods::Cmd), "a");
+        harness.process_event(Event::KeyDown(select_all));
+        let selected = |harness: &TestHarness| {
+            harness
+                .get_widget(label_id)
+                .downcast::<Label>()
+                .unwrap()
+                .selected_text()
+                .map(str::to_owned)
+        };
+        let selection = selected(&harness);
+        assert_eq!(selection.as_deref(), Some("new"));
+
+        harness.set_env_value(SHOW_BADGE, false);
+        let hidden = harness.render();
+        assert_eq!(
+            harness.get_widget(box_id).state().layout_rect().size(),
+            Size::ZERO
+        );
+        assert!(harness.get_widget(label_id).state().is_stashed);
+        assert!(hidden != shown);
+
+        harness.set_env_value(SHOW_BADGE, true);
+        assert!(!harness.get_widget(label_id).state().is_stashed);
+        assert!(!harness.get_widget(box_id).state().layout_rect().is_empty());
+        assert_eq!(selected(&harness), selection);
+    }
+
+    #[test]
+    fn env_scope() {
+        let [plain_id, scoped_id] = widget_ids();
+        let scoped = SizedBox::new(Label::new("Hello").with_id(scoped_id)).env_scope(|env| {
+            let font = FontDescriptor::new(FontFamily::SYSTEM_UI).with_size(30.0);
+            env.set(theme::UI_FONT, font);
+        });
+        let widget = Flex::row()
+            .with_child(Label::new("Hello").with_id(plain_id))
+            .with_child(scoped);
+
+        let mut harness = TestHarness::create_with_size(widget, Size::new(400.0, 100.0));
+        let plain_size = harness.get_widget(plain_id).state().layout_rect().size();
+        let scoped_size = harness.get_widget(scoped_id).state().layout_rect().size();
+        assert!(scoped_size.width > plain_size.width);
+        assert!(scoped_size.height > plain_size.height);
+
+        // Removing the scope brings the label back to the theme's font.
+        harness.edit_root_widget(|mut flex, _| {
+            let mut flex = flex.downcast::<Flex>().unwrap();
+            let mut child = flex.child_mut(1).unwrap();
+            child.downcast::<SizedBox>().unwrap().clear_env_scope();
+        });
+        let scoped_size = harness.get_widget(scoped_id).state().layout_rect().size();
+        assert_eq!(scoped_size, plain_size);
+    }
+
+    #[test]
+    fn env_scope_is_cached() {
+        const SCOPED: Key<f64> = Key::new("org.linebender.test.scoped");
+        let calls = Rc::new(Cell::new(0));
+        let widget = SizedBox::new(Label::new("Hello")).env_scope({
+            let calls = calls.clone();
+            move |env| {
+                calls.set(calls.get() + 1);
+                env.set(SCOPED, 1.0);
+            }
+        });
+
+        let mut harness = TestHarness::create_with_size(widget, Size::new(100.0, 100.0));
+        harness.render();
+        assert_eq!(calls.get(), 1);
+
+        // Events and repaints reuse the scoped env.
+        harness.mouse_move(Point::new(10.0, 10.0));
+        harness.mouse_move(Point::new(20.0, 20.0));
+        harness.render();
+        assert_eq!(calls.get(), 1);
+
+        // A new parent env is scoped again.
+        harness.set_env_value(theme::TEXT_SIZE_NORMAL, 20.0);
+        assert_eq!(calls.get(), 2);
+    }
+
+    #[test]
+    fn image_background_cache() {
+        let red = ImageBuf::from_raw(vec![255, 0, 0, 255], ImageFormat::RgbaSeparate, 1, 1);
+        let blue = ImageBuf::from_raw(vec![0, 0, 255, 255], ImageFormat::RgbaSeparate, 1, 1);
+        let widget = SizedBox::empty()
+            .width(20.0)
+            .height(20.0)
+            .background(red.clone());
+        let mut harness = TestHarness::create_with_size(widget, Size::new(20.0, 20.0));
+        let cached_source = |harness: &TestHarness| {
+            let sized_box = harness.root_widget();
+            let sized_box = sized_box.downcast::<SizedBox>().unwrap();
+            sized_box
+                .image_cache
+                .as_ref()
+                .map(|cache| cache.source.clone())
+        };
+
+        let image = harness.render();
+        assert_eq!(pixel_at(&image, 20, 10, 10), [255, 0, 0, 255]);
+        assert!(cached_source(&harness).unwrap().ptr_eq(&red));
+
+        // Another image replaces the cached one.
+        harness.edit_root_widget(|mut sized_box, _| {
+            let mut sized_box = sized_box.downcast::<SizedBox>().unwrap();
+            sized_box.set_background(blue.clone());
+        });
+        let image = harness.render();
+        assert_eq!(pixel_at(&image, 20, 10, 10), [0, 0, 255, 255]);
+        assert!(cached_source(&harness).unwrap().ptr_eq(&blue));
+    }
+
+    #[cfg(feature = "png")]
+    #[test]
+    fn png_background() {
+        // A 2x2 image with red, green, blue and white pixels.
+        const PNG: &[u8] = &[
+            0x89, 0x50, 0x4e, 0x47, 0x0d, 0x0a, 0x1a, 0x0a, 0x00, 0x00, 0x00, 0x0d, 0x49, 0x48,
+            0x44, 0x52, 0x00, 0x00, 0x00, 0x02, 0x00, 0x00, 0x00, 0x02, 0x08, 0x02, 0x00, 0x00,
+            0x00, 0xfd, 0xd4, 0x9a, 0x73, 0x00, 0x00, 0x00, 0x11, 0x49, 0x44, 0x41, 0x54, 0x78,
+            0xda, 0x63, 0xf8, 0xcf, 0x00, 0x04, 0x20, 0xe2, 0x3f, 0x10, 0x00, 0x00, 0x1e, 0xef,
+            0x05, 0xfb, 0x35, 0x81, 0xa0, 0xc0, 0x00, 0x00, 0x00, 0x00, 0x49, 0x45, 0x4e, 0x44,
+            0xae, 0x42, 0x60, 0x82,
+        ];
+
+        let brush = BackgroundBrush::from_png_bytes(PNG).unwrap();
+        match &brush {
+            BackgroundBrush::Image(image) => {
+                assert_eq!(image.size(), Size::new(2.0, 2.0));
+                assert_eq!(&image.raw_pixels()[..3], &[255, 0, 0]);
+            }
+            _ => panic!("expected an image brush"),
         }
+        assert!(brush.is_opaque(&Env::empty()));
+        assert!(BackgroundBrush::from_png_bytes(&PNG[..40]).is_err());
+        #[cfg(feature = "jpeg")]
+        assert!(BackgroundBrush::from_jpeg_bytes(PNG).is_err());
+
+        let widget = SizedBox::empty().width(40.0).height(40.0).background(brush);
+        let mut harness = TestHarness::create_with_size(widget, Size::new(40.0, 40.0));
+        let image = harness.render();
+        // Each image pixel covers a quarter of the box, check the middle of each one.
+        let assert_color = |x: usize, y: usize, expected: [u8; 4]| {
+            let actual = pixel_at(&image, 40, x, y);
+            for (actual, expected) in actual.iter().zip(expected) {
+                assert!(
+                    actual.abs_diff(expected) <= 16,
+                    "{:?} != {:?}",
+                    actual,
+                    expected
+                );
+            }
+        };
+        assert_color(10, 10, [255, 0, 0, 255]);
+        assert_color(30, 10, [0, 255, 0, 255]);
+        assert_color(10, 30, [0, 0, 255, 255]);
+        assert_color(30, 30, [255, 255, 255, 255]);
     }
-}
 
-// --- Tests ---
+    #[test]
+    fn aspect_ratio_cover() {
+        let [cover_id] = widget_ids();
+        let cover = SizedBox::empty().aspect_ratio_cover(1.0);
+        let widget = SizedBox::new_with_id(cover, cover_id).clip_child(true);
+        let mut harness = TestHarness::create_with_size(widget, Size::new(400.0, 200.0));
 
-#[cfg(test)]
-mod tests {
-    use insta::assert_debug_snapshot;
+        // The cover overflows vertically, and the clipping box keeps its size.
+        let size = harness.get_widget(cover_id).state().layout_rect().size();
+        assert_eq!(size, Size::new(400.0, 400.0));
+        let size = harness.root_widget().state().layout_rect().size();
+        assert_eq!(size, Size::new(400.0, 200.0));
 
-    use super::*;
-    use crate::assert_render_snapshot;
-    use crate::testing::TestHarness;
-    use crate::widget::Label;
+        harness.edit_root_widget(|mut root, _| {
+            let mut root = root.downcast::<SizedBox>().unwrap();
+            let mut child = root.child_mut().unwrap();
+            child
+                .downcast::<SizedBox>()
+                .unwrap()
+                .set_aspect_ratio_cover(2.0);
+        });
+        let size = harness.get_widget(cover_id).state().layout_rect().size();
+        assert_eq!(size, Size::new(400.0, 200.0));
+    }
 
     #[test]
-    fn expand() {
-        let expand = SizedBox::new(Label::new("hello!")).expand();
-        let bc = BoxConstraints::tight(Size::new(400., 400.)).loosen();
-        let child_bc = expand.child_constraints(&bc);
-        assert_eq!(child_bc.min(), Size::new(400., 400.,));
+    fn accessibility_label() {
+        let widget = SizedBox::new(SizedBox::new(Label::new("Home")))
+            .accessibility_label("Main menu")
+            .accessibility_role(Role::Navigation);
+        let mut harness = TestHarness::create(widget);
+
+        assert_debug_snapshot!(harness.accessibility_tree());
+
+        harness.edit_root_widget(|mut sized_box, _| {
+            let mut sized_box = sized_box.downcast::<SizedBox>().unwrap();
+            sized_box.clear_accessibility_role();
+        });
+        let label = Accessibility {
+            role: Role::Generic,
+            label: Some("Main menu".to_string()),
+        };
+        assert_eq!(harness.root_widget().accessibility(), Some(label));
+        // The label and role aren't shown in the debug text.
+        assert_eq!(harness.root_widget().get_debug_text(), None);
     }
 
     #[test]
-    fn no_width() {
-        let expand = SizedBox::new(Label::new("hello!")).height(200.);
-        let bc = BoxConstraints::tight(Size::new(400., 400.)).loosen();
-        let child_bc = expand.child_constraints(&bc);
-        assert_eq!(child_bc.min(), Size::new(0., 200.,));
-        assert_eq!(child_bc.max(), Size::new(400., 200.,));
+    fn context_menu() {
+        let [box_id] = widget_ids();
+        let menu_pos = Rc::new(Cell::new(None));
+        let sized_box = SizedBox::empty().width(50.0).height(50.0).on_context_menu({
+            let menu_pos = menu_pos.clone();
+            move |_, pos| menu_pos.set(Some(pos))
+        });
+        let widget = Flex::column()
+            .with_spacer(10.0)
+            .with_child_id(sized_box, box_id);
+
+        let mut harness = TestHarness::create_with_size(widget, Size::new(100.0, 100.0));
+        let box_origin = harness.get_widget(box_id).state().window_origin();
+
+        harness.mouse_move(box_origin + (12.0, 34.0));
+        harness.mouse_button_press(MouseButton::Left);
+        harness.mouse_button_release(MouseButton::Left);
+        assert_eq!(menu_pos.get(), None);
+
+        harness.mouse_button_press(MouseButton::Right);
+        assert_eq!(menu_pos.get(), Some(Point::new(12.0, 34.0)));
     }
 
     #[test]
-    fn empty_box() {
+    fn hairline_border() {
+        let widget = SizedBox::empty()
+            .width(10.0)
+            .height(10.0)
+            .background(Color::BLACK)
+            .hairline_border(Color::WHITE);
+
+        let mut harness = TestHarness::create_with_size(widget, Size::new(10.0, 10.0));
+        harness.set_scale(2.0);
+        let image = harness.render();
+        assert_eq!(image.len(), 20 * 20 * 4);
+
+        // Pixels along the middle row, in device pixels.
+        let row: Vec<&[u8]> = image.chunks(4).skip(10 * 20).take(20).collect();
+        assert_eq!(row[0], [255, 255, 255, 255]);
+        assert_eq!(row[1], [0, 0, 0, 255]);
+        assert_eq!(row[18], [0, 0, 0, 255]);
+        assert_eq!(row[19], [255, 255, 255, 255]);
+    }
+
+    #[test]
+    fn style_bundle_resolves_atomically() {
+        const STYLE: Key<Arc<StyleBundle>> = Key::new("org.linebender.test.style");
+        let light = StyleBundle {
+            background: Color::WHITE,
+            brush: None,
+            border_color: Color::BLACK,
+            border_width: 4.0,
+            corner_radius: RoundedRectRadii::from(0.0),
+        };
+        let dark = StyleBundle {
+            background: Color::BLACK,
+            brush: None,
+            border_color: Color::WHITE,
+            border_width: 4.0,
+            corner_radius: RoundedRectRadii::from(0.0),
+        };
+
+        fn painted_pair(harness: &mut TestHarness) -> (Color, Color) {
+            let image = harness.render();
+            let pixel = |x: usize, y: usize| {
+                let offset = (y * 20 + x) * 4;
+                Color::rgba8(
+                    image[offset],
+                    image[offset + 1],
+                    image[offset + 2],
+                    image[offset + 3],
+                )
+            };
+            (pixel(10, 10), pixel(1, 10))
+        }
+
+        let widget = SizedBox::empty().width(20.0).height(20.0);
+        let mut harness = TestHarness::create_with_size(widget, Size::new(20.0, 20.0));
+        harness.set_env_value(STYLE, Arc::new(light.clone()));
+        harness.edit_root_widget(|mut sized_box, _| {
+            let mut sized_box = sized_box.downcast::<SizedBox>().unwrap();
+            sized_box.set_style(STYLE);
+        });
+        assert_eq!(
+            painted_pair(&mut harness),
+            (light.background, light.border_color)
+        );
+
+        // A theme switch is a single env update, so there's no intermediate env in
+        // which only one of the two colors has changed.
+        harness.set_env_value(STYLE, Arc::new(dark.clone()));
+        assert_eq!(
+            painted_pair(&mut harness),
+            (dark.background, dark.border_color)
+        );
+
+        let sized_box = harness.root_widget().downcast::<SizedBox>().unwrap();
+        assert_eq!(sized_box.deref().painted_color, Some(dark.background));
+    }
+
+    #[test]
+    fn background_over_child() {
+        let overlay = || {
+            SizedBox::new(Label::new("hidden"))
+                .width(60.0)
+                .height(30.0)
+                .background(Color::PURPLE)
+                .background_over_child(true)
+        };
+
+        let image_1 = {
+            let mut harness = TestHarness::create_with_size(overlay(), Size::new(60.0, 30.0));
+            harness.render()
+        };
+
+        let image_2 = {
+            let widget = SizedBox::empty()
+                .width(60.0)
+                .height(30.0)
+                .background(Color::PURPLE);
+
+            let mut harness = TestHarness::create_with_size(widget, Size::new(60.0, 30.0));
+            harness.render()
+        };
+
+        let image_3 = {
+            let widget = SizedBox::new(Label::new("hidden"))
+                .width(60.0)
+                .height(30.0)
+                .background(Color::PURPLE);
+
+            let mut harness = TestHarness::create_with_size(widget, Size::new(60.0, 30.0));
+            harness.render()
+        };
+
+        // We don't use assert_eq because we don't want rich assert
+        assert!(image_1 == image_2);
+        assert!(image_1 != image_3);
+    }
+
+    #[test]
+    fn rounded_each() {
         let widget = SizedBox::empty()
             .width(40.0)
             .height(40.0)
-            .border(Color::BLUE, 5.0)
-            .rounded(5.0);
+            .background(Color::PURPLE)
+            .rounded_each([0.0, 5.0, 10.0, 20.0]);
 
-        let mut harness = TestHarness::create(widget);
+        let mut harness = TestHarness::create_with_size(widget, Size::new(60.0, 60.0));
+        let size = harness.root_widget().state().layout_rect().size();
+        let (w, h) = (size.width as usize, size.height as usize);
+        let image = harness.render();
+        let pixel = |x, y| pixel_at(&image, 60, x, y);
+        let purple = [128, 0, 128, 255];
 
-        assert_debug_snapshot!(harness.root_widget());
-        assert_render_snapshot!(harness, "empty_box");
+        // Only the top left corner is square.
+        assert_eq!(pixel(0, 0), purple);
+        assert_ne!(pixel(w - 1, 0), purple);
+        assert_ne!(pixel(w - 1, h - 1), purple);
+        assert_ne!(pixel(0, h - 1), purple);
+
+        // The other corners are rounded by their own radius.
+        assert_eq!(pixel(w - 3, 2), purple);
+        assert_eq!(pixel(w - 5, h - 5), purple);
+        assert_ne!(pixel(4, h - 5), purple);
     }
 
     #[test]
-    fn label_box_no_size() {
+    fn rounded_each_invalid() {
+        let sized_box = SizedBox::empty().rounded_each([-3.0, 5.0, f64::NAN, 20.0]);
+
+        assert_eq!(
+            sized_box.corner_radius,
+            KeyOrValue::Concrete(RoundedRectRadii::new(0.0, 5.0, 0.0, 20.0))
+        );
+    }
+
+    #[test]
+    fn to_child_space() {
         let widget = SizedBox::new(Label::new("hello"))
-            .border(Color::BLUE, 5.0)
-            .rounded(5.0);
+            .width(50.0)
+            .height(50.0)
+            .border(Color::WHITE, 5.0);
 
-        let mut harness = TestHarness::create(widget);
+        let harness = TestHarness::create_with_size(widget, Size::new(50.0, 50.0));
+        let sized_box = harness.root_widget().downcast::<SizedBox>().unwrap();
+        let sized_box = sized_box.deref();
 
-        assert_debug_snapshot!(harness.root_widget());
-        assert_render_snapshot!(harness, "label_box_no_size");
+        assert_eq!(sized_box.to_child_space(Point::new(2.0, 2.0)), None);
+        assert_eq!(sized_box.to_child_space(Point::new(48.0, 25.0)), None);
+        assert_eq!(
+            sized_box.to_child_space(Point::new(25.0, 25.0)),
+            Some(Point::new(20.0, 20.0))
+        );
+        assert_eq!(SizedBox::empty().to_child_space(Point::ZERO), None);
     }
 
     #[test]
-    fn label_box_with_size() {
-        let widget = SizedBox::new(Label::new("hello"))
-            .width(40.0)
-            .height(40.0)
-            .border(Color::BLUE, 5.0)
-            .rounded(5.0);
+    fn set_same_background() {
+        let widget = SizedBox::empty()
+            .width(20.0)
+            .height(20.0)
+            .background(Color::BLACK);
 
-        let mut harness = TestHarness::create(widget);
+        let mut harness = TestHarness::create_with_size(widget, Size::new(20.0, 20.0));
+        harness.render();
 
-        assert_debug_snapshot!(harness.root_widget());
-        assert_render_snapshot!(harness, "label_box_no_size");
+        harness.edit_root_widget(|mut sized_box, _| {
+            let mut sized_box = sized_box.downcast::<SizedBox>().unwrap();
+            sized_box.set_background(Color::RED);
+            assert!(!sized_box.ctx.widget_state.invalid.is_empty());
+        });
+        harness.render();
+
+        harness.edit_root_widget(|mut sized_box, _| {
+            let mut sized_box = sized_box.downcast::<SizedBox>().unwrap();
+            sized_box.set_background(Color::RED);
+            assert!(sized_box.ctx.widget_state.invalid.is_empty());
+        });
     }
 
     // TODO - add screenshot tests for different brush types
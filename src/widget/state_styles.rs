@@ -0,0 +1,176 @@
+// This software is licensed under Apache License 2.0 and distributed on an
+// "as-is" basis without warranties of any kind. See the LICENSE file for
+// details.
+
+//! Per-state overrides for the look of a box.
+
+use smallvec::SmallVec;
+
+use crate::kurbo::RoundedRectRadii;
+use crate::{Color, Env, KeyOrValue, PaintCtx};
+
+/// Overrides for the look of a [`SizedBox`](crate::widget::SizedBox) in one interaction state.
+///
+/// Build one with the builder methods and add it to [`StateStyles`]. Properties left
+/// unset don't override anything.
+#[derive(Debug, Clone, Default)]
+pub struct StateStyle {
+    pub(crate) background: Option<KeyOrValue<Color>>,
+    pub(crate) border: Option<(KeyOrValue<Color>, KeyOrValue<f64>)>,
+    pub(crate) corner_radius: Option<KeyOrValue<RoundedRectRadii>>,
+}
+
+/// Per-state overrides for the look of a [`SizedBox`](crate::widget::SizedBox), see
+/// [`SizedBox::state_styles`](crate::widget::SizedBox::state_styles).
+///
+/// A box can be in several states at once, eg hovered while focused. Each property is
+/// then taken from the first of those states whose [`StateStyle`] sets it, in this
+/// order of precedence:
+///
+/// 1. Disabled
+/// 2. Active, ie pressed
+/// 3. Hover
+/// 4. Focused
+/// 5. Normal, which always applies
+///
+/// Properties which no applicable state sets come from the box's own settings.
+#[derive(Debug, Clone, Default)]
+pub struct StateStyles {
+    normal: StateStyle,
+    hover: StateStyle,
+    active: StateStyle,
+    disabled: StateStyle,
+    focused: StateStyle,
+}
+
+/// The properties of [`StateStyles`] which apply to a box, resolved in the [`Env`].
+#[derive(Default)]
+pub(crate) struct ResolvedStateStyle {
+    pub(crate) background: Option<Color>,
+    /// The border color and width.
+    pub(crate) border: Option<(Color, f64)>,
+    pub(crate) corner_radius: Option<RoundedRectRadii>,
+}
+
+impl StateStyle {
+    /// Create a style which doesn't override anything.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Builder-style method for overriding the background with a solid color.
+    pub fn background(mut self, color: impl Into<KeyOrValue<Color>>) -> Self {
+        self.background = Some(color.into());
+        self
+    }
+
+    /// Builder-style method for overriding the border.
+    ///
+    /// Negative and NaN widths are treated as zero.
+    pub fn border(
+        mut self,
+        color: impl Into<KeyOrValue<Color>>,
+        width: impl Into<KeyOrValue<f64>>,
+    ) -> Self {
+        self.border = Some((color.into(), width.into()));
+        self
+    }
+
+    /// Builder-style method for overriding the corner radius.
+    pub fn rounded(mut self, radius: impl Into<KeyOrValue<RoundedRectRadii>>) -> Self {
+        self.corner_radius = Some(radius.into());
+        self
+    }
+
+    /// The raw [`Env`] keys this style refers to.
+    pub(crate) fn raw_keys(&self) -> impl Iterator<Item = &'static str> + '_ {
+        let background = self.background.as_ref().and_then(KeyOrValue::raw_key);
+        let border = self
+            .border
+            .iter()
+            .flat_map(|(color, width)| color.raw_key().into_iter().chain(width.raw_key()));
+        let corner_radius = self.corner_radius.as_ref().and_then(KeyOrValue::raw_key);
+        background.into_iter().chain(border).chain(corner_radius)
+    }
+}
+
+impl StateStyles {
+    /// Create an empty set of overrides.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Builder-style method for the overrides which always apply.
+    pub fn normal(mut self, style: StateStyle) -> Self {
+        self.normal = style;
+        self
+    }
+
+    /// Builder-style method for the overrides applied while the mouse is over the box.
+    pub fn hover(mut self, style: StateStyle) -> Self {
+        self.hover = style;
+        self
+    }
+
+    /// Builder-style method for the overrides applied while the box is
+    /// [active](crate::EventCtx::is_active), eg pressed with
+    /// [`SizedBox::on_click`](crate::widget::SizedBox::on_click).
+    pub fn active(mut self, style: StateStyle) -> Self {
+        self.active = style;
+        self
+    }
+
+    /// Builder-style method for the overrides applied while the box is disabled.
+    pub fn disabled(mut self, style: StateStyle) -> Self {
+        self.disabled = style;
+        self
+    }
+
+    /// Builder-style method for the overrides applied while the box has focus.
+    pub fn focused(mut self, style: StateStyle) -> Self {
+        self.focused = style;
+        self
+    }
+
+    /// All styles, from the highest precedence to the lowest.
+    pub(crate) fn by_precedence(&self) -> [&StateStyle; 5] {
+        [
+            &self.disabled,
+            &self.active,
+            &self.hover,
+            &self.focused,
+            &self.normal,
+        ]
+    }
+
+    /// The overrides which apply to the box being painted, resolved in `env`.
+    pub(crate) fn resolve(&self, ctx: &PaintCtx, env: &Env) -> ResolvedStateStyle {
+        let applies = [
+            ctx.is_disabled(),
+            ctx.is_active(),
+            ctx.is_hot(),
+            ctx.is_focused(),
+            true,
+        ];
+        let styles: SmallVec<[&StateStyle; 5]> = self
+            .by_precedence()
+            .into_iter()
+            .zip(applies)
+            .filter_map(|(style, applies)| applies.then_some(style))
+            .collect();
+        ResolvedStateStyle {
+            background: styles
+                .iter()
+                .find_map(|style| style.background.as_ref())
+                .map(|color| color.resolve(env)),
+            border: styles
+                .iter()
+                .find_map(|style| style.border.as_ref())
+                .map(|(color, width)| (color.resolve(env), width.resolve(env).max(0.0))),
+            corner_radius: styles
+                .iter()
+                .find_map(|style| style.corner_radius.as_ref())
+                .map(|radius| radius.resolve(env)),
+        }
+    }
+}
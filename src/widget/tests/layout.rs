@@ -6,10 +6,16 @@
 
 #![allow(unused_imports)]
 
-use druid_shell::kurbo::{Insets, Size};
+use std::cell::Cell;
+use std::rc::Rc;
+
+use druid_shell::kurbo::{Insets, Point, Rect, Size};
+use druid_shell::piet::Color;
+use smallvec::smallvec;
 
 use crate::testing::{widget_ids, ModularWidget, TestHarness, TestWidgetExt};
-use crate::widget::{Flex, SizedBox};
+use crate::widget::{Flex, Label, LineBreaking, SizedBox, WidgetPod, MAX_CHILD_LAYOUTS};
+use crate::Widget;
 
 #[test]
 fn layout_simple() {
@@ -72,6 +78,199 @@ fn layout_insets() {
     assert_eq!(parent_paint_rect.y1, BOX_WIDTH + 20.0);
 }
 
+#[test]
+fn read_paint_insets() {
+    const BLUR: f64 = 5.;
+    const OFFSET: (f64, f64) = (2., 2.);
+
+    let [shadow_id, parent_id] = widget_ids();
+
+    // A widget painting a drop shadow, which extends by the blur radius around the
+    // widget, moved by the offset.
+    let shadow_widget = ModularWidget::new(()).layout_fn(|_, ctx, _, _| {
+        ctx.set_paint_insets(Insets::new(
+            BLUR - OFFSET.0,
+            BLUR - OFFSET.1,
+            BLUR + OFFSET.0,
+            BLUR + OFFSET.1,
+        ));
+        Size::new(50., 50.)
+    });
+    let parent_widget = SizedBox::new_with_id(shadow_widget, shadow_id).with_id(parent_id);
+
+    let harness = TestHarness::create(parent_widget);
+
+    assert_eq!(
+        harness.get_paint_insets(shadow_id),
+        Insets::new(3., 3., 7., 7.)
+    );
+    // The parent paints its child's shadow, but doesn't report it as its own insets.
+    assert_eq!(harness.get_paint_insets(parent_id), Insets::ZERO);
+}
+
+#[test]
+fn oscillating_layout_is_halted() {
+    let iterations = Rc::new(Cell::new(0));
+
+    // A child whose size changes each time it's laid out.
+    let child = ModularWidget::new(false).layout_fn(|is_wide, _, _, _| {
+        *is_wide = !*is_wide;
+        if *is_wide {
+            Size::new(20., 20.)
+        } else {
+            Size::new(10., 10.)
+        }
+    });
+
+    // A parent which lays out its child until the child's size settles.
+    let parent = ModularWidget::new(WidgetPod::new(child))
+        .lifecycle_fn(|child, ctx, event, env| child.lifecycle(ctx, event, env))
+        .layout_fn({
+            let iterations = iterations.clone();
+            move |child, ctx, bc, env| {
+                let mut size = child.layout(ctx, bc, env);
+                let mut count = 1;
+                loop {
+                    let new_size = child.layout(ctx, bc, env);
+                    count += 1;
+                    if new_size == size {
+                        break;
+                    }
+                    size = new_size;
+                }
+                iterations.set(count);
+                ctx.place_child(child, Point::ZERO, env);
+                size
+            }
+        })
+        .children_fn(|child| smallvec![child.as_dyn()]);
+
+    let _harness = TestHarness::create(parent);
+    assert_eq!(iterations.get(), MAX_CHILD_LAYOUTS + 1);
+}
+
+#[test]
+fn nested_measuring_containers_are_not_halted() {
+    let leaf_layouts = Rc::new(Cell::new(0_u32));
+
+    let mut widget: Box<dyn Widget> = Box::new(ModularWidget::new(()).layout_fn({
+        let leaf_layouts = leaf_layouts.clone();
+        move |_, _, _, _| {
+            leaf_layouts.set(leaf_layouts.get() + 1);
+            Size::new(10., 10.)
+        }
+    }));
+    // Each container lays out its child twice, eg to measure it first, so the leaf
+    // is laid out far more than MAX_CHILD_LAYOUTS times during the pass.
+    for _ in 0..6 {
+        widget = Box::new(
+            ModularWidget::new(WidgetPod::new(widget))
+                .lifecycle_fn(|child, ctx, event, env| child.lifecycle(ctx, event, env))
+                .layout_fn(|child, ctx, bc, env| {
+                    child.layout(ctx, bc, env);
+                    let size = child.layout(ctx, bc, env);
+                    ctx.place_child(child, Point::ZERO, env);
+                    size
+                })
+                .children_fn(|child| smallvec![child.as_dyn()]),
+        );
+    }
+
+    let _harness = TestHarness::create(widget);
+    assert!(leaf_layouts.get() >= 2_u32.pow(6));
+}
+
+#[test]
+fn measure_candidates() {
+    let heights = Rc::new(Cell::new(Vec::new()));
+
+    let label = Label::new("The quick brown fox jumps over the lazy dog")
+        .with_line_break_mode(LineBreaking::WordWrap);
+    let parent = ModularWidget::new(WidgetPod::new(label))
+        .lifecycle_fn(|child, ctx, event, env| child.lifecycle(ctx, event, env))
+        .layout_fn({
+            let heights = heights.clone();
+            move |child, ctx, bc, env| {
+                let sizes = child.measure_candidates(ctx, &[100., 200., 400.], env);
+                heights.set(sizes.iter().map(|size| size.height).collect());
+                let size = child.layout(ctx, bc, env);
+                ctx.place_child(child, Point::ZERO, env);
+                size
+            }
+        })
+        .children_fn(|child| smallvec![child.as_dyn()]);
+
+    let _harness = TestHarness::create(parent);
+    let heights = heights.take();
+    assert_eq!(heights.len(), 3);
+    assert!(heights.windows(2).all(|pair| pair[0] >= pair[1]));
+    assert!(heights[0] > heights[2]);
+}
+
+#[test]
+fn subtree_bounds() {
+    let [box_id] = widget_ids();
+
+    let child = SizedBox::empty().width(100.).height(60.);
+    let sized_box = SizedBox::new(child).width(40.).height(40.).clip_child(true);
+    let widget = Flex::column()
+        .with_spacer(10.)
+        .with_child_id(sized_box, box_id)
+        .with_flex_spacer(1.0);
+
+    let harness = TestHarness::create(widget);
+
+    let sized_box = harness.get_widget(box_id);
+    assert_eq!(sized_box.state().layout_rect().size(), Size::new(40., 40.));
+    assert_eq!(sized_box.subtree_bounds(), Rect::new(0., 0., 100., 60.));
+
+    // From the column's point of view, the overflow is moved by the box's offset.
+    let box_origin = sized_box.state().layout_rect().origin();
+    let bounds = harness.root_widget().subtree_bounds();
+    assert!(bounds.contains(box_origin + (99., 59.)));
+}
+
 // TODO - insets + flex
 // TODO - viewport
 // TODO - insets + viewport
+
+#[test]
+fn intrinsic_width() {
+    fn measure(widget: SizedBox) -> (f64, f64) {
+        let widths = Rc::new(Cell::new((0., 0.)));
+        let parent = ModularWidget::new(WidgetPod::new(widget))
+            .lifecycle_fn(|child, ctx, event, env| child.lifecycle(ctx, event, env))
+            .layout_fn({
+                let widths = widths.clone();
+                move |child, ctx, bc, env| {
+                    let min = child.min_intrinsic_width(ctx, f64::INFINITY, env);
+                    let max = child.max_intrinsic_width(ctx, f64::INFINITY, env);
+                    widths.set((min, max));
+                    let size = child.layout(ctx, bc, env);
+                    ctx.place_child(child, Point::ZERO, env);
+                    size
+                }
+            })
+            .children_fn(|child| smallvec![child.as_dyn()]);
+
+        let _harness = TestHarness::create(parent);
+        widths.get()
+    }
+
+    // A fixed width already includes the border.
+    let fixed = SizedBox::new(Label::new("Hello"))
+        .width(120.)
+        .border(Color::WHITE, 5.);
+    assert_eq!(measure(fixed), (120., 120.));
+
+    // Otherwise the border and padding are added to the child's width.
+    let child = SizedBox::empty().width(50.);
+    let padded = SizedBox::new(child)
+        .padding(Insets::uniform(10.))
+        .border(Color::WHITE, 5.);
+    assert_eq!(measure(padded), (80., 80.));
+
+    // A box which expands is as wide as its content.
+    let expanded = SizedBox::new(SizedBox::empty().width(50.)).expand_width();
+    assert_eq!(measure(expanded), (50., 50.));
+}
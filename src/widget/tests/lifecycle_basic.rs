@@ -116,3 +116,20 @@ fn register_after_adding_child() {
     assert!(root_state.children.may_contain(&id_2));
     assert!(root_state.children.may_contain(&id_3));
 }
+
+/// Test that the harness lists the widgets of the tree in depth-first order
+#[test]
+fn all_ids() {
+    let [box_id, flex_id, first_id, second_id] = widget_ids();
+    let flex = Flex::row()
+        .with_child_id(SizedBox::empty(), first_id)
+        .with_child_id(SizedBox::empty(), second_id);
+    let widget = SizedBox::new_with_id(flex, flex_id).with_id(box_id);
+
+    let harness = TestHarness::create(widget);
+    let root_id = harness.root_widget().id();
+    assert_eq!(
+        harness.all_ids(),
+        vec![root_id, box_id, flex_id, first_id, second_id]
+    );
+}
@@ -86,6 +86,20 @@ fn check_forget_to_call_place_child() {
     let _harness = TestHarness::create(widget);
 }
 
+#[should_panic(expected = "was laid out but not placed")]
+#[test]
+fn check_child_placed_before_returning() {
+    let widget = make_parent_widget(Flex::row()).layout_fn(|child, ctx, bc, env| {
+        let size = child.layout(ctx, bc, env);
+        // We check for place_child before calling it
+        ctx.debug_check_child_placed(child);
+        ctx.place_child(child, Point::ZERO, env);
+        size
+    });
+
+    let _harness = TestHarness::create(widget);
+}
+
 #[should_panic(expected = "not visited in method paint")]
 #[test]
 fn check_forget_to_recurse_paint() {
@@ -12,8 +12,8 @@ use tracing::{trace_span, Span};
 use crate::event::StatusChange;
 use crate::widget::WidgetRef;
 use crate::{
-    AsAny, BoxConstraints, Env, Event, EventCtx, LayoutCtx, LifeCycle, LifeCycleCtx, PaintCtx,
-    Point, Size, WidgetCtx,
+    Accessibility, AsAny, BoxConstraints, ChangedKeys, Env, Event, EventCtx, LayoutCtx, LifeCycle,
+    LifeCycleCtx, PaintCtx, Point, Size, WidgetCtx,
 };
 
 /// A unique identifier for a single [`Widget`].
@@ -81,6 +81,19 @@ pub trait Widget: AsAny {
     /// changes in the widget graph or in the state of your specific widget.
     fn lifecycle(&mut self, ctx: &mut LifeCycleCtx, event: &LifeCycle, env: &Env);
 
+    /// Update the widget after values in the [`Env`] changed, eg when a theme is reloaded.
+    ///
+    /// `changed` lists the keys whose values changed, and `env` has the new values. This is
+    /// called right before the widget receives [`LifeCycle::EnvChanged`].
+    ///
+    /// The default implementation requests a layout, since the widget may depend on any
+    /// of the keys. Widgets which know which keys they read can override it to only
+    /// invalidate what depends on `changed`.
+    fn env_changed(&mut self, ctx: &mut LifeCycleCtx, changed: &ChangedKeys, env: &Env) {
+        let _ = (changed, env);
+        ctx.request_layout();
+    }
+
     /// Compute layout.
     ///
     /// A leaf widget should determine its size (subject to the provided
@@ -100,6 +113,34 @@ pub trait Widget: AsAny {
     /// The layout strategy is strongly inspired by Flutter.
     fn layout(&mut self, ctx: &mut LayoutCtx, bc: &BoxConstraints, env: &Env) -> Size;
 
+    /// The narrowest width the widget can be laid out at without clipping its content,
+    /// given a maximum `height`, which may be infinite.
+    ///
+    /// This is for containers which size children from their content rather than from
+    /// the space available, eg a table sizing its columns. Call it through
+    /// [`WidgetPod::min_intrinsic_width`](crate::WidgetPod::min_intrinsic_width); the
+    /// `ctx` is the same as the one [`layout`](Self::layout) receives.
+    ///
+    /// The default implementation returns the width the widget picks when laid out
+    /// without a maximum width, like [`max_intrinsic_width`](Self::max_intrinsic_width).
+    /// Widgets which can get narrower than that, eg by wrapping text, should override it.
+    fn min_intrinsic_width(&mut self, ctx: &mut LayoutCtx, height: f64, env: &Env) -> f64 {
+        self.max_intrinsic_width(ctx, height, env)
+    }
+
+    /// The width past which giving the widget more space doesn't change its height,
+    /// given a maximum `height`, which may be infinite.
+    ///
+    /// See [`min_intrinsic_width`](Self::min_intrinsic_width). The default implementation
+    /// lays the widget out without a maximum width, and returns the width it picks;
+    /// [`WidgetPod`](crate::WidgetPod) keeps the results of the real layout intact.
+    /// A widget which fills whatever width it's given has no preferred width: infinite
+    /// widths are reported as 0.
+    fn max_intrinsic_width(&mut self, ctx: &mut LayoutCtx, height: f64, env: &Env) -> f64 {
+        let bc = BoxConstraints::new(Size::ZERO, Size::new(f64::INFINITY, height));
+        self.layout(ctx, &bc, env).width
+    }
+
     /// Paint the widget appearance.
     ///
     /// The [`PaintCtx`] derefs to something that implements the
@@ -143,6 +184,14 @@ pub trait Widget: AsAny {
         None
     }
 
+    /// What assistive technologies, like screen readers, are told about this widget.
+    ///
+    /// Widgets which return `None`, the default, aren't exposed themselves, but their
+    /// children may be. See [`AccessibilityNode::tree`](crate::AccessibilityNode::tree).
+    fn accessibility(&self) -> Option<Accessibility> {
+        None
+    }
+
     // --- Auto-generated implementations ---
 
     /// Return which child, if any, has the given `pos` in its layout rect.
@@ -361,10 +410,22 @@ impl Widget for Box<dyn Widget> {
         self.deref_mut().lifecycle(ctx, event, env);
     }
 
+    fn env_changed(&mut self, ctx: &mut LifeCycleCtx, changed: &ChangedKeys, env: &Env) {
+        self.deref_mut().env_changed(ctx, changed, env);
+    }
+
     fn layout(&mut self, ctx: &mut LayoutCtx, bc: &BoxConstraints, env: &Env) -> Size {
         self.deref_mut().layout(ctx, bc, env)
     }
 
+    fn min_intrinsic_width(&mut self, ctx: &mut LayoutCtx, height: f64, env: &Env) -> f64 {
+        self.deref_mut().min_intrinsic_width(ctx, height, env)
+    }
+
+    fn max_intrinsic_width(&mut self, ctx: &mut LayoutCtx, height: f64, env: &Env) -> f64 {
+        self.deref_mut().max_intrinsic_width(ctx, height, env)
+    }
+
     fn paint(&mut self, ctx: &mut PaintCtx, env: &Env) {
         self.deref_mut().paint(ctx, env);
     }
@@ -385,6 +446,10 @@ impl Widget for Box<dyn Widget> {
         self.deref().get_debug_text()
     }
 
+    fn accessibility(&self) -> Option<Accessibility> {
+        self.deref().accessibility()
+    }
+
     fn as_any(&self) -> &dyn Any {
         self.deref().as_dyn_any()
     }
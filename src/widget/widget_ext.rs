@@ -0,0 +1,103 @@
+// This software is licensed under Apache License 2.0 and distributed on an
+// "as-is" basis without warranties of any kind. See the LICENSE file for
+// details.
+
+//! Convenience methods for wrapping widgets.
+
+use crate::kurbo::RoundedRectRadii;
+use crate::widget::{BackgroundBrush, SizedBox};
+use crate::{Color, Insets, KeyOrValue, Widget};
+
+/// Convenience methods for wrapping any widget in a [`SizedBox`].
+///
+/// Each method wraps the widget in a new `SizedBox`. Calls can still be chained:
+/// since `SizedBox`'s inherent methods take precedence over this trait, every call
+/// after the first configures the same box.
+///
+/// ```
+/// use masonry::widget::{Label, WidgetExt as _};
+/// use masonry::Color;
+///
+/// let label = Label::new("Hello").background(Color::BLACK).border(Color::WHITE, 1.0);
+/// ```
+pub trait WidgetExt: Widget + Sized + 'static {
+    /// Wrap this widget in a [`SizedBox`] with an explicit width and height.
+    fn fixed_size(self, width: f64, height: f64) -> SizedBox {
+        SizedBox::new(self).width(width).height(height)
+    }
+
+    /// Wrap this widget in a [`SizedBox`] with empty space around it.
+    fn padding(self, padding: impl Into<Insets>) -> SizedBox {
+        SizedBox::new(self).padding(padding)
+    }
+
+    /// Wrap this widget in a [`SizedBox`] with a background.
+    fn background(self, brush: impl Into<BackgroundBrush>) -> SizedBox {
+        SizedBox::new(self).background(brush)
+    }
+
+    /// Wrap this widget in a [`SizedBox`] with a border.
+    fn border(
+        self,
+        color: impl Into<KeyOrValue<Color>>,
+        width: impl Into<KeyOrValue<f64>>,
+    ) -> SizedBox {
+        SizedBox::new(self).border(color, width)
+    }
+
+    /// Wrap this widget in a [`SizedBox`] with rounded corners.
+    fn rounded(self, radius: impl Into<KeyOrValue<RoundedRectRadii>>) -> SizedBox {
+        SizedBox::new(self).rounded(radius)
+    }
+}
+
+impl<W: Widget + 'static> WidgetExt for W {}
+
+// --- Tests ---
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::TestHarness;
+    use crate::widget::Label;
+    use crate::{Env, Size};
+
+    fn assert_same_tree(widget_1: SizedBox, widget_2: SizedBox) {
+        let env = Env::with_theme();
+        assert_eq!(widget_1.config_hash(&env), widget_2.config_hash(&env));
+
+        let harness_1 = TestHarness::create_with_size(widget_1, Size::new(100.0, 100.0));
+        let harness_2 = TestHarness::create_with_size(widget_2, Size::new(100.0, 100.0));
+        // The Debug output of layout trees leaves out widget ids.
+        assert_eq!(
+            format!("{:?}", harness_1.layout_tree()),
+            format!("{:?}", harness_2.layout_tree())
+        );
+    }
+
+    #[test]
+    fn fixed_size() {
+        assert_same_tree(
+            Label::new("x").fixed_size(40., 40.),
+            SizedBox::new(Label::new("x")).width(40.).height(40.),
+        );
+    }
+
+    #[test]
+    fn wrappers() {
+        assert_same_tree(
+            Label::new("x").padding(5.0),
+            SizedBox::new(Label::new("x")).padding(5.0),
+        );
+        assert_same_tree(
+            Label::new("x")
+                .background(Color::RED)
+                .border(Color::WHITE, 2.0)
+                .rounded(4.0),
+            SizedBox::new(Label::new("x"))
+                .background(Color::RED)
+                .border(Color::WHITE, 2.0)
+                .rounded(4.0),
+        );
+    }
+}
@@ -4,7 +4,7 @@
 
 use std::collections::VecDeque;
 
-use tracing::{info_span, trace, warn};
+use tracing::{error, info_span, trace, warn};
 
 use crate::contexts::GlobalPassCtx;
 use crate::kurbo::{Affine, Insets, Point, Rect, Shape, Size};
@@ -18,6 +18,22 @@ use crate::{
 
 // TODO - rewrite links in doc
 
+/// The number of times a container can lay out a child during one call to its own
+/// [`layout`](Widget::layout).
+///
+/// Containers may lay out a child more than once, eg to measure it first.
+/// A container which lays out a child until its size settles may loop forever if the
+/// child's size oscillates; past this limit, [`WidgetPod::layout`] logs an error and
+/// returns the child's last size without laying it out again.
+pub const MAX_CHILD_LAYOUTS: u32 = 32;
+
+/// A new id for a call to [`Widget::layout`], never 0.
+fn next_layout_call() -> u64 {
+    use druid_shell::Counter;
+    static LAYOUT_CALL_COUNTER: Counter = Counter::new();
+    LAYOUT_CALL_COUNTER.next_nonzero().get()
+}
+
 /// A container for one widget in the hierarchy.
 ///
 /// Generally, container widgets don't contain other widgets directly,
@@ -165,6 +181,30 @@ impl<W: Widget> WidgetPod<W> {
         self.state.paint_insets
     }
 
+    /// Return how far this widget's content extends beyond its layout rect.
+    ///
+    /// This is zero unless the widget clips its content and reported the overflow
+    /// with [`set_content_overflow`] during its [`layout`] method. Scrolling containers
+    /// can add it to the layout size to get the full content size.
+    ///
+    /// [`set_content_overflow`]: struct.LayoutCtx.html#method.set_content_overflow
+    /// [`layout`]: trait.Widget.html#tymethod.layout
+    pub fn content_overflow(&self) -> Insets {
+        self.state.content_overflow
+    }
+
+    /// Return the hit-testing order of this widget among its overlapping siblings.
+    ///
+    /// This is zero unless the widget called [`set_z_index`] during its [`layout`]
+    /// method. See [`EventCtx::dispatch_by_z_index`].
+    ///
+    /// [`set_z_index`]: struct.LayoutCtx.html#method.set_z_index
+    /// [`layout`]: trait.Widget.html#tymethod.layout
+    /// [`EventCtx::dispatch_by_z_index`]: struct.EventCtx.html#method.dispatch_by_z_index
+    pub fn z_index(&self) -> i32 {
+        self.state.z_index
+    }
+
     /// Given a parents layout size, determine the appropriate paint `Insets`
     /// for the parent.
     ///
@@ -186,6 +226,11 @@ impl<W: Widget> WidgetPod<W> {
         self.state.baseline_offset
     }
 
+    /// The distance from the bottom of this widget to the baseline of its last line.
+    pub fn last_baseline_offset(&self) -> f64 {
+        self.state.last_baseline_offset
+    }
+
     // FIXME - Remove
     /// Return a mutable reference to the inner widget.
     pub(crate) fn widget_mut(&mut self) -> &mut W {
@@ -379,6 +424,7 @@ impl<W: Widget> WidgetPod<W> {
 
         let had_active = self.state.has_active;
         let rect = self.layout_rect();
+        let hit_rect = self.state.hit_rect();
 
         // If we need to replace either the event or its data.
         let mut modified_event = None;
@@ -391,7 +437,7 @@ impl<W: Widget> WidgetPod<W> {
                         &mut self.inner,
                         &mut self.state,
                         parent_ctx.global_state,
-                        rect,
+                        hit_rect,
                         None,
                         env,
                     );
@@ -451,7 +497,7 @@ impl<W: Widget> WidgetPod<W> {
                     &mut self.inner,
                     &mut self.state,
                     parent_ctx.global_state,
-                    rect,
+                    hit_rect,
                     Some(mouse_event.pos),
                     env,
                 );
@@ -469,7 +515,7 @@ impl<W: Widget> WidgetPod<W> {
                     &mut self.inner,
                     &mut self.state,
                     parent_ctx.global_state,
-                    rect,
+                    hit_rect,
                     Some(mouse_event.pos),
                     env,
                 );
@@ -487,7 +533,7 @@ impl<W: Widget> WidgetPod<W> {
                     &mut self.inner,
                     &mut self.state,
                     parent_ctx.global_state,
-                    rect,
+                    hit_rect,
                     Some(mouse_event.pos),
                     env,
                 );
@@ -508,7 +554,7 @@ impl<W: Widget> WidgetPod<W> {
                     &mut self.inner,
                     &mut self.state,
                     parent_ctx.global_state,
-                    rect,
+                    hit_rect,
                     Some(mouse_event.pos),
                     env,
                 );
@@ -767,6 +813,9 @@ impl<W: Widget> WidgetPod<W> {
                 self.state.update_focus_chain = true;
                 self.env = Some(env.clone());
                 self.state.is_new = false;
+                // Widgets added under a disabled ancestor start disabled, since they
+                // missed the DisabledChanged event which disabled their siblings.
+                self.state.ancestor_disabled = parent_ctx.widget_state.is_disabled();
 
                 true
             }
@@ -806,6 +855,14 @@ impl<W: Widget> WidgetPod<W> {
             }
             // This is called by children when going up the widget tree.
             LifeCycle::RequestPanToChild(_) => false,
+            LifeCycle::EnvChanged(changed) => {
+                let mut inner_ctx = LifeCycleCtx {
+                    global_state: parent_ctx.global_state,
+                    widget_state: &mut self.state,
+                };
+                self.inner.env_changed(&mut inner_ctx, changed, env);
+                true
+            }
         };
 
         // widget_pod is a reborrow of `self`
@@ -919,6 +976,32 @@ impl<W: Widget> WidgetPod<W> {
             return Size::ZERO;
         }
 
+        let parent_call = parent_ctx.widget_state.layout_call;
+        if parent_call != 0 && parent_call == self.state.parent_layout_call {
+            self.state.layouts_in_parent_call += 1;
+        } else {
+            self.state.parent_layout_call = parent_call;
+            self.state.layouts_in_parent_call = 1;
+        }
+        let layout_count = self.state.layouts_in_parent_call;
+        if layout_count > MAX_CHILD_LAYOUTS {
+            if layout_count == MAX_CHILD_LAYOUTS + 1 {
+                error!(
+                    "Widget '{}' #{} was laid out more than {} times by a single layout of its parent. Its size may be oscillating; keeping its last size.",
+                    self.inner.short_type_name(),
+                    self.state().id.to_raw(),
+                    MAX_CHILD_LAYOUTS,
+                );
+                parent_ctx.global_state.debug_logger.push_log(
+                    true,
+                    "too many layouts by a single parent layout, skipping layout",
+                );
+            }
+            self.state.is_expecting_place_child_call = true;
+            parent_ctx.global_state.debug_logger.pop_span();
+            return self.state.size;
+        }
+
         // TODO - explain this
         self.mark_as_visited();
         self.check_initialized("layout");
@@ -926,6 +1009,7 @@ impl<W: Widget> WidgetPod<W> {
         self.state.needs_layout = false;
         self.state.needs_window_origin = false;
         self.state.is_expecting_place_child_call = true;
+        self.state.layout_call = next_layout_call();
 
         bc.debug_check(self.inner.short_type_name());
 
@@ -937,6 +1021,9 @@ impl<W: Widget> WidgetPod<W> {
         let _prev_size = self.state.size;
 
         self.state.local_paint_rect = Rect::ZERO;
+        self.state.content_overflow = Insets::ZERO;
+        self.state.hit_test_inset = Insets::ZERO;
+        self.state.z_index = 0;
 
         let new_size = self.call_widget_method_with_checks("layout", |widget_pod| {
             // widget_pod is a reborrow of `self`
@@ -1024,6 +1111,110 @@ impl<W: Widget> WidgetPod<W> {
         }
     }
 
+    /// Measure the widget at each of the given widths, in the current layout pass.
+    ///
+    /// The widget is laid out with each width as a tight width and an unbounded
+    /// height, and the resulting sizes are returned in the same order. This is for
+    /// heuristics which try several widths, eg to find the narrowest width at which
+    /// some text fits in a given number of lines.
+    ///
+    /// Measuring doesn't replace a real layout: the caller must still call
+    /// [`layout`](Self::layout) with the constraints it settles on, and place the
+    /// widget. Each measurement counts as a layout towards [`MAX_CHILD_LAYOUTS`].
+    pub fn measure_candidates(
+        &mut self,
+        parent_ctx: &mut LayoutCtx,
+        widths: &[f64],
+        env: &Env,
+    ) -> Vec<Size> {
+        widths
+            .iter()
+            .map(|&width| {
+                let bc =
+                    BoxConstraints::new(Size::new(width, 0.0), Size::new(width, f64::INFINITY));
+                self.layout(parent_ctx, &bc, env)
+            })
+            .collect()
+    }
+
+    /// The narrowest width the widget can be laid out at, in the current layout pass.
+    ///
+    /// See [`Widget::min_intrinsic_width`]. Like [`measure_candidates`](Self::measure_candidates),
+    /// this doesn't replace a real layout.
+    pub fn min_intrinsic_width(
+        &mut self,
+        parent_ctx: &mut LayoutCtx,
+        height: f64,
+        env: &Env,
+    ) -> f64 {
+        self.measure_intrinsic_width(parent_ctx, "min_intrinsic_width", |widget, ctx| {
+            widget.min_intrinsic_width(ctx, height, env)
+        })
+    }
+
+    /// The widest width the widget makes use of, in the current layout pass.
+    ///
+    /// See [`Widget::max_intrinsic_width`]. Like [`measure_candidates`](Self::measure_candidates),
+    /// this doesn't replace a real layout.
+    pub fn max_intrinsic_width(
+        &mut self,
+        parent_ctx: &mut LayoutCtx,
+        height: f64,
+        env: &Env,
+    ) -> f64 {
+        self.measure_intrinsic_width(parent_ctx, "max_intrinsic_width", |widget, ctx| {
+            widget.max_intrinsic_width(ctx, height, env)
+        })
+    }
+
+    fn measure_intrinsic_width(
+        &mut self,
+        parent_ctx: &mut LayoutCtx,
+        method_name: &str,
+        measure: impl FnOnce(&mut W, &mut LayoutCtx) -> f64,
+    ) -> f64 {
+        if self.state.is_stashed {
+            debug_panic!(
+                "Error in '{}' #{}: trying to measure stashed widget.",
+                self.inner.short_type_name(),
+                self.state().id.to_raw(),
+            );
+            return 0.0;
+        }
+        self.mark_as_visited();
+        self.check_initialized(method_name);
+
+        let inner_mouse_pos = parent_ctx
+            .mouse_pos
+            .map(|pos| pos - self.layout_rect().origin().to_vec2());
+        // Measuring may lay the widget out, eg in the default implementation, but
+        // mustn't change the results of the real layout.
+        let saved_layout = SavedLayout::save(&self.state);
+        self.state.layout_call = next_layout_call();
+        let width = self.call_widget_method_with_checks(method_name, |widget_pod| {
+            let mut inner_ctx = LayoutCtx {
+                widget_state: &mut widget_pod.state,
+                global_state: parent_ctx.global_state,
+                mouse_pos: inner_mouse_pos,
+            };
+            let width = measure(&mut widget_pod.inner, &mut inner_ctx);
+            // A widget doesn't need to measure all of its children, eg if it has
+            // a fixed width.
+            for child in widget_pod.inner.children() {
+                child.state().mark_as_visited(true);
+            }
+            width
+        });
+        saved_layout.restore(&mut self.state);
+
+        if width.is_finite() {
+            width
+        } else {
+            // The widget fills whatever width it's given, so it has no preferred width.
+            0.0
+        }
+    }
+
     // --- PAINT ---
 
     // TODO - make non-pub?
@@ -1070,6 +1261,7 @@ impl<W: Widget> WidgetPod<W> {
             }
 
             ctx.z_ops.append(&mut inner_ctx.z_ops);
+            widget_pod.state.needs_paint = false;
         });
     }
 
@@ -1173,3 +1365,42 @@ fn rect_contains(larger: &Rect, smaller: &Rect) -> bool {
         && smaller.y0 >= larger.y0
         && smaller.y1 <= larger.y1
 }
+
+/// The parts of a [`WidgetState`] which [`Widget::layout`] sets, saved while a widget
+/// is measured.
+struct SavedLayout {
+    size: Size,
+    paint_insets: Insets,
+    content_overflow: Insets,
+    hit_test_inset: Insets,
+    z_index: i32,
+    local_paint_rect: Rect,
+    baseline_offset: f64,
+    last_baseline_offset: f64,
+}
+
+impl SavedLayout {
+    fn save(state: &WidgetState) -> Self {
+        SavedLayout {
+            size: state.size,
+            paint_insets: state.paint_insets,
+            content_overflow: state.content_overflow,
+            hit_test_inset: state.hit_test_inset,
+            z_index: state.z_index,
+            local_paint_rect: state.local_paint_rect,
+            baseline_offset: state.baseline_offset,
+            last_baseline_offset: state.last_baseline_offset,
+        }
+    }
+
+    fn restore(self, state: &mut WidgetState) {
+        state.size = self.size;
+        state.paint_insets = self.paint_insets;
+        state.content_overflow = self.content_overflow;
+        state.hit_test_inset = self.hit_test_inset;
+        state.z_index = self.z_index;
+        state.local_paint_rect = self.local_paint_rect;
+        state.baseline_offset = self.baseline_offset;
+        state.last_baseline_offset = self.last_baseline_offset;
+    }
+}
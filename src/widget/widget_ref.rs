@@ -6,7 +6,7 @@ use std::ops::Deref;
 
 use smallvec::SmallVec;
 
-use crate::kurbo::Point;
+use crate::kurbo::{Point, Rect};
 use crate::{Widget, WidgetId, WidgetState};
 
 /// A rich reference to a [`Widget`].
@@ -154,6 +154,23 @@ impl<'w> WidgetRef<'w, dyn Widget> {
         }
     }
 
+    /// Return the union of the layout rects of this widget and all its descendants.
+    ///
+    /// The rect is in this widget's coordinate space, so it's this widget's
+    /// [`layout_rect`](WidgetState::layout_rect) at the origin, grown to cover
+    /// descendants which overflow it, eg the child of a `SizedBox` with
+    /// [`clip_child`](crate::widget::SizedBox::clip_child). Stashed widgets are skipped.
+    pub fn subtree_bounds(&self) -> Rect {
+        let own_rect = self.state().size().to_rect();
+        self.children()
+            .into_iter()
+            .filter(|child| !child.state().is_stashed)
+            .fold(own_rect, |bounds, child| {
+                let origin = child.state().layout_rect().origin().to_vec2();
+                bounds.union(child.subtree_bounds() + origin)
+            })
+    }
+
     /// Recursively check that the Widget tree upholds various invariants.
     ///
     /// Can only be called after on_event and lifecycle.
@@ -9,7 +9,7 @@ use std::sync::atomic::{AtomicBool, Ordering};
 use druid_shell::{Cursor, Region};
 
 use crate::bloom::Bloom;
-use crate::kurbo::{Insets, Point, Rect, Size};
+use crate::kurbo::{Insets, Point, Rect, RoundedRectRadii, Size};
 use crate::text::TextFieldRegistration;
 use crate::widget::{CursorChange, FocusChange};
 use crate::WidgetId;
@@ -53,6 +53,19 @@ pub struct WidgetState {
     /// In general, these will be zero; the exception is for things like
     /// drop shadows or overflowing text.
     pub(crate) paint_insets: Insets,
+    /// How far the widget's content extends beyond its layout rect, eg because it
+    /// clips a larger child. Reset before each layout.
+    pub(crate) content_overflow: Insets,
+    /// How far inside the layout rect the area that receives mouse events starts.
+    /// Negative values make that area larger than the layout rect. Reset before each
+    /// layout.
+    pub(crate) hit_test_inset: Insets,
+    /// The corner radii of the rounded area the parent draws this widget in, if any.
+    /// Set by the parent during layout.
+    pub(crate) parent_corner_radii: RoundedRectRadii,
+    /// Where the widget stands in the hit-testing order among its overlapping siblings.
+    /// Reset before each layout.
+    pub(crate) z_index: i32,
     // TODO - Document
     // The computed paint rect, in local coordinates.
     pub(crate) local_paint_rect: Rect,
@@ -62,6 +75,11 @@ pub struct WidgetState {
     /// the baseline. Widgets that contain text or controls that expect to be
     /// laid out alongside text can set this as appropriate.
     pub(crate) baseline_offset: f64,
+    /// The offset of the last baseline relative to the bottom of the widget.
+    ///
+    /// This is the same as `baseline_offset`, unless the widget contains several
+    /// lines of text.
+    pub(crate) last_baseline_offset: f64,
     // TODO - Document
     pub(crate) is_portal: bool,
 
@@ -70,6 +88,12 @@ pub struct WidgetState {
     // TODO: consider using bitflags for the booleans.
     // The region that needs to be repainted, relative to the widget's bounds.
     pub(crate) invalid: Region,
+    /// This widget or one of its descendants asked to be repainted, and hasn't been
+    /// painted since.
+    ///
+    /// Unlike `invalid`, this isn't cleared when merged up, so a parent can see it
+    /// during paint.
+    pub(crate) needs_paint: bool,
     /// A flag used to track and debug missing calls to place_child.
     pub(crate) is_expecting_place_child_call: bool,
 
@@ -129,6 +153,13 @@ pub struct WidgetState {
     // TODO - document
     pub(crate) is_stashed: bool,
 
+    /// Identifies the widget's current or last call to `layout`, or 0 if there was none.
+    pub(crate) layout_call: u64,
+    /// The parent's `layout` call during which the widget was last laid out.
+    pub(crate) parent_layout_call: u64,
+    /// How many times the widget was laid out during `parent_layout_call`.
+    pub(crate) layouts_in_parent_call: u32,
+
     // --- DEBUG INFO ---
     // Used in event/lifecycle/etc methods that are expected to be called recursively
     // on a widget's children, to make sure each child was visited.
@@ -153,14 +184,20 @@ impl WidgetState {
             size: size.unwrap_or_default(),
             is_expecting_place_child_call: false,
             paint_insets: Insets::ZERO,
+            content_overflow: Insets::ZERO,
+            hit_test_inset: Insets::ZERO,
+            parent_corner_radii: RoundedRectRadii::from_single_radius(0.0),
+            z_index: 0,
             local_paint_rect: Rect::ZERO,
             invalid: Region::EMPTY,
+            needs_paint: true,
             is_portal: false,
             is_new: true,
             children_disabled_changed: false,
             ancestor_disabled: false,
             is_explicitly_disabled: false,
             baseline_offset: 0.0,
+            last_baseline_offset: 0.0,
             is_hot: false,
             needs_layout: false,
             needs_window_origin: false,
@@ -178,6 +215,9 @@ impl WidgetState {
             text_registrations: Vec::new(),
             update_focus_chain: false,
             is_stashed: false,
+            layout_call: 0,
+            parent_layout_call: 0,
+            layouts_in_parent_call: 0,
             #[cfg(debug_assertions)]
             needs_visit: VisitBool(false.into()),
             #[cfg(debug_assertions)]
@@ -233,6 +273,7 @@ impl WidgetState {
         // invalid rects.
         child_state.invalid.clear();
 
+        self.needs_paint |= child_state.needs_paint;
         self.needs_layout |= child_state.needs_layout;
         self.needs_window_origin |= child_state.needs_window_origin;
         self.request_anim |= child_state.request_anim;
@@ -289,6 +330,14 @@ impl WidgetState {
         Rect::from_origin_size(self.origin, self.size)
     }
 
+    /// The area which receives mouse events, in the parent's coordinate space.
+    ///
+    /// This is the layout rect, unless the widget set a hit-test inset with
+    /// [`LayoutCtx::set_hit_test_inset`](crate::LayoutCtx::set_hit_test_inset).
+    pub fn hit_rect(&self) -> Rect {
+        self.layout_rect().inset(-self.hit_test_inset)
+    }
+
     /// The [layout_rect](crate::WidgetPod::layout_rect) in window coordinates.
     ///
     /// This might not map to a visible area of the screen, eg if the widget is scrolled